@@ -0,0 +1,323 @@
+// A table of every day, with the solvers run in the background and the table
+// filled in as they finish.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use termion::{clear, cursor, is_tty};
+
+use crate::solver::{Run, Solver};
+use crate::Error;
+
+// How many characters of an answer fit in the table.
+const ANSWER_WIDTH: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Status {
+    Missing,
+    NoInput,
+    Waiting,
+    Running,
+    Done,
+    Failed,
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Missing => "missing",
+            Status::NoInput => "no input",
+            Status::Waiting => "waiting",
+            Status::Running => "running",
+            Status::Done => "done",
+            Status::Failed => "failed",
+        }
+    }
+}
+
+struct Row {
+    day: u8,
+    status: Status,
+    // The answer, or what went wrong if the solver failed.
+    answer: Option<String>,
+    expected: Option<String>,
+    elapsed: Option<Duration>,
+}
+
+impl Row {
+    fn check(&self) -> &'static str {
+        match (&self.status, &self.answer, &self.expected) {
+            (Status::Done, Some(answer), Some(expected)) if tokens(answer) == tokens(expected) => {
+                "correct"
+            }
+            (Status::Done, Some(_), Some(_)) => "wrong",
+            _ => "",
+        }
+    }
+}
+
+// The days still to run, with their input.
+type Queue = Arc<Mutex<Vec<(Solver, Option<PathBuf>)>>>;
+
+enum Update {
+    Running(u8),
+    Finished(u8, Result<Run, Error>),
+}
+
+// The lines a solver printed, on one line.
+fn answer(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    lines.join(", ")
+}
+
+// The separate answers in an answer, so that "212, 358" from a solver matches
+// "212 358" in the answers file.
+fn tokens(answer: &str) -> Vec<&str> {
+    answer
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+// The expected answers, a line for each day like "7 19539216", with the
+// answers separated by spaces for a day with more than one.  Blank lines and
+// lines starting with # are ignored.
+fn parse_answers(text: &str) -> Result<HashMap<u8, String>, Error> {
+    let mut answers = HashMap::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (day, answer) = line
+            .split_once(' ')
+            .and_then(|(day, answer)| Some((day.parse().ok()?, answer.trim())))
+            .ok_or_else(|| format!("Bad line in answers: {:?}", line))?;
+        answers.insert(day, answer.to_string());
+    }
+    Ok(answers)
+}
+
+// The arguments to run a day with so that it only prints the answers.
+fn arguments(day: u8) -> Vec<String> {
+    let args: &[&str] = match day {
+        25 => &["--solve", "--quiet"],
+        _ => &["--quiet"],
+    };
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn shorten(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        text.chars().take(width - 3).collect::<String>() + "..."
+    }
+}
+
+fn table(rows: &[Row]) -> String {
+    let mut text = format!(
+        "{:>3}  {:<8}  {:<width$}  {:<7}  {}\n",
+        "Day",
+        "Status",
+        "Answer",
+        "Check",
+        "Time",
+        width = ANSWER_WIDTH
+    );
+    for row in rows {
+        let answer = shorten(row.answer.as_deref().unwrap_or(""), ANSWER_WIDTH);
+        let elapsed = row
+            .elapsed
+            .map(|elapsed| format!("{:.3}s", elapsed.as_secs_f64()))
+            .unwrap_or_default();
+        let line = format!(
+            "{:>3}  {:<8}  {:<width$}  {:<7}  {}",
+            row.day,
+            row.status.name(),
+            answer,
+            row.check(),
+            elapsed,
+            width = ANSWER_WIDTH
+        );
+        text += line.trim_end();
+        text += "\n";
+    }
+
+    let count = |check| rows.iter().filter(|row| row.check() == check).count();
+    let failed = rows.iter().filter(|row| row.status == Status::Failed).count();
+    text += &format!(
+        "\n{} correct, {} wrong, {} failed\n",
+        count("correct"),
+        count("wrong"),
+        failed
+    );
+    text
+}
+
+// Take days off the queue and run them until there are none left.
+fn work(queue: Queue, updates: Sender<Update>) {
+    loop {
+        let next = queue.lock().unwrap().pop();
+        let (solver, input) = match next {
+            Some(next) => next,
+            None => return,
+        };
+
+        let _ = updates.send(Update::Running(solver.day));
+        let run = solver.run(input.as_deref(), &arguments(solver.day), true);
+        let _ = updates.send(Update::Finished(solver.day, run));
+    }
+}
+
+// Run each of `days` with its input from `inputs`, like inputs/day-07.txt,
+// `jobs` at a time.  The table is redrawn as each one finishes if it's going
+// to a terminal, or printed once at the end if not.
+pub fn dashboard(
+    root: &Path,
+    days: &[u8],
+    inputs: &Path,
+    answers: Option<&Path>,
+    jobs: usize,
+) -> Result<(), Error> {
+    let expected = match answers {
+        Some(path) => parse_answers(&fs::read_to_string(path)?)?,
+        None => HashMap::new(),
+    };
+
+    let mut rows = vec![];
+    let mut queue = vec![];
+    for &day in days {
+        let input = inputs.join(format!("day-{:02}.txt", day));
+        let input = if input.is_file() { Some(input) } else { None };
+        let status = match Solver::new(root, day) {
+            Err(_) => Status::Missing,
+            Ok(_) if input.is_none() => Status::NoInput,
+            Ok(solver) => {
+                queue.push((solver, input));
+                Status::Waiting
+            }
+        };
+        rows.push(Row {
+            day,
+            status,
+            answer: None,
+            expected: expected.get(&day).cloned(),
+            elapsed: None,
+        });
+    }
+
+    // The queue is taken from the back, so reverse it to run days in order.
+    queue.reverse();
+    let queue = Arc::new(Mutex::new(queue));
+    let (sender, updates) = channel();
+    for _ in 0..jobs.max(1) {
+        let queue = queue.clone();
+        let sender = sender.clone();
+        thread::spawn(move || work(queue, sender));
+    }
+    drop(sender);
+
+    let live = is_tty(&stdout());
+    let draw = |rows: &[Row]| {
+        print!("{}{}{}", clear::All, cursor::Goto(1, 1), table(rows));
+        let _ = stdout().flush();
+    };
+    if live {
+        draw(&rows);
+    }
+
+    for update in updates {
+        let (day, status, answer, elapsed) = match update {
+            Update::Running(day) => (day, Status::Running, None, None),
+            Update::Finished(day, Ok(run)) if run.succeeded => {
+                (day, Status::Done, Some(answer(&run.output)), Some(run.elapsed))
+            }
+            Update::Finished(day, Ok(run)) => {
+                let error = run.errors.lines().rev().find(|line| !line.trim().is_empty());
+                (day, Status::Failed, error.map(str::to_string), Some(run.elapsed))
+            }
+            Update::Finished(day, Err(Error(message))) => {
+                (day, Status::Failed, Some(message), None)
+            }
+        };
+
+        if let Some(row) = rows.iter_mut().find(|row| row.day == day) {
+            row.status = status;
+            row.answer = answer;
+            row.elapsed = elapsed;
+        }
+        if live {
+            draw(&rows);
+        }
+    }
+
+    if !live {
+        print!("{}", table(&rows));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn row(day: u8, status: Status, answer: Option<&str>, expected: Option<&str>) -> Row {
+        Row {
+            day,
+            status,
+            answer: answer.map(str::to_string),
+            expected: expected.map(str::to_string),
+            elapsed: Some(Duration::from_millis(1500)).filter(|_| answer.is_some()),
+        }
+    }
+
+    #[test]
+    fn answers() {
+        assert_eq!(answer("212\n\n358\n"), "212, 358");
+        assert_eq!(answer("  Distance: 86 \n"), "Distance: 86");
+
+        let answers = parse_answers("# Mine\n1 3287899\n\n15 212 358\n").unwrap();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[&1], "3287899");
+        assert_eq!(answers[&15], "212 358");
+        assert!(parse_answers("one 2\n").is_err());
+        assert!(parse_answers("7\n").is_err());
+
+        assert_eq!(tokens(&answer("212\n358\n")), tokens(&answers[&15]));
+        assert_ne!(tokens("212, 358"), tokens("212 359"));
+    }
+
+    #[test]
+    fn tables() {
+        let rows = [
+            row(1, Status::Done, Some("3287899"), Some("3287899")),
+            row(2, Status::Done, Some("1202"), Some("1203")),
+            row(3, Status::Failed, Some("Lines do not intersect"), None),
+            row(4, Status::Running, None, None),
+            row(5, Status::NoInput, None, Some("1")),
+            row(15, Status::Done, Some("212, 358"), Some("212 358")),
+        ];
+        let expected = format!(
+            "Day  Status    {:32}  Check    Time\n\
+             \x20 1  done      {:32}  correct  1.500s\n\
+             \x20 2  done      {:32}  wrong    1.500s\n\
+             \x20 3  failed    {:32}           1.500s\n\
+             \x20 4  running\n\
+             \x20 5  no input\n\
+             \x2015  done      {:32}  correct  1.500s\n\
+             \n\
+             2 correct, 1 wrong, 1 failed\n",
+            "Answer", "3287899", "1202", "Lines do not intersect", "212, 358"
+        );
+        assert_eq!(table(&rows), expected);
+
+        assert_eq!(shorten("abcdef", 6), "abcdef");
+        assert_eq!(shorten("abcdefg", 6), "abc...");
+    }
+}
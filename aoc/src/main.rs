@@ -0,0 +1,177 @@
+// Tools for working with the solvers for every day at once.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use structopt::StructOpt;
+
+use common::gen;
+use intcode::trace;
+
+mod dashboard;
+mod solver;
+mod watch;
+
+use solver::Solver;
+
+#[derive(Debug)]
+pub struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Re-run a day's solver every time its input changes
+    Watch {
+        /// Which day to run
+        day: u8,
+
+        /// Input to pass to the solver on stdin
+        #[structopt(short, long, parse(from_os_str))]
+        input: Option<PathBuf>,
+
+        /// Another file the solver reads to watch, such as a springscript
+        #[structopt(short, long, parse(from_os_str), number_of_values = 1)]
+        watch: Vec<PathBuf>,
+
+        /// Milliseconds between checking for changes
+        #[structopt(long, default_value = "500")]
+        interval: u64,
+
+        /// Where the solvers are, if not where this was built from
+        #[structopt(long, parse(from_os_str))]
+        root: Option<PathBuf>,
+
+        /// Arguments for the solver
+        #[structopt(last = true)]
+        args: Vec<String>,
+    },
+
+    /// Run every day in the background, showing a table of their answers
+    Dashboard {
+        /// Which days to run, if not all of them
+        days: Vec<u8>,
+
+        /// Directory with the input for each day, like day-07.txt
+        #[structopt(long, parse(from_os_str))]
+        inputs: Option<PathBuf>,
+
+        /// File with the expected answers for each day, a line each like "7 19539216",
+        /// or "15 212 358" for a day with more than one answer, in the order the
+        /// day prints them
+        #[structopt(long, parse(from_os_str))]
+        answers: Option<PathBuf>,
+
+        /// How many solvers to run at once
+        #[structopt(short, long)]
+        jobs: Option<usize>,
+
+        /// Where the solvers are, if not where this was built from
+        #[structopt(long, parse(from_os_str))]
+        root: Option<PathBuf>,
+    },
+
+    /// Make up input for a day, for days 3, 4, 6 and 12
+    Gen {
+        /// Which day to make input for
+        day: u8,
+
+        /// Seed for the input, the same seed gives the same input
+        #[structopt(long, default_value = "0")]
+        seed: u64,
+
+        /// How big to make the input, defaults to the size of a real one
+        #[structopt(long)]
+        size: Option<usize>,
+    },
+
+    /// Show where two traces of an Intcode program, like from day 9's --trace, first differ
+    TraceDiff {
+        #[structopt(parse(from_os_str))]
+        first: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        second: PathBuf,
+
+        /// How many lines to show around the difference
+        #[structopt(short, long, default_value = "5")]
+        context: usize,
+    },
+}
+
+// Print where the traces differ, returning whether they're the same.
+fn trace_diff(first: &Path, second: &Path, context: usize) -> Result<bool, Error> {
+    let read = |path: &Path| {
+        fs::read_to_string(path)
+            .map_err(|error| format!("Can't read {}: {}", path.display(), error))
+    };
+    match trace::diff(&read(first)?, &read(second)?, context) {
+        Some(report) => {
+            print!("{}", report);
+            Ok(false)
+        }
+        None => {
+            println!("The traces are the same");
+            Ok(true)
+        }
+    }
+}
+
+fn main() {
+    let result = match Command::from_args() {
+        Command::Watch {
+            day,
+            input,
+            watch,
+            interval,
+            root,
+            args,
+        } => {
+            let root = root.unwrap_or_else(Solver::default_root);
+            Solver::new(&root, day).and_then(|solver| {
+                let interval = Duration::from_millis(interval);
+                watch::watch(&solver, input.as_deref(), &watch, &args, interval)
+            })
+        }
+        Command::Dashboard {
+            days,
+            inputs,
+            answers,
+            jobs,
+            root,
+        } => {
+            let root = root.unwrap_or_else(Solver::default_root);
+            let days = if days.is_empty() { (1..=25).collect() } else { days };
+            // Both default to the inputs directory, the answers only if they're there.
+            let inputs = inputs.unwrap_or_else(|| root.join("inputs"));
+            let answers =
+                answers.or_else(|| Some(inputs.join("answers.txt")).filter(|path| path.is_file()));
+            let jobs =
+                jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+            dashboard::dashboard(&root, &days, &inputs, answers.as_deref(), jobs)
+        }
+        Command::Gen { day, seed, size } => gen::default_size(day)
+            .and_then(|default| gen::generate(day, seed, size.unwrap_or(default)))
+            .map(|input| print!("{}", input))
+            .ok_or_else(|| format!("There's no generator for day {}", day).into()),
+        Command::TraceDiff {
+            first,
+            second,
+            context,
+        } => trace_diff(&first, &second, context).map(|same| {
+            if !same {
+                std::process::exit(1);
+            }
+        }),
+    };
+
+    if let Err(Error(message)) = result {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
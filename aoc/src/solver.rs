@@ -0,0 +1,147 @@
+// Building and running the solver for a day, which is the workspace member in
+// the directory named after the day, like day-07.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::input;
+
+use crate::Error;
+
+pub struct Solver {
+    pub day: u8,
+    root: PathBuf,
+    directory: PathBuf,
+}
+
+pub struct Run {
+    pub output: String,
+    pub errors: String,
+    pub elapsed: Duration,
+    pub succeeded: bool,
+}
+
+// The package name from a Cargo.toml, which is also the name of its binary.
+fn package_name(manifest: &str) -> Option<&str> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package && line.starts_with("name") {
+            let value = line.split_once('=')?.1.trim();
+            return Some(value.trim_matches('"'));
+        }
+    }
+    None
+}
+
+impl Solver {
+    pub fn new(root: &Path, day: u8) -> Result<Self, Error> {
+        let directory = root.join(format!("day-{:02}", day));
+        if !directory.join("Cargo.toml").is_file() {
+            return Err(format!("There's no solver for day {} in {}", day, directory.display()).into());
+        }
+        Ok(Solver {
+            day,
+            root: root.to_path_buf(),
+            directory,
+        })
+    }
+
+    // The root of the workspace this was built from.
+    pub fn default_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+    }
+
+    fn package(&self) -> Result<String, Error> {
+        let manifest = fs::read_to_string(self.directory.join("Cargo.toml"))?;
+        package_name(&manifest)
+            .map(str::to_string)
+            .ok_or_else(|| format!("Can't find the package name for day {}", self.day).into())
+    }
+
+    // Build the solver in release mode, which does nothing if it's up to date.
+    // Every day shares the workspace's target directory.  When `quiet` is set
+    // nothing cargo says is shown.
+    pub fn build(&self, quiet: bool) -> Result<PathBuf, Error> {
+        let package = self.package()?;
+        let mut cargo = Command::new("cargo");
+        cargo
+            .args(["build", "--release", "--quiet", "--package", &package])
+            .current_dir(&self.root);
+        let status = if quiet {
+            cargo.output()?.status
+        } else {
+            cargo.status()?
+        };
+        if !status.success() {
+            return Err(format!("Day {} didn't build", self.day).into());
+        }
+        Ok(self.root.join("target").join("release").join(package))
+    }
+
+    // Run the solver with the input on stdin, capturing what it prints.  It runs
+    // in the current directory so any paths in the arguments mean what they
+    // look like.  Compressed input is decompressed first, so that it works for
+    // the days which don't understand it themselves.  When `quiet` is set what
+    // it prints to stderr is captured too, rather than shown.
+    pub fn run(&self, input: Option<&Path>, args: &[String], quiet: bool) -> Result<Run, Error> {
+        let binary = self.build(quiet)?;
+        let input = match input {
+            Some(path) => {
+                let mut data = vec![];
+                input::open(Some(path))?.read_to_end(&mut data)?;
+                Some(data)
+            }
+            None => None,
+        };
+
+        let start = Instant::now();
+        let mut child = Command::new(binary)
+            .args(args)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(if quiet { Stdio::piped() } else { Stdio::inherit() })
+            .spawn()?;
+
+        // Write the input from another thread, so that a solver which prints a
+        // lot before reading all of it can't block.
+        let writer = match (child.stdin.take(), input) {
+            (Some(mut stdin), Some(data)) => Some(thread::spawn(move || {
+                // A solver which stops reading early isn't a problem.
+                let _ = stdin.write_all(&data);
+            })),
+            _ => None,
+        };
+        let output = child.wait_with_output()?;
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+
+        Ok(Run {
+            output: String::from_utf8_lossy(&output.stdout).into_owned(),
+            errors: String::from_utf8_lossy(&output.stderr).into_owned(),
+            elapsed: start.elapsed(),
+            succeeded: output.status.success(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn package_names() {
+        let manifest = "[package]\nname = \"day-02\"\nversion = \"0.1.0\"\n";
+        assert_eq!(package_name(manifest), Some("day-02"));
+
+        let manifest = "[lib]\nname = \"arcade\"\n\n[package]\nname = \"day-13\"\n";
+        assert_eq!(package_name(manifest), Some("day-13"));
+        assert_eq!(package_name("[dependencies]\nname = \"1\"\n"), None);
+    }
+}
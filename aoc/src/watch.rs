@@ -0,0 +1,102 @@
+// Re-run a solver whenever its input, or anything else it reads, changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::solver::{Run, Solver};
+use crate::Error;
+
+// When each file was last modified, or None if it doesn't exist right now.
+fn modified(paths: &[&Path]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+        .collect()
+}
+
+// The lines which differ between two answers, old ones marked with - and new
+// ones with +, or None if they're the same.
+pub fn diff(old: &str, new: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut lines = vec![];
+    for index in 0..old.len().max(new.len()) {
+        match (old.get(index), new.get(index)) {
+            (Some(old), Some(new)) if old == new => {}
+            (old, new) => {
+                lines.extend(old.map(|line| format!("- {}", line)));
+                lines.extend(new.map(|line| format!("+ {}", line)));
+            }
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+fn report(run: &Run, previous: Option<&str>) {
+    print!("{}", run.output);
+    if !run.succeeded {
+        println!("Failed after {:.3}s", run.elapsed.as_secs_f64());
+        return;
+    }
+
+    println!("Took {:.3}s", run.elapsed.as_secs_f64());
+    match previous.map(|previous| diff(previous, &run.output)) {
+        None => {}
+        Some(None) => println!("Answer unchanged"),
+        Some(Some(changes)) => println!("Answer changed:\n{}", changes),
+    }
+}
+
+pub fn watch(
+    solver: &Solver,
+    input: Option<&Path>,
+    also: &[PathBuf],
+    args: &[String],
+    interval: Duration,
+) -> Result<(), Error> {
+    let paths: Vec<&Path> = input.into_iter().chain(also.iter().map(PathBuf::as_path)).collect();
+    if paths.is_empty() {
+        return Err("Nothing to watch, give an input or a file to watch".into());
+    }
+
+    let mut previous: Option<String> = None;
+    let mut last_modified = None;
+    loop {
+        let now = modified(&paths);
+        if last_modified.as_ref() != Some(&now) {
+            last_modified = Some(now);
+
+            println!("Running day {}", solver.day);
+            match solver.run(input, args, false) {
+                Ok(run) => {
+                    report(&run, previous.as_deref());
+                    if run.succeeded {
+                        previous = Some(run.output);
+                    }
+                }
+                Err(Error(message)) => eprintln!("{}", message),
+            }
+            println!("Waiting for changes...");
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diffs() {
+        assert_eq!(diff("12\n", "12\n"), None);
+        assert_eq!(diff("12\n", "13\n").unwrap(), "- 12\n+ 13");
+        assert_eq!(diff("1\n2\n", "1\n3\n4\n").unwrap(), "- 2\n+ 3\n+ 4");
+    }
+}
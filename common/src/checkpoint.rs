@@ -0,0 +1,53 @@
+// Saving the state of a long search every so often, so that an interrupted
+// run can carry on from where it got to instead of starting again.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub struct Checkpoint {
+    path: PathBuf,
+    interval: Duration,
+    last_saved: Instant,
+}
+
+impl Checkpoint {
+    pub fn new(path: &Path, interval: Duration) -> Self {
+        Checkpoint {
+            path: path.to_path_buf(),
+            interval,
+            last_saved: Instant::now(),
+        }
+    }
+
+    // What was saved last time.
+    pub fn load(&self) -> Result<String, String> {
+        fs::read_to_string(&self.path)
+            .map_err(|error| format!("Can't read checkpoint {}: {}", self.path.display(), error))
+    }
+
+    // Save with `write` if it's been long enough since the last time.  This
+    // goes via another file so that being interrupted part way through leaves
+    // the last checkpoint intact.  Failing to save isn't worth stopping the
+    // search for, so it just says so and tries again next time.
+    pub fn update(&mut self, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) {
+        if self.last_saved.elapsed() < self.interval {
+            return;
+        }
+        self.last_saved = Instant::now();
+
+        if let Err(error) = self.save(write) {
+            eprintln!("Can't save checkpoint {}: {}", self.path.display(), error);
+        }
+    }
+
+    fn save(&self, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+        let partial = self.path.with_extension("partial");
+        let mut output = BufWriter::new(File::create(&partial)?);
+        write(&mut output)?;
+        output.flush()?;
+        drop(output);
+        fs::rename(partial, &self.path)
+    }
+}
@@ -0,0 +1,101 @@
+// Running two implementations of the same solver against each other, for
+// days which have kept a simple version around to check a faster one.
+
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+// What one implementation came up with, and how long it took.
+pub struct Timing<T> {
+    pub name: String,
+    pub answer: T,
+    pub elapsed: Duration,
+}
+
+pub fn time<T>(name: &str, solve: impl FnOnce() -> T) -> Timing<T> {
+    let start = Instant::now();
+    let answer = solve();
+    Timing {
+        name: name.to_string(),
+        answer,
+        elapsed: start.elapsed(),
+    }
+}
+
+// A line for each implementation, then one saying whether they agree and if
+// so how many times faster the second was than the first.
+pub fn report<T: PartialEq + Debug>(first: &Timing<T>, second: &Timing<T>) -> String {
+    let width = first.name.len().max(second.name.len()) + 1;
+    let mut text = String::new();
+    for timing in [first, second].iter() {
+        text += &format!(
+            "{:width$} {:?} in {:.3}s\n",
+            format!("{}:", timing.name),
+            timing.answer,
+            timing.elapsed.as_secs_f64(),
+            width = width
+        );
+    }
+
+    if first.answer != second.answer {
+        text += "The answers differ\n";
+    } else if second.elapsed.as_nanos() == 0 {
+        text += "The answers agree\n";
+    } else {
+        let speedup = first.elapsed.as_secs_f64() / second.elapsed.as_secs_f64();
+        text += &format!(
+            "The answers agree, and {} is {:.1}x as fast\n",
+            second.name, speedup
+        );
+    }
+    text
+}
+
+// Run the slow implementation then the fast one and print how they did,
+// returning whether they came up with the same answer.
+pub fn compare<T: PartialEq + Debug>(
+    slow: (&str, impl FnOnce() -> T),
+    fast: (&str, impl FnOnce() -> T),
+) -> bool {
+    let slow = time(slow.0, slow.1);
+    let fast = time(fast.0, fast.1);
+    print!("{}", report(&slow, &fast));
+    slow.answer == fast.answer
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timing(name: &str, answer: Option<usize>, millis: u64) -> Timing<Option<usize>> {
+        Timing {
+            name: name.to_string(),
+            answer,
+            elapsed: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn reports() {
+        assert_eq!(
+            report(
+                &timing("naive", Some(31), 1500),
+                &timing("topological", Some(31), 10)
+            ),
+            "naive:       Some(31) in 1.500s\n\
+             topological: Some(31) in 0.010s\n\
+             The answers agree, and topological is 150.0x as fast\n"
+        );
+        assert!(report(&timing("a", Some(1), 2), &timing("b", None, 1))
+            .ends_with("The answers differ\n"));
+        assert!(
+            report(&timing("a", None, 2), &timing("b", None, 0)).ends_with("The answers agree\n")
+        );
+    }
+
+    #[test]
+    fn times() {
+        let timing = time("sum", || (1..=10).sum::<u32>());
+        assert_eq!(timing.name, "sum");
+        assert_eq!(timing.answer, 55);
+    }
+}
@@ -0,0 +1,95 @@
+// Drawing on the terminal, and reading keys from it while something else
+// is being drawn.
+
+use std::fmt;
+use std::io::Write;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+use termion::event::Key;
+use termion::get_tty;
+use termion::input::TermRead;
+use termion::{clear, cursor};
+
+pub trait Screen {
+    fn clear(&mut self);
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, position: [u16; 2], tile: T);
+    fn goto(&mut self, position: [u16; 2]);
+    fn print(&mut self, text: String);
+}
+
+impl<W: Write> Screen for W {
+    fn clear(&mut self) {
+        let _ = write!(self, "{}", clear::All);
+    }
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, [x, y]: [u16; 2], tile: T) {
+        let _ = write!(self, "{}{}", cursor::Goto(x + 1, y + 1), tile);
+        let _ = self.flush();
+    }
+
+    fn goto(&mut self, [x, y]: [u16; 2]) {
+        let _ = write!(self, "{}", cursor::Goto(x + 1, y + 1));
+        let _ = self.flush();
+    }
+
+    fn print(&mut self, text: String) {
+        print!("{}\r\n", text);
+    }
+}
+
+pub struct ScreenBuffer {}
+
+impl Screen for ScreenBuffer {
+    fn clear(&mut self) {}
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, [x, y]: [u16; 2], tile: T) {
+        println!("Output: ({}, {}): {:?}", x, y, tile);
+    }
+
+    fn goto(&mut self, _: [u16; 2]) {}
+
+    fn print(&mut self, text: String) {
+        println!("{}\n", text);
+    }
+}
+
+// A rectangular area of the screen which lines of text can be written to,
+// so several can be laid out side by side.
+pub struct Pane {
+    pub origin: [u16; 2],
+    pub width: u16,
+}
+
+impl Pane {
+    pub fn new(origin: [u16; 2], width: u16) -> Self {
+        Pane { origin, width }
+    }
+
+    // Write a line of text into the pane, padded or cut to fill its width.
+    pub fn line(&self, screen: &mut impl Screen, row: u16, text: &str) {
+        let width = self.width as usize;
+        let text: String = text.chars().take(width).collect();
+        let [x, y] = self.origin;
+        screen.set_tile([x, y + row], format!("{:<width$}", text, width = width));
+    }
+}
+
+// Whether the key is one of the usual ways of asking to quit.
+pub fn is_quit(key: &Key) -> bool {
+    matches!(key, Key::Char('q') | Key::Esc | Key::Ctrl('c'))
+}
+
+// The keys pressed on the terminal, read on a separate thread so that
+// whatever's being drawn can keep changing between key presses.
+pub fn read_keys() -> Receiver<Key> {
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        for key in get_tty().unwrap().keys() {
+            if sender.send(key.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
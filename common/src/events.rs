@@ -0,0 +1,113 @@
+// Every change to what's on screen, one JSON object per line, for anything
+// which wants to follow a run without reading terminal escape codes.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::options::quote;
+
+pub enum Value<'a> {
+    Number(i64),
+    Text(&'a str),
+}
+
+impl From<i64> for Value<'_> {
+    fn from(number: i64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<u16> for Value<'_> {
+    fn from(number: u16) -> Self {
+        Value::Number(number.into())
+    }
+}
+
+impl From<usize> for Value<'_> {
+    fn from(number: usize) -> Self {
+        Value::Number(number as i64)
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(text: &'a str) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Text(text) => write!(f, "{}", quote(text)),
+        }
+    }
+}
+
+fn format_event(timestamp: u128, event: &str, fields: &[(&str, Value)]) -> String {
+    let mut line = format!(
+        "{{\"timestamp\": {}, \"event\": {}",
+        timestamp,
+        Value::Text(event)
+    );
+    for (name, value) in fields {
+        line += &format!(", {}: {}", Value::Text(name), value);
+    }
+    line + "}"
+}
+
+pub struct Events {
+    output: Box<dyn Write>,
+    start: Instant,
+}
+
+impl Events {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|error| format!("Can't create {}: {}", path.display(), error))?;
+        Ok(Events {
+            output: Box::new(BufWriter::new(file)),
+            start: Instant::now(),
+        })
+    }
+
+    pub fn write(&mut self, event: &str, fields: &[(&str, Value)]) -> io::Result<()> {
+        let timestamp = self.start.elapsed().as_micros();
+        writeln!(self.output, "{}", format_event(timestamp, event, fields))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format() {
+        assert_eq!(
+            format_event(12, "clear", &[]),
+            "{\"timestamp\": 12, \"event\": \"clear\"}"
+        );
+
+        let fields = [
+            ("x", 3_u16.into()),
+            ("y", (-1_i64).into()),
+            ("tile", "Wall".into()),
+        ];
+        assert_eq!(
+            format_event(0, "tile", &fields),
+            "{\"timestamp\": 0, \"event\": \"tile\", \"x\": 3, \"y\": -1, \"tile\": \"Wall\"}"
+        );
+
+        assert_eq!(
+            Value::Text("a \"b\"\\\n").to_string(),
+            "\"a \\\"b\\\"\\\\\\u000a\""
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// Made up puzzle input for the days which don't need an Intcode program, for
+// testing the solvers on more than the one input each.  The same seed and
+// size always give the same input.
+
+use std::collections::HashSet;
+
+// SplitMix64, the same as the network faults on day 23 use.
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in [0, limit).
+    pub fn below(&mut self, limit: u64) -> u64 {
+        self.next() % limit
+    }
+
+    // A number in [low, high].
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        low + self.below((high - low + 1) as u64) as i64
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index as u64 + 1) as usize);
+        }
+    }
+}
+
+// Two wires of `size` movements each, like "R75,D30,U83", on separate lines.
+pub fn wires(seed: u64, size: usize) -> String {
+    let mut random = Random::new(seed);
+    let longest = (size as i64 * 3).clamp(1, 1000);
+
+    let mut wire = || {
+        let movements: Vec<String> = (0..size)
+            .map(|_| {
+                let direction = ['U', 'D', 'L', 'R'][random.below(4) as usize];
+                format!("{}{}", direction, random.between(1, longest))
+            })
+            .collect();
+        movements.join(",") + "\n"
+    };
+
+    wire() + &wire()
+}
+
+// A range of passwords with `size` digits, like "134792-675810".
+pub fn passwords(seed: u64, size: usize) -> String {
+    let mut random = Random::new(seed);
+    let size = size.clamp(1, 9) as u32;
+    let lowest = if size == 1 { 0 } else { 10_i64.pow(size - 1) };
+    let highest = 10_i64.pow(size) - 1;
+
+    let low = random.between(lowest, highest);
+    let high = random.between(low, highest);
+    format!("{}-{}\n", low, high)
+}
+
+// A map of `size` objects orbiting around COM, with YOU and SAN orbiting two
+// of them, one orbit per line like "COM)B" in no particular order.
+pub fn orbits(seed: u64, size: usize) -> String {
+    let mut random = Random::new(seed);
+
+    let mut taken: HashSet<String> = ["COM", "YOU", "SAN"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let mut objects = vec!["COM".to_string()];
+    let mut orbits = vec![];
+    while objects.len() <= size {
+        let name: String = (0..3)
+            .map(|_| b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789"[random.below(36) as usize] as char)
+            .collect();
+        if taken.insert(name.clone()) {
+            let centre = &objects[random.below(objects.len() as u64) as usize];
+            orbits.push(format!("{}){}", centre, name));
+            objects.push(name);
+        }
+    }
+
+    for object in ["YOU", "SAN"].iter() {
+        // Anything but COM, unless there's nothing else.
+        let centre = if size == 0 {
+            &objects[0]
+        } else {
+            &objects[1 + random.below(size as u64) as usize]
+        };
+        orbits.push(format!("{}){}", centre, object));
+    }
+
+    random.shuffle(&mut orbits);
+    orbits.iter().map(|orbit| orbit.clone() + "\n").collect()
+}
+
+// `size` moons, one per line like "<x=-1, y=0, z=2>".
+pub fn moons(seed: u64, size: usize) -> String {
+    let mut random = Random::new(seed);
+    (0..size)
+        .map(|_| {
+            let [x, y, z] = [(); 3].map(|_| random.between(-15, 15));
+            format!("<x={}, y={}, z={}>\n", x, y, z)
+        })
+        .collect()
+}
+
+// The size of a real input for each day which can be generated.
+pub fn default_size(day: u8) -> Option<usize> {
+    match day {
+        3 => Some(300),
+        4 => Some(6),
+        6 => Some(1000),
+        12 => Some(4),
+        _ => None,
+    }
+}
+
+// Input for `day`, or None if there's no generator for it.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    match day {
+        3 => Some(wires(seed, size)),
+        4 => Some(passwords(seed, size)),
+        6 => Some(orbits(seed, size)),
+        12 => Some(moons(seed, size)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repeatable() {
+        for day in [3, 4, 6, 12].iter().cloned() {
+            let size = default_size(day).unwrap();
+            assert_eq!(generate(day, 1, size), generate(day, 1, size));
+            assert_ne!(generate(day, 1, size), generate(day, 2, size));
+        }
+        assert_eq!(generate(5, 1, 10), None);
+    }
+
+    #[test]
+    fn shapes() {
+        let text = wires(3, 5);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.split(',').count() == 5));
+
+        for seed in 0..20 {
+            let text = passwords(seed, 6);
+            let (low, high) = text.trim().split_once('-').unwrap();
+            let (low, high): (u32, u32) = (low.parse().unwrap(), high.parse().unwrap());
+            assert!((100_000..=high).contains(&low) && high <= 999_999);
+        }
+
+        let text = orbits(4, 50);
+        assert_eq!(text.lines().count(), 52);
+        let objects: HashSet<&str> = text.lines().map(|line| &line[4..]).collect();
+        assert_eq!(objects.len(), 52);
+        assert!(objects.contains("YOU") && objects.contains("SAN") && !objects.contains("COM"));
+        assert_eq!(orbits(4, 0), "COM)SAN\nCOM)YOU\n");
+
+        assert_eq!(moons(5, 4).lines().count(), 4);
+    }
+}
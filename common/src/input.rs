@@ -0,0 +1,98 @@
+// Reading puzzle input, which may have been compressed with gzip or zstd to
+// keep archives of old inputs small.  The format is recognised from the first
+// few bytes rather than the file name, so compressed input can be piped in
+// too.
+
+use std::fs::File;
+use std::io::{self, stdin, BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// Read as much of the start of `input` as fits in `buffer`, stopping early
+// only at the end of the input.
+fn read_start(input: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match input.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(count) => filled += count,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(filled)
+}
+
+// `input` decompressed if it's compressed, or as it is if not.
+pub fn decompress(mut input: impl Read + 'static) -> io::Result<Box<dyn BufRead>> {
+    let mut start = [0; 4];
+    let length = read_start(&mut input, &mut start)?;
+    let start = &start[..length];
+    let input = Cursor::new(start.to_vec()).chain(input);
+
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(input))))
+    } else if start.starts_with(ZSTD_MAGIC) {
+        let decoder = StreamingDecoder::new(input)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        Ok(Box::new(BufReader::new(decoder)))
+    } else {
+        Ok(Box::new(BufReader::new(input)))
+    }
+}
+
+// The input in `path`, or on stdin if there isn't one.
+pub fn open(path: Option<&Path>) -> Result<Box<dyn BufRead>, String> {
+    match path {
+        Some(path) => File::open(path)
+            .and_then(decompress)
+            .map_err(|error| format!("Can't read {}: {}", path.display(), error)),
+        None => decompress(stdin()).map_err(|error| format!("Can't read stdin: {}", error)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn read_all(input: Vec<u8>) -> String {
+        let mut text = String::new();
+        decompress(Cursor::new(input))
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(read_all(b"1,2,3\n".to_vec()), "1,2,3\n");
+        assert_eq!(read_all(b"1".to_vec()), "1");
+        assert_eq!(read_all(vec![]), "");
+    }
+
+    #[test]
+    fn gzip() {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(b"<x=-1, y=0, z=2>\n").unwrap();
+        assert_eq!(read_all(encoder.finish().unwrap()), "<x=-1, y=0, z=2>\n");
+    }
+
+    #[test]
+    fn zstd() {
+        // "1,0,0,3,99\n" compressed with `zstd`.
+        let compressed = vec![
+            0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x58, 0x59, 0x00, 0x00, 0x31, 0x2c, 0x30, 0x2c, 0x30,
+            0x2c, 0x33, 0x2c, 0x39, 0x39, 0x0a, 0x13, 0x2b, 0xd7, 0xd8,
+        ];
+        assert_eq!(read_all(compressed), "1,0,0,3,99\n");
+    }
+}
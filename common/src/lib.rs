@@ -0,0 +1,14 @@
+// Things every day's solver does the same way.
+
+pub mod checkpoint;
+pub mod compare;
+pub mod display;
+pub mod events;
+pub mod gen;
+pub mod input;
+pub mod modmath;
+pub mod options;
+
+pub use checkpoint::Checkpoint;
+pub use events::Events;
+pub use options::{Answer, CommonOpts, Flag};
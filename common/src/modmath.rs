@@ -0,0 +1,197 @@
+// Arithmetic modulo m, for any m that fits in a u128.
+
+// a * b (mod m), or None if the product doesn't fit in a u128.
+pub fn checked_mul_mod(a: u128, b: u128, m: u128) -> Option<u128> {
+    (a % m).checked_mul(b % m).map(|product| product % m)
+}
+
+// a * b (mod m), falling back to doubling and adding when the product won't
+// fit in a u128.
+pub fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(product) = checked_mul_mod(a, b, m) {
+        return product;
+    }
+
+    let (a, mut b) = (a % m, b % m);
+
+    let mut result = 0;
+    let mut double = a;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, double, m);
+        }
+        double = add_mod(double, double, m);
+        b >>= 1;
+    }
+    result
+}
+
+// a + b (mod m), for a and b already less than m.
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+// a - b (mod m), for a and b already less than m.
+fn sub_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= b {
+        a - b
+    } else {
+        a + (m - b)
+    }
+}
+
+// x ^ n (mod m).
+pub fn mod_pow(x: u128, mut n: u128, m: u128) -> u128 {
+    let mut result = 1 % m;
+    let mut square = x % m;
+
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mul_mod(result, square, m);
+        }
+        square = mul_mod(square, square, m);
+        n >>= 1;
+    }
+
+    result
+}
+
+// The y such that x * y = 1 (mod m), if there is one.
+pub fn inverse(x: u128, m: u128) -> Option<u128> {
+    // Extended Euclid, keeping the coefficients of x reduced mod m so they
+    // stay unsigned.
+    let mut t = 0;
+    let mut r = m;
+    let mut new_t = 1 % m;
+    let mut new_r = x % m;
+
+    while new_r != 0 {
+        let quotient = r / new_r;
+        let tmp_t = sub_mod(t, mul_mod(quotient, new_t, m), m);
+        t = new_t;
+        new_t = tmp_t;
+        let tmp_r = r - quotient * new_r;
+        r = new_r;
+        new_r = tmp_r;
+    }
+
+    if r > 1 {
+        None
+    } else {
+        Some(t)
+    }
+}
+
+// The map x -> a * x + b (mod m).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    pub a: u128,
+    pub b: u128,
+    pub m: u128,
+}
+
+impl Affine {
+    pub fn new(a: u128, b: u128, m: u128) -> Self {
+        Affine {
+            a: a % m,
+            b: b % m,
+            m,
+        }
+    }
+
+    pub fn identity(m: u128) -> Self {
+        Affine::new(1, 0, m)
+    }
+
+    pub fn apply(self, x: u128) -> u128 {
+        add_mod(mul_mod(self.a, x, self.m), self.b, self.m)
+    }
+
+    // The map which applies this one and then `next`.
+    pub fn then(self, next: Affine) -> Self {
+        assert_eq!(self.m, next.m);
+        Affine::new(mul_mod(next.a, self.a, self.m), next.apply(self.b), self.m)
+    }
+
+    // This map applied `n` times.
+    pub fn pow(self, mut n: u128) -> Self {
+        let mut result = Affine::identity(self.m);
+        let mut square = self;
+
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.then(square);
+            }
+            square = square.then(square);
+            n >>= 1;
+        }
+
+        result
+    }
+
+    // The map which undoes this one, if there is one.
+    pub fn inverse(self) -> Option<Self> {
+        let a = inverse(self.a, self.m)?;
+        Some(Affine::new(a, mul_mod(self.m - self.b, a, self.m), self.m))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A prime large enough that products of numbers below it overflow.
+    const LARGE: u128 = (1 << 127) - 1;
+
+    #[test]
+    fn multiply() {
+        assert_eq!(checked_mul_mod(1 << 64, 1 << 63, LARGE), Some(1));
+        assert_eq!(checked_mul_mod(1 << 64, 1 << 64, LARGE), None);
+        assert_eq!(mul_mod(123456789, 987654321, 1000000007), 259106859);
+        assert_eq!(mul_mod(LARGE - 1, LARGE - 1, LARGE), 1);
+        assert_eq!(mul_mod(1 << 100, 1 << 100, LARGE), 1 << 73);
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(7, 0, 13), 1);
+        assert_eq!(mod_pow(7, 0, 1), 0);
+        // Fermat's little theorem.
+        assert_eq!(mod_pow(3, 10006, 10007), 1);
+        assert_eq!(mod_pow(12345, LARGE - 1, LARGE), 1);
+    }
+
+    #[test]
+    fn inverses() {
+        assert_eq!(inverse(3, 10), Some(7));
+        assert_eq!(inverse(4, 10), None);
+        assert_eq!(inverse(0, 7), None);
+        let deck_size = 119315717514047;
+        assert_eq!(
+            inverse(5, deck_size).map(|y| mul_mod(5, y, deck_size)),
+            Some(1)
+        );
+        assert_eq!(inverse(LARGE - 1, LARGE), Some(LARGE - 1));
+    }
+
+    #[test]
+    fn affine() {
+        let f = Affine::new(3, 4, 11);
+        assert_eq!(f.then(f), Affine::new(9, 16, 11));
+        assert_eq!(f.pow(5).apply(2), (0..5).fold(2, |x, _| f.apply(x)));
+        assert_eq!(f.then(f.inverse().unwrap()), Affine::identity(11));
+        assert_eq!(Affine::new(2, 0, 10).inverse(), None);
+
+        let g = Affine::new(LARGE - 2, 5, LARGE);
+        assert_eq!(
+            g.pow(3).apply(LARGE - 1),
+            g.apply(g.apply(g.apply(LARGE - 1)))
+        );
+        assert_eq!(g.then(g.inverse().unwrap()), Affine::identity(LARGE));
+    }
+}
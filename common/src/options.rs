@@ -0,0 +1,230 @@
+// Options which mean the same thing for every day, for each day's own
+// options to include with `#[structopt(flatten)]`.
+
+use std::fmt;
+use std::io::BufRead;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+use crate::input;
+
+#[derive(Debug, StructOpt)]
+pub struct CommonOpts {
+    /// Read the puzzle input from this file instead of stdin
+    #[structopt(short, long, parse(from_os_str))]
+    pub input: Option<PathBuf>,
+
+    /// Which part of the puzzle to solve, if not the day's default
+    #[structopt(short, long, possible_values = &["1", "2"])]
+    pub part: Option<u8>,
+
+    /// Set speed of the animation
+    #[structopt(short, long)]
+    pub speed: Option<u64>,
+
+    /// Print what would be drawn rather than drawing it on the terminal
+    #[structopt(short, long, alias = "headless")]
+    pub debug: bool,
+
+    /// Only print the answers, as a JSON object
+    #[structopt(long, conflicts_with = "quiet")]
+    pub json: bool,
+
+    /// Only print the answers
+    #[structopt(short, long)]
+    pub quiet: bool,
+}
+
+// The options a day may not have any use for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    Part,
+    Speed,
+    Debug,
+}
+
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Flag::Part => write!(f, "--part"),
+            Flag::Speed => write!(f, "--speed"),
+            Flag::Debug => write!(f, "--debug"),
+        }
+    }
+}
+
+// An answer to a puzzle, which is usually a number but sometimes text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(String),
+    Text(String),
+}
+
+macro_rules! number_answer {
+    ($($number:ty),*) => {
+        $(
+            impl From<$number> for Answer {
+                fn from(number: $number) -> Self {
+                    Answer::Number(number.to_string())
+                }
+            }
+        )*
+    };
+}
+
+number_answer!(i32, i64, u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(text) | Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+// The answers, one per line, or as a JSON object keyed by their names.
+fn format_answers(json: bool, answers: &[(&str, Answer)]) -> String {
+    if !json {
+        return answers
+            .iter()
+            .map(|(_, answer)| format!("{}\n", answer))
+            .collect();
+    }
+
+    let fields: Vec<String> = answers
+        .iter()
+        .map(|(name, answer)| match answer {
+            Answer::Number(number) => format!("{}: {}", quote(name), number),
+            Answer::Text(text) => format!("{}: {}", quote(name), quote(text)),
+        })
+        .collect();
+    format!("{{{}}}\n", fields.join(", "))
+}
+
+// `text` as a JSON string.
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted + "\""
+}
+
+impl CommonOpts {
+    // The part to solve, or `default` if it wasn't given.
+    pub fn part(&self, default: u8) -> u8 {
+        self.part.unwrap_or(default)
+    }
+
+    // Fail if any of `flags` were given, for a day which doesn't use them.
+    pub fn unsupported(&self, flags: &[Flag]) -> Result<(), String> {
+        for flag in flags {
+            let given = match flag {
+                Flag::Part => self.part.is_some(),
+                Flag::Speed => self.speed.is_some(),
+                Flag::Debug => self.debug,
+            };
+            if given {
+                return Err(format!("{} doesn't do anything for this day", flag));
+            }
+        }
+        Ok(())
+    }
+
+    // The speed to animate at, unless there's nothing to see.
+    pub fn speed(&self) -> Option<u64> {
+        if self.answers_only() {
+            None
+        } else {
+            self.speed
+        }
+    }
+
+    // Whether only the answers should be printed.
+    pub fn answers_only(&self) -> bool {
+        self.quiet || self.json
+    }
+
+    // The puzzle input, from the file given or stdin, decompressed if need be.
+    pub fn input(&self) -> Result<Box<dyn BufRead>, String> {
+        input::open(self.input.as_deref())
+    }
+
+    // Print the answers in whichever form was asked for.
+    pub fn print_answers(&self, answers: &[(&str, Answer)]) {
+        print!("{}", format_answers(self.json, answers));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, StructOpt)]
+    struct Opts {
+        #[structopt(long)]
+        dot: bool,
+
+        #[structopt(flatten)]
+        common: CommonOpts,
+    }
+
+    fn parse(args: &[&str]) -> Result<Opts, structopt::clap::Error> {
+        Opts::from_iter_safe(std::iter::once("day").chain(args.iter().cloned()))
+    }
+
+    #[test]
+    fn flags() {
+        let opts = parse(&["-p", "1", "--headless", "-s", "10", "--dot"]).unwrap();
+        assert!(opts.dot);
+        assert_eq!(opts.common.part(2), 1);
+        assert_eq!(opts.common.speed(), Some(10));
+        assert!(opts.common.debug);
+        assert!(!opts.common.answers_only());
+        assert_eq!(
+            opts.common.unsupported(&[Flag::Part]).unwrap_err(),
+            "--part doesn't do anything for this day"
+        );
+
+        let opts = parse(&["--json", "-s", "10"]).unwrap();
+        assert_eq!(opts.common.part(2), 2);
+        assert_eq!(opts.common.speed(), None);
+        assert!(opts.common.answers_only());
+        assert!(opts.common.unsupported(&[Flag::Part, Flag::Debug]).is_ok());
+
+        assert!(parse(&["-p", "3"]).is_err());
+        assert!(parse(&["--json", "--quiet"]).is_err());
+    }
+
+    #[test]
+    fn answers() {
+        let answers = [("distance", 212_usize.into()), ("route", "R,8,L,\"4\"".into())];
+        assert_eq!(format_answers(false, &answers[..1]), "212\n");
+        assert_eq!(format_answers(false, &answers), "212\nR,8,L,\"4\"\n");
+        assert_eq!(
+            format_answers(true, &answers),
+            "{\"distance\": 212, \"route\": \"R,8,L,\\\"4\\\"\"}\n"
+        );
+        assert_eq!(
+            format_answers(true, &[("message", "0123".into())]),
+            "{\"message\": \"0123\"}\n"
+        );
+    }
+}
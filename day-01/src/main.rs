@@ -1,5 +1,8 @@
-use std::io::{stdin, BufRead};
+use std::io::BufRead;
 use std::str::FromStr;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
 
 struct Component {
     weight: u32,
@@ -34,10 +37,29 @@ fn fuel_for_weight(weight: u32) -> u32 {
     (weight / 3).saturating_sub(2)
 }
 
+#[derive(Debug, StructOpt)]
+struct Opts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
 fn main() {
-    let fuel: u32 = get_components(stdin().lock())
+    let opts = Opts::from_args();
+    let input = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input());
+    let input = match input {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    let fuel: u32 = get_components(input)
         .map(|component| component.fuel_required())
         .sum();
 
-    println!("{}", fuel)
+    opts.common.print_answers(&[("fuel", fuel.into())]);
 }
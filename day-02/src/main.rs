@@ -1,7 +1,11 @@
-use std::io::{stdin, Read};
+use std::io::Read;
 use std::str::FromStr;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Error(String);
 
 impl<T: ToString> From<T> for Error {
@@ -111,12 +115,31 @@ fn find_result(program: &Program, expected_result: usize) -> Option<(usize, usiz
     None
 }
 
+#[derive(Debug, StructOpt)]
+struct Opts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
 fn main() {
-    let program = Program::parse(stdin()).unwrap();
+    let opts = Opts::from_args();
+    let program = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|input| Program::parse(input).unwrap());
+    let program = match program {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
 
     if let Some((noun, verb)) = find_result(&program, 19_690_720) {
-        println!("{}", 100 * noun + verb);
+        opts.common.print_answers(&[("answer", (100 * noun + verb).into())]);
     } else {
-        println!("Not possible")
+        eprintln!("Not possible");
+        std::process::exit(1);
     }
 }
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt;
-use std::io::{stdin, BufRead};
+use std::io::BufRead;
 use std::str::FromStr;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
 
 #[derive(Debug)]
 struct Error(String);
@@ -148,20 +151,86 @@ impl Wire {
     }
 }
 
-fn main() {
-    let input = stdin();
-    let wire_1 = Wire::read(input.lock()).unwrap();
-    let wire_2 = Wire::read(input.lock()).unwrap();
+// Where the wires cross in the fewest total steps, and how many that is.
+fn closest(wire_1: &Wire, wire_2: &Wire) -> Option<(Position, usize)> {
+    wire_1
+        .intersection(wire_2)
+        .into_iter()
+        .map(|(position, step_1, step_2)| (position, step_1 + step_2))
+        .min_by_key(|(_, steps)| *steps)
+}
 
-    let intersection = wire_1.intersection(&wire_2);
+#[derive(Debug, StructOpt)]
+struct Opts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
 
-    let closest = intersection
-        .into_iter()
-        .min_by_key(|(_, step_1, step_2)| step_1 + step_2);
+fn main() {
+    let opts = Opts::from_args();
+    let wires = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .map_err(Error)
+        .and_then(|_| opts.common.input().map_err(Error))
+        .and_then(|mut input| Ok((Wire::read(&mut input)?, Wire::read(&mut input)?)));
+    let (wire_1, wire_2) = match wires {
+        Ok(wires) => wires,
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
 
-    if let Some((position, step_1, step_2)) = closest {
-        println!("Closest point is at {}, distance {}, total steps = {}", position, position.distance(), step_1 + step_2);
+    if let Some((position, steps)) = closest(&wire_1, &wire_2) {
+        if !opts.common.answers_only() {
+            println!("Closest point is at {}", position);
+        }
+        opts.common.print_answers(&[("distance", position.distance().into()), ("steps", steps.into())]);
     } else {
-        println!("Lines do not intersect");
+        eprintln!("Lines do not intersect");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::gen;
+
+    // Compare every point on one wire with every point on the other.
+    fn brute_force(wire_1: &Wire, wire_2: &Wire) -> Option<usize> {
+        let mut best = None;
+        for (step_1, position_1) in wire_1.route.iter().enumerate() {
+            for (step_2, position_2) in wire_2.route.iter().enumerate() {
+                if position_1 == position_2 {
+                    let steps = step_1 + step_2 + 2;
+                    best = Some(best.map_or(steps, |best: usize| best.min(steps)));
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn example() {
+        let wire_1: Wire = "R75,D30,R83,U83,L12,D49,R71,U7,L72".parse().unwrap();
+        let wire_2: Wire = "U62,R66,U55,R34,D71,R55,D58,R83".parse().unwrap();
+        assert_eq!(closest(&wire_1, &wire_2).map(|(_, steps)| steps), Some(610));
+    }
+
+    #[test]
+    fn generated() {
+        for seed in 0..50 {
+            let input = gen::wires(seed, 10);
+            let wire_1 = Wire::read(input.as_bytes()).unwrap();
+            let wire_2 = Wire::read(input.lines().nth(1).unwrap().as_bytes()).unwrap();
+            assert_eq!(
+                closest(&wire_1, &wire_2).map(|(_, steps)| steps),
+                brute_force(&wire_1, &wire_2),
+                "seed {}",
+                seed
+            );
+        }
     }
 }
@@ -0,0 +1,130 @@
+use std::io::Read;
+use std::ops::RangeInclusive;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
+
+#[derive(Clone, Copy)]
+struct Password(u32);
+
+impl Password {
+    fn is_valid(&self) -> bool {
+        let string = self.0.to_string();
+        for (c1, c2) in string.chars().zip(string.chars().skip(1)) {
+            if c2 < c1 {
+                return false;
+            } 
+        }
+
+        let mut have_pair = false;
+
+        let mut current_char = string.chars().nth(0).unwrap();
+        let mut group_size = 1;
+        for c in string.chars().skip(1) {
+            if c != current_char {
+                if group_size == 2 {
+                    have_pair = true;
+                }
+
+                current_char = c;
+                group_size = 1;
+            } else {
+                group_size += 1;
+            }
+        }
+
+        if group_size == 2 {
+            have_pair = true;
+        }
+
+        have_pair
+    }
+}
+
+// A range like "134792-675810".
+fn parse_range(text: &str) -> Option<RangeInclusive<u32>> {
+    let (low, high) = text.trim().split_once('-')?;
+    Some(low.parse().ok()?..=high.parse().ok()?)
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// The range of passwords to check, like 134792-675810, instead of reading it from the input
+    range: Option<String>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    // The puzzle input is just the range, so it can be given on the command
+    // line instead of the input.
+    let text = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| match &opts.range {
+            Some(text) => Ok(text.clone()),
+            None => {
+                let mut text = String::new();
+                opts.common
+                    .input()?
+                    .read_to_string(&mut text)
+                    .map_err(|err| err.to_string())?;
+                Ok(text)
+            }
+        });
+    let text = match text {
+        Ok(text) => text,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    let range = match parse_range(&text) {
+        Some(range) => range,
+        None => {
+            eprintln!("Invalid range {:?}", text.trim());
+            std::process::exit(1);
+        }
+    };
+
+    let num_passwords = range.map(Password).filter(Password::is_valid).count();
+
+    opts.common.print_answers(&[("passwords", num_passwords.into())]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::gen;
+
+    // The digits never decrease, and some digit appears exactly twice.
+    fn brute_force(password: u32) -> bool {
+        let digits: Vec<u32> = password
+            .to_string()
+            .chars()
+            .map(|c| c.to_digit(10).unwrap())
+            .collect();
+        digits.windows(2).all(|pair| pair[0] <= pair[1])
+            && (0..10).any(|digit| digits.iter().filter(|&&d| d == digit).count() == 2)
+    }
+
+    #[test]
+    fn examples() {
+        assert!(Password(112233).is_valid());
+        assert!(!Password(123444).is_valid());
+        assert!(Password(111122).is_valid());
+        assert!(!Password(223450).is_valid());
+    }
+
+    #[test]
+    fn generated() {
+        for seed in 0..20 {
+            let range = parse_range(&gen::passwords(seed, 5)).unwrap();
+            for password in range {
+                assert_eq!(Password(password).is_valid(), brute_force(password), "{}", password);
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
-use std::io::{stdin, Read};
+use std::io::Read;
 use std::str::FromStr;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Error(String);
 
 impl<T: ToString> From<T> for Error {
@@ -20,7 +24,7 @@ struct Modes(i64);
 
 impl Modes {
     fn mode(&self, index: usize) -> Result<Mode, String> {
-        let mode = (self.0 / (10 as i64).pow(index as u32)) % (10 as i64).pow(index as u32 + 1);
+        let mode = (self.0 / 10_i64.pow(index as u32)) % 10_i64.pow(index as u32 + 1);
         match mode {
             0 => Ok(Mode::Position),
             1 => Ok(Mode::Immediate),
@@ -245,17 +249,38 @@ impl<I: Input, O: Output> Process<I, O> {
     }
 }
 
-fn main() {
-    let args: Vec<_> = std::env::args().collect();
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// The ID of the system to test, instead of 1 for part 1 or 5 for part 2
+    system: Option<i64>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
 
-    let program = Program::parse(stdin()).unwrap();
+fn main() {
+    let opts = Opts::from_args();
+    let program = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|input| Program::parse(input).unwrap());
+    let program = match program {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
 
-    let mut input = args[1].trim().parse().unwrap();
+    let mut input = opts
+        .system
+        .unwrap_or(if opts.common.part(2) == 1 { 1 } else { 5 });
     let mut output = 0;
 
     Process::new(&program, &mut input, &mut output).execute();
 
-    println!("{}", output);
+    opts.common.print_answers(&[("code", output.into())]);
 }
 
 #[test]
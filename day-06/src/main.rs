@@ -1,6 +1,9 @@
 use std::str::FromStr;
-use std::io::{BufRead, stdin};
+use std::io::BufRead;
 use std::collections::HashMap;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
 
 struct Orbit {
     object: String,
@@ -28,7 +31,6 @@ fn get_orbiters<'a>(orbits: impl Iterator<Item = &'a Orbit>) -> HashMap<String,
     orbiters
 }
 
-#[allow(dead_code)]
 fn total_orbits<'a>(orbits: impl Iterator<Item = &'a Orbit>) -> usize {
     let orbiters = get_orbiters(orbits);
     
@@ -68,9 +70,33 @@ fn orbit_transfers(orbits: Vec<Orbit>, from: String, to: String) -> usize {
     from_chain.len() + to_chain.len() - 2 * common_prefix(from_chain.iter(), to_chain.iter()).count()
 }
 
+#[derive(Debug, StructOpt)]
+struct Opts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
 fn main() {
-    let orbits: Vec<Orbit> = stdin().lock().lines().map(|line| line.unwrap().parse().unwrap()).collect();
-    println!("{}", orbit_transfers(orbits, "YOU".to_string(), "SAN".to_string()));
+    let opts = Opts::from_args();
+    let input = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input());
+    let input = match input {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    let orbits: Vec<Orbit> = input.lines().map(|line| line.unwrap().parse().unwrap()).collect();
+    if opts.common.part(2) == 1 {
+        opts.common.print_answers(&[("orbits", total_orbits(orbits.iter()).into())]);
+    } else {
+        let transfers = orbit_transfers(orbits, "YOU".to_string(), "SAN".to_string());
+        opts.common.print_answers(&[("transfers", transfers.into())]);
+    }
 }
 
 #[test]
@@ -109,4 +135,47 @@ fn transfers() {
         Orbit { centre: "I".to_string(), object: "SAN".to_string() },
     ];
     assert_eq!(orbit_transfers(orbits, "YOU".to_string(), "SAN".to_string()), 4);
-}
\ No newline at end of file
+}
+
+// The fewest steps between the objects `from` and `to` orbit, searching the
+// whole map without relying on it being a tree.
+#[cfg(test)]
+fn brute_force(orbits: &[Orbit], from: &str, to: &str) -> Option<usize> {
+    use std::collections::{HashSet, VecDeque};
+
+    let centre = |object: &str| {
+        orbits.iter().find(|orbit| orbit.object == object).map(|orbit| orbit.centre.clone())
+    };
+    let (start, end) = (centre(from)?, centre(to)?);
+
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    queue.push_back((start, 0));
+    while let Some((object, steps)) = queue.pop_front() {
+        if object == end {
+            return Some(steps);
+        }
+        if !seen.insert(object.clone()) {
+            continue;
+        }
+        for orbit in orbits {
+            if orbit.centre == object {
+                queue.push_back((orbit.object.clone(), steps + 1));
+            } else if orbit.object == object {
+                queue.push_back((orbit.centre.clone(), steps + 1));
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn generated() {
+    for seed in 0..50 {
+        let input = common::gen::orbits(seed, 30);
+        let orbits: Vec<Orbit> = input.lines().map(|line| line.parse().unwrap()).collect();
+        let expected = brute_force(&orbits, "YOU", "SAN");
+        let transfers = orbit_transfers(orbits, "YOU".to_string(), "SAN".to_string());
+        assert_eq!(Some(transfers), expected, "seed {}", seed);
+    }
+}
@@ -1,11 +1,11 @@
 use itertools::iproduct;
-use std::io::stdin;
+use structopt::StructOpt;
 
-mod process;
-mod program;
+use common::{CommonOpts, Flag};
 
-use process::{run_to_completion, Channel, Input, Output, Process};
-use program::Program;
+use intcode::process::{Channel, Input, Output, Process};
+use intcode::program::Program;
+use intcode::scheduler::Scheduler;
 
 fn get_output_signal(program: &Program, phase_settings: &[i64]) -> i64 {
     let channels: Vec<_> = phase_settings
@@ -25,7 +25,11 @@ fn get_output_signal(program: &Program, phase_settings: &[i64]) -> i64 {
 
     channels[0].put(0);
 
-    run_to_completion(processes.iter_mut().collect());
+    let mut scheduler = Scheduler::new();
+    for process in processes.iter_mut() {
+        scheduler.add(process);
+    }
+    scheduler.run();
 
     channels[0].get().unwrap()
 }
@@ -39,12 +43,30 @@ fn find_max_output_signal(program: &Program) -> i64 {
         .unwrap()
 }
 
+#[derive(Debug, StructOpt)]
+struct Opts {
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
 fn main() {
-    let program = Program::parse(stdin()).unwrap();
+    let opts = Opts::from_args();
+    let program = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|input| Program::parse(input).unwrap());
+    let program = match program {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
 
     let max_signal = find_max_output_signal(&program);
 
-    println!("{}", max_signal);
+    opts.common.print_answers(&[("signal", max_signal.into())]);
 }
 
 #[test]
@@ -57,7 +79,7 @@ fn output_signal_1() {
         .into_boxed_slice(),
     };
 
-    assert_eq!(get_output_signal(&program, &vec![9, 8, 7, 6, 5]), 139629729);
+    assert_eq!(get_output_signal(&program, &[9, 8, 7, 6, 5]), 139629729);
     assert_eq!(find_max_output_signal(&program), 139629729);
 }
 
@@ -72,6 +94,6 @@ fn output_signal_2() {
         .into_boxed_slice(),
     };
 
-    assert_eq!(get_output_signal(&program, &vec![9, 7, 8, 5, 6]), 18216);
+    assert_eq!(get_output_signal(&program, &[9, 7, 8, 5, 6]), 18216);
     assert_eq!(find_max_output_signal(&program), 18216);
 }
@@ -0,0 +1,226 @@
+use std::io::BufRead;
+use std::str;
+use std::fmt;
+use std::char;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
+
+const WIDTH: usize = 25;
+const HEIGHT: usize = 6;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Pixel {
+    Black,
+    White,
+    Transparent,
+}
+
+impl Pixel {
+    fn parse(c: char) -> Result<Self, String> {
+        use Pixel::*;
+        match c {
+            '0' => Ok(Black),
+            '1' => Ok(White),
+            '2' => Ok(Transparent),
+            _ => Err(format!("Invalid pixel {}", c)),
+        }
+    }
+
+    fn as_char(&self) -> char {
+        use Pixel::*;
+        match self {
+            Black => char::from_u32(0x2588).unwrap(),
+            _ => ' '
+        }
+    }
+
+    fn as_digit(self) -> char {
+        char::from_digit(self as u32, 10).unwrap()
+    }
+}
+
+struct Layer {
+    data: Box<[Pixel]>,
+    width: usize,
+    height: usize,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..self.height {
+            writeln!(f, "{}", self.data[row*self.width..(row+1)*self.width].iter().map(Pixel::as_char).collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+impl Layer {
+    fn new(data: Box<[Pixel]>, width: usize, height: usize) -> Self {
+        Layer {
+            data,
+            width,
+            height,
+        }
+    }
+
+    // The layer as it was in the image data, with a row on each line.
+    fn digits(&self) -> String {
+        self.data
+            .chunks(self.width)
+            .map(|row| row.iter().map(|pixel| pixel.as_digit()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    // How many of the pixels are 0, 1 and 2.
+    fn counts(&self) -> [usize; 3] {
+        let mut counts = [0; 3];
+        for &pixel in self.data.iter() {
+            counts[pixel as usize] += 1;
+        }
+        counts
+    }
+
+    fn merge(&self, layer: &Layer) -> Layer {
+        let data: Vec<_> = self
+            .data
+            .iter()
+            .zip(layer.data.iter())
+            .map(|(top, bottom)| {
+                if *top != Pixel::Transparent {
+                    *top
+                } else {
+                    *bottom
+                }
+            })
+            .collect();
+        Layer::new(data.into_boxed_slice(), self.width, self.height)
+    }
+}
+
+fn parse_layers<'a>(
+    data: &'a str,
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = Layer> + 'a {
+    data.trim().as_bytes().chunks(width * height).map(move |bytes| {
+        let data: Vec<_> = str::from_utf8(bytes)
+            .unwrap()
+            .chars()
+            .map(Pixel::parse)
+            .map(Result::unwrap)
+            .collect();
+        Layer::new(data.into_boxed_slice(), width, height)
+    })
+}
+
+fn combine_layers(mut layers: impl Iterator<Item = Layer>) -> Layer {
+    let first_layer = layers.next().unwrap();
+    layers.fold(first_layer, |current, layer| current.merge(&layer))
+}
+
+fn describe_counts(index: usize, [zeros, ones, twos]: [usize; 3]) -> String {
+    format!("Layer {}: {} zeros, {} ones, {} twos", index, zeros, ones, twos)
+}
+
+// The counts of each digit on every layer, followed by the layer with the
+// fewest zeros and the number of ones multiplied by the number of twos on it.
+fn stats(layers: &[Layer]) -> String {
+    let mut text = String::new();
+    for (index, layer) in layers.iter().enumerate() {
+        text += &describe_counts(index, layer.counts());
+        text += "\n";
+    }
+
+    let fewest_zeros = layers.iter().enumerate().min_by_key(|(_, layer)| layer.counts()[0]);
+    if let Some((index, layer)) = fewest_zeros {
+        let [_, ones, twos] = layer.counts();
+        text += &format!("Fewest zeros on layer {}, ones times twos is {}\n", index, ones * twos);
+    }
+    text
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Print the digits of the layer with this index, counting from 0 at the top, and how many
+    /// of each there are, instead of the image
+    #[structopt(long)]
+    layer: Option<usize>,
+
+    /// Print how many of each digit there are on every layer, instead of the image
+    #[structopt(long, conflicts_with = "layer")]
+    stats: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let mut data = String::new();
+    let read = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|mut input| input.read_line(&mut data).unwrap());
+    if let Err(message) = read {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+
+    if let Some(index) = opts.layer {
+        let mut layers = parse_layers(&data, WIDTH, HEIGHT);
+        let count = layers.by_ref().take(index).count();
+        match layers.next() {
+            Some(layer) => {
+                print!("{}", layer.digits());
+                println!("{}", describe_counts(index, layer.counts()));
+            }
+            None => {
+                let plural = if count == 1 { "" } else { "s" };
+                eprintln!("There's no layer {}, as the image has {} layer{}", index, count, plural);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if opts.stats {
+        let layers: Vec<Layer> = parse_layers(&data, WIDTH, HEIGHT).collect();
+        print!("{}", stats(&layers));
+        return;
+    }
+
+    let layers = parse_layers(&data, WIDTH, HEIGHT);
+
+    let layer = combine_layers(layers);
+
+    if opts.common.answers_only() {
+        opts.common.print_answers(&[("image", layer.to_string().into())]);
+    } else {
+        println!("{}", layer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn layers() {
+        let layers: Vec<Layer> = parse_layers("0222112222120000\n", 2, 2).collect();
+        assert_eq!(layers.len(), 4);
+        assert_eq!(layers[1].digits(), "11\n22\n");
+        assert_eq!(layers[1].counts(), [0, 2, 2]);
+        assert_eq!(layers[3].counts(), [4, 0, 0]);
+        assert_eq!(
+            stats(&layers),
+            "Layer 0: 1 zeros, 0 ones, 3 twos\n\
+             Layer 1: 0 zeros, 2 ones, 2 twos\n\
+             Layer 2: 0 zeros, 1 ones, 3 twos\n\
+             Layer 3: 4 zeros, 0 ones, 0 twos\n\
+             Fewest zeros on layer 1, ones times twos is 4\n"
+        );
+        assert_eq!(combine_layers(layers.into_iter()).digits(), "01\n10\n");
+    }
+}
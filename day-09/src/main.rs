@@ -0,0 +1,270 @@
+use itertools::Itertools;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use structopt::StructOpt;
+
+use common::{Answer, CommonOpts, Flag};
+use intcode::extension::{Extension, Extensions};
+use intcode::file::{FileInput, FileOutput};
+use intcode::process::{Channel, Input, Output, Process, State, Tee};
+use intcode::program::Program;
+use intcode::trace::Tracer;
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Stop with an error if the program does anything suspicious, like writing through an
+    /// immediate mode parameter
+    #[structopt(long)]
+    strict: bool,
+
+    /// Stop with an error if the program uses memory at or past this many cells
+    #[structopt(long)]
+    memory_limit: Option<usize>,
+
+    /// When the program faults, step back this many instructions and show what it ran on the way
+    #[structopt(long)]
+    rewind: Option<usize>,
+
+    /// Give the program host functions: opcode 50 prints the zero-terminated string at an address,
+    /// and opcode 51 writes a random number
+    #[structopt(long)]
+    host: bool,
+
+    /// Write every instruction the program runs to a file
+    #[structopt(long, parse(from_os_str))]
+    trace: Option<PathBuf>,
+
+    /// Give the program the values in a file, one per line or separated by commas, instead of the
+    /// part number
+    #[structopt(long, parse(from_os_str))]
+    feed: Option<PathBuf>,
+
+    /// Append the values the program outputs to a file instead of printing them
+    #[structopt(long, parse(from_os_str))]
+    append: Option<PathBuf>,
+
+    /// Append the values the program outputs to a file as well as printing them
+    #[structopt(long, parse(from_os_str), conflicts_with = "append")]
+    log: Option<PathBuf>,
+
+    /// Print how long the program had been running before each value it outputs
+    #[structopt(long, conflicts_with = "append")]
+    timestamps: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+// Run the program, passing each value it outputs on to `output` as soon as
+// it's been output.
+fn run_test_program(
+    program: &Program,
+    input: &dyn Input<i64>,
+    output: &dyn Output<i64>,
+    opts: &Opts,
+) -> Result<(), String> {
+    let produced = Channel::new();
+    let mut process = Process::new("test".to_string(), program, input, &produced);
+    process.set_strict(opts.strict);
+    if let Some(limit) = opts.memory_limit {
+        process.set_memory_limit(limit);
+    }
+    if opts.host {
+        process.set_extensions(Rc::new(host_functions()));
+    }
+    if let Some(steps) = opts.rewind {
+        process.record_history(steps);
+    }
+    let tracer = match &opts.trace {
+        Some(path) => {
+            let file = File::create(path)
+                .map_err(|error| format!("Can't create {}: {}", path.display(), error))?;
+            let tracer = Rc::new(RefCell::new(Tracer::new(BufWriter::new(file))));
+            process.add_hook(tracer.clone());
+            Some(tracer)
+        }
+        None => None,
+    };
+
+    let mut stream = process.stream(&produced);
+    let mut fault = None;
+    for value in &mut stream {
+        match value {
+            Ok(value) => output.put(value),
+            Err(error) => fault = Some(error),
+        }
+    }
+    let state = stream.state();
+
+    // In strict mode or when rewinding show where the program had got to as
+    // well, since it's probably being debugged.
+    let result = fault.map_or(Ok(state), |fault| {
+        Err(if opts.rewind.is_some() {
+            format!("{}\n{}", fault, rewind(&mut process))
+        } else if opts.strict {
+            format!("{}\n{}", fault, process)
+        } else {
+            fault.to_string()
+        })
+    });
+    if let Some(tracer) = tracer {
+        tracer.borrow_mut().finish()?;
+    }
+
+    match result? {
+        Some(State::Complete) => Ok(()),
+        _ => Err("The program wanted more input than it was given".to_string()),
+    }
+}
+
+fn host_functions() -> Extensions {
+    let print = Extension::new("print", 1, |values, memory| {
+        let text: String = memory
+            .iter()
+            .skip(values[0].max(0) as usize)
+            .take_while(|&&c| c != 0)
+            .map(|&c| c as u8 as char)
+            .collect();
+        print!("{}", text);
+    });
+
+    // A xorshift generator, which is plenty random enough for a puzzle.
+    let mut state = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(1, |time| time.as_nanos() as u64)
+        | 1;
+    let random = Extension::returning("random", 0, move |_, _| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 1) as i64
+    });
+
+    let mut extensions = Extensions::new();
+    extensions.register(50, print).unwrap();
+    extensions.register(51, random).unwrap();
+    extensions
+}
+
+// Step back through the history one instruction at a time, so each one can be
+// disassembled as it was when it ran, and describe the state it started from.
+fn rewind<I, O>(process: &mut Process<I, O>) -> String {
+    let mut instructions = vec![];
+    while let Some(address) = process.history().last().map(|step| step.address) {
+        process.step_back(1);
+        instructions.push((address, process.disassemble(address)));
+    }
+    instructions.reverse();
+
+    let mut description = format!(
+        "{} instructions earlier:\n{}\nthen ran:",
+        instructions.len(),
+        process
+    );
+    for (address, instruction) in instructions {
+        description += &format!("\n{:>8}: {}", address, instruction);
+    }
+    description
+}
+
+// Prints each value, after how long it took to produce if asked.
+struct Printer {
+    start: Instant,
+    timestamps: bool,
+}
+
+impl Printer {
+    fn new(timestamps: bool) -> Self {
+        Printer {
+            start: Instant::now(),
+            timestamps,
+        }
+    }
+}
+
+impl Output<i64> for Printer {
+    fn put(&self, value: i64) {
+        if self.timestamps {
+            println!("[{:>9.3}s] {}", self.start.elapsed().as_secs_f64(), value);
+        } else {
+            println!("{}", value);
+        }
+    }
+}
+
+fn run(program: &Program, opts: &Opts) -> Result<(), String> {
+    let input: Box<dyn Input<i64>> = match &opts.feed {
+        Some(path) => Box::new(FileInput::open(path)?),
+        None => {
+            let input = Channel::new();
+            input.put(opts.common.part(2).into());
+            Box::new(input)
+        }
+    };
+
+    // When only the answers are wanted they're kept to print at the end.
+    let answers = Channel::new();
+    let printer: Box<dyn Output<i64>> = if opts.common.answers_only() {
+        Box::new(&answers)
+    } else {
+        Box::new(Printer::new(opts.timestamps))
+    };
+
+    match (&opts.append, &opts.log) {
+        (Some(path), _) => {
+            let output = FileOutput::append(path)?;
+            run_test_program(program, &input, &output, opts)?;
+            output.finish()?;
+        }
+        (None, Some(path)) => {
+            let output = Tee::new(printer, FileOutput::append(path)?);
+            let result = run_test_program(program, &input, &output, opts);
+            let (_, logged) = output.into_inner();
+            logged.finish()?;
+            result?;
+        }
+        (None, None) => {
+            run_test_program(program, &input, &printer, opts)?;
+        }
+    }
+
+    if opts.common.answers_only() && opts.append.is_none() {
+        let name = if opts.common.part(2) == 1 {
+            "keycode"
+        } else {
+            "coordinates"
+        };
+        let values = answers.drain();
+        let answer: Answer = match values[..] {
+            [value] => value.into(),
+            _ => values.iter().join(",").into(),
+        };
+        opts.common.print_answers(&[(name, answer)]);
+    }
+    Ok(())
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let program = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|input| Program::parse(input).unwrap());
+    let program = match program {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(message) = run(&program, &opts) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use termion::event::Key;
+use termion::get_tty;
+use termion::input::TermRead;
+use termion::{clear, color, cursor};
+
+use crate::{base_location, Map, Position};
+
+// Shows the map with a cursor on it, picking out the asteroids which can be
+// seen from wherever the cursor is.
+pub struct Explorer<W> {
+    screen: W,
+    cursor: Position,
+}
+
+impl<W: Write> Explorer<W> {
+    pub fn new(mut screen: W, cursor: Position) -> Self {
+        let _ = write!(screen, "{}", clear::All);
+        Explorer { screen, cursor }
+    }
+
+    // Move the cursor, stopping at the edges of the map.
+    pub fn step(&mut self, map: &Map, (dx, dy): (isize, isize)) {
+        let (x, y) = self.cursor;
+        let x = (x as isize + dx).max(0).min(map.width() as isize - 1);
+        let y = (y as isize + dy).max(0).min(map.height() as isize - 1);
+        self.cursor = (x as usize, y as usize);
+    }
+
+    pub fn jump(&mut self, position: Position) {
+        self.cursor = position;
+    }
+
+    pub fn draw(&mut self, map: &Map) -> io::Result<()> {
+        let visible: HashSet<Position> = map.viewable_from(self.cursor).collect();
+
+        write!(self.screen, "{}", cursor::Goto(1, 1))?;
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                let position = (x, y);
+                let tile = if map.asteroid_at(position) { '#' } else { '.' };
+                let text = if position == self.cursor {
+                    format!("{}{}{}", color::Bg(color::Yellow), tile, color::Bg(color::Reset))
+                } else if visible.contains(&position) {
+                    format!("{}{}{}", color::Fg(color::Green), tile, color::Fg(color::Reset))
+                } else if tile == '#' {
+                    format!("{}{}{}", color::Fg(color::LightBlack), tile, color::Fg(color::Reset))
+                } else {
+                    tile.to_string()
+                };
+                write!(self.screen, "{}", text)?;
+            }
+            write!(self.screen, "\r\n")?;
+        }
+
+        let (x, y) = self.cursor;
+        write!(
+            self.screen,
+            "\r\n({}, {}): {} asteroids visible{}{}\r\n",
+            x,
+            y,
+            visible.len(),
+            if map.asteroid_at(self.cursor) { "" } else { " (no asteroid here)" },
+            clear::UntilNewline
+        )?;
+        write!(self.screen, "arrows/hjkl: move, b: best location, q: quit\r\n")?;
+        self.screen.flush()
+    }
+}
+
+// Let the cursor be moved around the map with the keyboard until asked to
+// quit, starting at the best location for the station.
+pub fn explore(map: &Map, screen: impl Write) -> io::Result<()> {
+    let mut explorer = Explorer::new(screen, base_location(map));
+    explorer.draw(map)?;
+
+    for key in get_tty()?.keys() {
+        match key? {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => break,
+            Key::Left | Key::Char('h') => explorer.step(map, (-1, 0)),
+            Key::Right | Key::Char('l') => explorer.step(map, (1, 0)),
+            Key::Up | Key::Char('k') => explorer.step(map, (0, -1)),
+            Key::Down | Key::Char('j') => explorer.step(map, (0, 1)),
+            Key::Char('b') => explorer.jump(base_location(map)),
+            _ => continue,
+        }
+        explorer.draw(map)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn draw() {
+        let map: Map = ".#..#\n.....\n#####\n....#\n...##".parse().unwrap();
+        let mut output = vec![];
+        let mut explorer = Explorer::new(&mut output, (0, 0));
+        explorer.step(&map, (-1, 5));
+        explorer.draw(&map).unwrap();
+        explorer.jump((3, 4));
+        explorer.draw(&map).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("(0, 4): 8 asteroids visible (no asteroid here)"));
+        assert!(text.contains("(3, 4): 8 asteroids visible"));
+    }
+}
@@ -1,11 +1,19 @@
 use gcd::Gcd;
 use itertools::iproduct;
-use std::io::{stdin, Read};
+use std::io::{stdout, Read};
 use std::str::FromStr;
 use std::cmp::Ordering;
 use std::f64::consts::PI;
+use structopt::StructOpt;
+use termion::cursor;
+use termion::raw::IntoRawMode;
+
+mod explorer;
+
+use common::{CommonOpts, Flag};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Error(String);
 
 impl<T: ToString> From<T> for Error {
@@ -74,7 +82,7 @@ impl Map {
         } else if offset.1 == 0 {
             offset.0.abs()
         } else {
-             (offset.0.abs() as usize).gcd(offset.1.abs() as usize) as isize
+             offset.0.unsigned_abs().gcd(offset.1.unsigned_abs()) as isize
         };
 
         Iterator::zip(
@@ -138,10 +146,47 @@ fn nth_destroyed(map: &mut Map, mut index: usize) -> Position {
     asteroids[index-1]
 }
 
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Move a cursor around the map to see which asteroids can be seen from each position
+    #[structopt(long, conflicts_with_all = &["quiet", "json"])]
+    explore: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
 fn main() {
-    let mut map = Map::read(stdin().lock()).unwrap();
+    let opts = Opts::from_args();
+    let map = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|input| Map::read(input).unwrap());
+    let mut map = match map {
+        Ok(map) => map,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if opts.explore {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        if let Err(error) = explorer::explore(&map, screen) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let position = nth_destroyed(&mut map, 200);
-    println!("{}", position.0 * 100 + position.1);
+    let answer = position.0 * 100 + position.1;
+    if opts.common.answers_only() {
+        opts.common.print_answers(&[("asteroid", answer.into())]);
+    } else {
+        println!("{}", answer);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,325 @@
+use std::char;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::iter::once;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
+use intcode::process::{Channel, Input, Output, Process, State};
+use intcode::program::Program;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl TryFrom<isize> for Direction {
+    type Error = String;
+
+    fn try_from(value: isize) -> Result<Self, Self::Error> {
+        use Direction::*;
+        match value {
+            0 => Ok(North),
+            1 => Ok(East),
+            2 => Ok(South),
+            3 => Ok(West),
+            _ => Err(format!("Invalid direction {}", value)),
+        }
+    }
+}
+
+impl Direction {
+    fn turn(&mut self, turn: Turn) {
+        use Turn::*;
+        let increment = match turn {
+            Left => 3,
+            Right => 1,
+        };
+        *self = ((*self as isize + increment) % 4).try_into().unwrap();
+    }
+
+    fn move_from(&self, (x, y): (isize, isize)) -> (isize, isize) {
+        use Direction::*;
+        match *self {
+            North => (x, y - 1),
+            East => (x + 1, y),
+            South => (x, y + 1),
+            West => (x - 1, y),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Turn {
+    Left,
+    Right,
+}
+
+impl TryFrom<i64> for Turn {
+    type Error = ();
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Turn::Left),
+            1 => Ok(Turn::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Colour {
+    #[default]
+    Black,
+    White,
+}
+
+impl Colour {
+    fn as_char(self) -> char {
+        use Colour::*;
+        match self {
+            White => char::from_u32(0x2588).unwrap(),
+            Black => ' ',
+        }
+    }
+}
+
+
+impl TryFrom<&str> for Colour {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "0" | "black" => Ok(Colour::Black),
+            "1" | "white" => Ok(Colour::White),
+            _ => Err(format!("Unknown colour {}", value)),
+        }
+    }
+}
+
+impl TryFrom<i64> for Colour {
+    type Error = ();
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Colour::Black),
+            1 => Ok(Colour::White),
+            _ => Err(()),
+        }
+    }
+}
+
+struct Robot {
+    position: (isize, isize),
+    direction: Direction,
+}
+
+impl Robot {
+    fn new() -> Self {
+        Robot {
+            position: (0, 0),
+            direction: Direction::North,
+        }
+    }
+
+    fn turn(&mut self, turn: Turn) {
+        self.direction.turn(turn)
+    }
+
+    fn move_forwards(&mut self) {
+        self.position = self.direction.move_from(self.position)
+    }
+}
+
+enum Signal {
+    Paint,
+    Turn,
+}
+
+impl Signal {
+    fn flip(&mut self) {
+        use Signal::*;
+        *self = match *self {
+            Paint => Turn,
+            Turn => Paint,
+        }
+    }
+}
+
+// A panel painted before the robot starts, written as "x,y,colour", where the
+// colour is black or white, or 0 or 1 as the robot paints them.
+fn parse_panel(line: &str) -> Result<((isize, isize), Colour), String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    match fields[..] {
+        [x, y, colour] => {
+            let x = x.parse().map_err(|_| format!("Invalid x coordinate {}", x))?;
+            let y = y.parse().map_err(|_| format!("Invalid y coordinate {}", y))?;
+            Ok(((x, y), Colour::try_from(colour)?))
+        }
+        _ => Err("Expected x,y,colour".to_string()),
+    }
+}
+
+fn read_panels(input: impl BufRead) -> Result<HashMap<(isize, isize), Colour>, String> {
+    let mut cells = HashMap::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|error| error.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (position, colour) = parse_panel(&line)
+            .map_err(|error| format!("Line {} of the panels: {}", index + 1, error))?;
+        cells.insert(position, colour);
+    }
+    Ok(cells)
+}
+
+// Run the robot from the origin over the panels already painted, returning
+// every panel which has been painted by the end.
+fn paint(
+    program: &Program,
+    mut cells: HashMap<(isize, isize), Colour>,
+) -> HashMap<(isize, isize), Colour> {
+    let mut robot = Robot::new();
+
+    let input = Channel::new();
+    let output = Channel::new();
+    let mut process = Process::new("Robot".to_string(), program, &input, &output);
+    let mut signal = Signal::Paint;
+
+    loop {
+        // The robot can paint and move after the last input it's given, so
+        // its outputs are handled before checking whether it's finished.
+        let state = process.execute();
+        while let Some(value) = output.get() {
+            match signal {
+                Signal::Paint => {
+                    cells.insert(robot.position, value.try_into().unwrap());
+                }
+                Signal::Turn => {
+                    robot.turn(value.try_into().unwrap());
+                    robot.move_forwards();
+                }
+            }
+            signal.flip()
+        }
+        if state == State::Complete {
+            break;
+        }
+
+        input.put(cells.get(&robot.position).cloned().unwrap_or_default() as i64);
+    }
+
+    cells
+}
+
+fn render_cells(cells: &HashMap<(isize, isize), Colour>) -> String {
+    let min_x = cells.keys().map(|(x, _)| x).min().cloned().unwrap();
+    let max_x = cells.keys().map(|(x, _)| x).max().cloned().unwrap();
+    let min_y = cells.keys().map(|(_, y)| y).min().cloned().unwrap();
+    let max_y = cells.keys().map(|(_, y)| y).max().cloned().unwrap();
+    let mut text = String::new();
+    for y in min_y..=max_y {
+        let row = (min_x..=max_x).map(|x| cells.get(&(x, y)).cloned().unwrap_or_default());
+        text += &row.map(Colour::as_char).collect::<String>();
+        text += "\n";
+    }
+    text
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Start with the panels in this file painted, one per line as "x,y,colour", rather than just
+    /// the panel the robot starts on painted white
+    #[structopt(long, parse(from_os_str))]
+    panels: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let program = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(|input| Program::parse(input).unwrap());
+    let program = match program {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    let cells = match &opts.panels {
+        Some(path) => {
+            let panels = File::open(path)
+                .map_err(|error| format!("Can't open {}: {}", path.display(), error))
+                .and_then(|file| read_panels(BufReader::new(file)));
+            match panels {
+                Ok(panels) => panels,
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => once(((0, 0), Colour::White)).collect(),
+    };
+
+    let cells = paint(&program, cells);
+    let hull = render_cells(&cells);
+    if opts.common.answers_only() {
+        opts.common.print_answers(&[("hull", hull.into())]);
+    } else {
+        print!("{}", hull);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn panels() {
+        let panels = read_panels("0,0,white\n\n-3, 2, 0\n5,-1,black\n1,1,1\n".as_bytes());
+        let panels = panels.unwrap();
+        assert_eq!(panels.len(), 4);
+        assert_eq!(panels[&(0, 0)], Colour::White);
+        assert_eq!(panels[&(-3, 2)], Colour::Black);
+        assert_eq!(panels[&(5, -1)], Colour::Black);
+        assert_eq!(panels[&(1, 1)], Colour::White);
+
+        assert!(read_panels("0,0".as_bytes()).is_err());
+        assert!(read_panels("0,x,white".as_bytes()).is_err());
+        assert!(read_panels("0,0,red".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn paint_over() {
+        // Paints the panel it's on the opposite colour, then turns right and
+        // moves, four times over.
+        let program = Program::parse(
+            "3,100,1008,100,0,101,4,101,104,1,1001,102,1,102,1007,102,4,103,1005,103,0,99"
+                .as_bytes(),
+        )
+        .unwrap();
+        let start = read_panels("0,0,white\n1,0,white\n3,3,white".as_bytes()).unwrap();
+        let cells = paint(&program, start);
+        assert_eq!(cells.len(), 5);
+        assert_eq!(cells[&(0, 0)], Colour::Black);
+        assert_eq!(cells[&(1, 0)], Colour::Black);
+        assert_eq!(cells[&(1, 1)], Colour::White);
+        assert_eq!(cells[&(0, 1)], Colour::White);
+        assert_eq!(cells[&(3, 3)], Colour::White);
+    }
+}
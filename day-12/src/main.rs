@@ -0,0 +1,341 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::collections::HashSet;
+use std::hash::Hash;
+use structopt::StructOpt;
+
+use common::{CommonOpts, Flag};
+
+#[derive(Clone)]
+struct Vector([i64; 3]);
+
+impl Vector {
+    fn zero() -> Self {
+        Vector([0, 0, 0])
+    }
+
+    fn sum(&self) -> i64 {
+        self.0.iter().cloned().map(i64::abs).sum()
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+impl FromStr for Vector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if !s.starts_with('<') || !s.ends_with('>') {
+            return Err(Error("Missing <, or >".to_string()));
+        }
+
+        let mut result = [0; 3];
+        for part in s[1..s.len() - 1].split(',') {
+            let mut parts = part.splitn(2, '=');
+            let index = match parts.next().unwrap().trim() {
+                "x" => 0,
+                "y" => 1,
+                "z" => 2,
+                c => return Err(Error(format!("Unknown component {:?}", c))),
+            };
+
+            if let Some(value) = parts.next() {
+                result[index] = value.trim().parse()?;
+            } else {
+                return Err(Error("No value for component".to_string()));
+            }
+        }
+        result[0] = 0;
+        result[1] = 0;
+        Ok(Vector(result))
+    }
+}
+
+#[derive(Clone)]
+struct Moon {
+    position: Vector,
+    velocity: Vector,
+}
+
+impl Moon {
+    fn new(position: Vector) -> Self {
+        Moon {
+            position,
+            velocity: Vector::zero(),
+        }
+    }
+
+    fn potential_energy(&self) -> i64 {
+        self.position.sum()
+    }
+
+    fn kinetic_energy(&self) -> i64 {
+        self.velocity.sum()
+    }
+
+    fn total_energy(&self) -> i64 {
+        self.potential_energy() * self.kinetic_energy()
+    }
+
+    fn step(&mut self) {
+        for axis in 0..3 {
+            self.position.0[axis] += self.velocity.0[axis];
+        }
+    }
+}
+
+fn update_velocity(moon_a: &mut Moon, moon_b: &mut Moon) {
+    for axis in 0..3 {
+        match moon_a.position.0[axis].cmp(&moon_b.position.0[axis]) {
+            Ordering::Less => {
+                moon_a.velocity.0[axis] += 1;
+                moon_b.velocity.0[axis] -= 1;
+            }
+            Ordering::Greater => {
+                moon_a.velocity.0[axis] -= 1;
+                moon_b.velocity.0[axis] += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_gravity(moons: &mut [Moon]) {
+    for i in 0..moons.len() {
+        for j in i + 1..moons.len() {
+            let (left, right) = moons.split_at_mut(j);
+            update_velocity(&mut left[i], &mut right[0])
+        }
+    }
+}
+
+fn update_position(moons: &mut [Moon]) {
+    for moon in moons {
+        moon.step()
+    }
+}
+
+fn step(moons: &mut [Moon]) {
+    apply_gravity(moons);
+    update_position(moons);
+}
+
+fn parse_moons(input: impl BufRead) -> Box<[Moon]> {
+    input
+        .lines()
+        .map(Result::unwrap)
+        .map(|line| line.parse().unwrap())
+        .map(Moon::new)
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+fn total_energy(moons: &[Moon]) -> i64 {
+    moons.iter().map(Moon::total_energy).sum()
+}
+
+fn key(moons: &[Moon]) -> impl Hash + Eq {
+    let mut result: Vec<i64> = Vec::new();
+    for moon in moons {
+        result.extend(&moon.position.0);
+        result.extend(&moon.velocity.0);
+    }
+    result
+}
+
+fn find_cycle(moons: &mut [Moon]) -> usize {
+    let mut seen = HashSet::new();
+    let mut num_steps = 0;
+
+    while !seen.contains(&key(moons)) {
+        seen.insert(key(moons));
+        step(moons);
+        num_steps += 1;
+    }
+
+    num_steps
+}
+
+// Write the energy of the system before the first step and after each one as
+// CSV, along with where each moon is if asked, so it can be plotted.
+fn write_metrics(
+    moons: &mut [Moon],
+    steps: usize,
+    positions: bool,
+    mut output: impl Write,
+) -> std::io::Result<()> {
+    let mut header: Vec<String> = vec!["step", "total", "kinetic", "potential"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    if positions {
+        for index in 0..moons.len() {
+            header.extend(["x", "y", "z"].iter().map(|axis| format!("moon{}_{}", index, axis)));
+        }
+    }
+    writeln!(output, "{}", header.join(","))?;
+
+    for num_steps in 0..=steps {
+        if num_steps > 0 {
+            step(moons);
+        }
+        let kinetic: i64 = moons.iter().map(Moon::kinetic_energy).sum();
+        let potential: i64 = moons.iter().map(Moon::potential_energy).sum();
+        let mut row = vec![
+            num_steps.to_string(),
+            total_energy(moons).to_string(),
+            kinetic.to_string(),
+            potential.to_string(),
+        ];
+        if positions {
+            for moon in moons.iter() {
+                row.extend(moon.position.0.iter().map(i64::to_string));
+            }
+        }
+        writeln!(output, "{}", row.join(","))?;
+    }
+    output.flush()
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Write the energy of the system at each step to a CSV file
+    #[structopt(long, parse(from_os_str))]
+    metrics: Option<PathBuf>,
+
+    /// How many steps to write metrics for
+    #[structopt(long, default_value = "1000")]
+    steps: usize,
+
+    /// Include where each moon is in the metrics
+    #[structopt(long, requires = "metrics")]
+    positions: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let moons = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map(parse_moons);
+    let mut moons = match moons {
+        Ok(moons) => moons,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(path) = &opts.metrics {
+        let result = File::create(path).and_then(|file| {
+            write_metrics(&mut moons.clone(), opts.steps, opts.positions, BufWriter::new(file))
+        });
+        if let Err(error) = result {
+            eprintln!("Can't write metrics to {}: {}", path.display(), error);
+            std::process::exit(1);
+        }
+    }
+
+    let (name, answer) = if opts.common.part(2) == 1 {
+        for _ in 0..1000 {
+            step(&mut moons);
+        }
+        ("energy", total_energy(&moons) as usize)
+    } else {
+        ("steps", find_cycle(&mut moons))
+    };
+    if opts.common.answers_only() {
+        opts.common.print_answers(&[(name, answer.into())]);
+    } else {
+        println!("{}", answer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        let mut moons = vec![
+            Moon::new(Vector([-1, 0, 2])),
+            Moon::new(Vector([2, -10, -7])),
+            Moon::new(Vector([4, -8, 8])),
+            Moon::new(Vector([3, 5, -1])),
+        ];
+
+        step(&mut moons);
+
+        assert_eq!(moons[0].position.0, [2, -1, 1]);
+        assert_eq!(moons[1].position.0, [3, -7, -4]);
+        assert_eq!(moons[2].position.0, [1, -7, 5]);
+        assert_eq!(moons[3].position.0, [2, 2, 0]);
+
+        step(&mut moons);
+
+        assert_eq!(moons[0].position.0, [5, -3, -1]);
+        assert_eq!(moons[1].position.0, [1, -2, 2]);
+        assert_eq!(moons[2].position.0, [1, -4, -1]);
+        assert_eq!(moons[3].position.0, [1, -4, 2]);
+    }
+
+    #[test]
+    fn metrics() {
+        let mut moons = vec![
+            Moon::new(Vector([-1, 0, 2])),
+            Moon::new(Vector([2, -10, -7])),
+            Moon::new(Vector([4, -8, 8])),
+            Moon::new(Vector([3, 5, -1])),
+        ];
+
+        let mut output = vec![];
+        write_metrics(&mut moons, 10, true, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert!(lines[0].starts_with("step,total,kinetic,potential,moon0_x,moon0_y,moon0_z,"));
+        assert!(lines[0].ends_with(",moon3_z"));
+        assert_eq!(lines[1], "0,0,0,51,-1,0,2,2,-10,-7,4,-8,8,3,5,-1");
+        assert_eq!(lines[11], "10,179,22,31,2,1,-3,1,-8,0,3,-6,1,2,0,4");
+    }
+
+    // Step until the moons are back where they started, which is always the
+    // first state to repeat as every state has only one state before it.
+    fn brute_force(moons: &mut [Moon]) -> usize {
+        let start = key(moons);
+        let mut num_steps = 0;
+        loop {
+            step(moons);
+            num_steps += 1;
+            if key(moons) == start {
+                return num_steps;
+            }
+        }
+    }
+
+    #[test]
+    fn generated() {
+        for seed in 0..10 {
+            let input = common::gen::moons(seed, 3);
+            let cycle_len = find_cycle(&mut parse_moons(input.as_bytes()));
+            let expected = brute_force(&mut parse_moons(input.as_bytes()));
+            assert_eq!(cycle_len, expected, "seed {}", seed);
+        }
+    }
+}
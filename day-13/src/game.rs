@@ -0,0 +1,460 @@
+// The arcade cabinet on its own, without a terminal or a real joystick: run it
+// until it wants the joystick moved, look at the screen, then tilt the
+// joystick and carry on.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use crate::process::{Channel, DynInput, DynOutput, DynProcess, Input, Output, State};
+use crate::program::Program;
+use crate::transcript::{Recorder, RecordingInput, RecordingOutput, Stub, Transcript};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Tile {
+    #[default]
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl TryFrom<i64> for Tile {
+    type Error = i64;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        use Tile::*;
+        match value {
+            0 => Ok(Empty),
+            1 => Ok(Wall),
+            2 => Ok(Block),
+            3 => Ok(Paddle),
+            4 => Ok(Ball),
+            _ => Err(value),
+        }
+    }
+}
+
+// A change to what's on the screen.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Update {
+    Tile([u16; 2], Tile),
+    Score(i64),
+}
+
+// How a game which has finished ended.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Outcome {
+    // Every block was broken.
+    Won,
+    // The ball got past the paddle with blocks left.
+    Lost,
+}
+
+#[derive(Clone)]
+pub struct GameState {
+    pub score: i64,
+    pub ball_position: [u16; 2],
+    pub paddle_position: [u16; 2],
+    pub ball_velocity: [i16; 2],
+    pub cells: HashMap<[u16; 2], Tile>,
+    // How many times the ball and the paddle have been drawn somewhere new.
+    pub ball_moves: usize,
+    pub paddle_moves: usize,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState {
+            score: 0,
+            ball_position: [19, 17],
+            paddle_position: [0, 0],
+            ball_velocity: [1, 1],
+            cells: HashMap::new(),
+            ball_moves: 0,
+            paddle_moves: 0,
+        }
+    }
+}
+
+impl GameState {
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Score(score) => self.score = score,
+            Update::Tile(position, tile) => {
+                match tile {
+                    Tile::Ball if position != self.ball_position => {
+                        self.ball_velocity = [
+                            position[0] as i16 - self.ball_position[0] as i16,
+                            position[1] as i16 - self.ball_position[1] as i16,
+                        ];
+                        self.ball_position = position;
+                        self.ball_moves += 1;
+                    }
+                    Tile::Paddle if position != self.paddle_position => {
+                        self.paddle_position = position;
+                        self.paddle_moves += 1;
+                    }
+                    _ => {}
+                }
+                self.cells.insert(position, tile);
+            }
+        }
+    }
+
+    // The width and height of the screen.
+    pub fn size(&self) -> [u16; 2] {
+        let width = self.cells.keys().map(|[x, _]| x + 1).max().unwrap_or(0);
+        let height = self.cells.keys().map(|[_, y]| y + 1).max().unwrap_or(0);
+        [width, height]
+    }
+
+    pub fn blocks(&self) -> usize {
+        self.cells
+            .values()
+            .filter(|&&tile| tile == Tile::Block)
+            .count()
+    }
+
+    // The screen as plain text, a line per row.
+    pub fn render(&self) -> String {
+        let [width, height] = self.size();
+        let mut text = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                text.push(match self.cells.get(&[x, y]).cloned().unwrap_or_default() {
+                    Tile::Empty => ' ',
+                    Tile::Wall => '#',
+                    Tile::Block => 'X',
+                    Tile::Paddle => '-',
+                    Tile::Ball => 'O',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // Every tile on the screen, a row at a time, as the number the game uses
+    // for it.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        let [width, height] = self.size();
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .map(|position| self.cells.get(&position).cloned().unwrap_or_default() as u8)
+            .collect()
+    }
+}
+
+fn offset_from(position: &[u16; 2], offset: &[i16; 2]) -> [u16; 2] {
+    [
+        (position[0] as i16 + offset[0]) as u16,
+        (position[1] as i16 + offset[1]) as u16,
+    ]
+}
+
+// Move the ball on one step, bouncing it off anything in the way.
+pub fn step(state: &mut GameState) {
+    loop {
+        let mut collision = false;
+
+        for axis in 0..2 {
+            let mut offset = [0; 2];
+            offset[axis] = state.ball_velocity[axis];
+            let position = offset_from(&state.ball_position, &offset);
+
+            let next_tile = state.cells.get(&position).cloned().unwrap_or_default();
+
+            if next_tile != Tile::Empty {
+                collision = true;
+                state.ball_velocity[axis] *= -1;
+                if next_tile == Tile::Block {
+                    state.cells.insert(position, Tile::Empty);
+                }
+            }
+        }
+
+        let position = offset_from(&state.ball_position, &state.ball_velocity);
+        let next_tile = state.cells.get(&position).cloned().unwrap_or_default();
+        if next_tile != Tile::Empty {
+            collision = true;
+            for axis in 0..2 {
+                state.ball_velocity[axis] *= -1;
+            }
+            if next_tile == Tile::Block {
+                state.cells.insert(position, Tile::Empty);
+            }
+        }
+
+        if !collision {
+            break;
+        }
+    }
+
+    state.cells.insert(state.ball_position, Tile::Empty);
+    for axis in 0..2 {
+        state.ball_position[axis] =
+            (state.ball_position[axis] as i16 + state.ball_velocity[axis]) as u16;
+    }
+    state.cells.insert(state.ball_position, Tile::Ball);
+}
+
+fn calculate_intersect(mut state: GameState) -> u16 {
+    while state.ball_position[1] < state.paddle_position[1] - 1 {
+        step(&mut state);
+    }
+    state.ball_position[0]
+}
+
+// Which way to tilt the joystick to get the paddle under the ball in time.
+pub fn autopilot(state: &GameState) -> i64 {
+    let intersect = calculate_intersect(state.clone());
+    match intersect.cmp(&state.paddle_position[0]) {
+        std::cmp::Ordering::Greater => 1,
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+    }
+}
+
+// What the joystick and screen are plugged into: the game itself, or a stub
+// replaying a transcript of it.
+enum Machine {
+    Game(DynProcess<'static>),
+    Stub(Stub<DynInput<'static>, DynOutput<'static>>),
+}
+
+impl Machine {
+    fn execute(&mut self) -> State {
+        match self {
+            Machine::Game(process) => process.execute(),
+            Machine::Stub(stub) => stub.execute(),
+        }
+    }
+}
+
+pub struct Arcade {
+    machine: Machine,
+    joystick: Rc<Channel<i64>>,
+    screen: Rc<Channel<i64>>,
+    recorder: Rc<Recorder>,
+    state: GameState,
+    finished: bool,
+}
+
+impl Arcade {
+    // Without free play the game just draws the screen and stops.
+    pub fn new(program: &Program, free_play: bool) -> Self {
+        Arcade::plugged_into(|joystick, screen, recorder| {
+            let mut process = DynProcess::new("Game".to_string(), program, joystick, screen);
+            if free_play {
+                process.set(0, 2);
+                recorder.set(0, 2);
+            }
+            Machine::Game(process)
+        })
+    }
+
+    // An arcade which plays back the screen from a transcript of a game rather
+    // than running it, to see what the joystick does with it.
+    pub fn stub(transcript: Transcript) -> Self {
+        Arcade::plugged_into(|joystick, screen, recorder| {
+            for &(address, value) in transcript.patches.iter() {
+                recorder.set(address, value);
+            }
+            Machine::Stub(Stub::new(transcript, joystick, screen))
+        })
+    }
+
+    fn plugged_into(
+        machine: impl FnOnce(DynInput<'static>, DynOutput<'static>, &Recorder) -> Machine,
+    ) -> Self {
+        let joystick = Rc::new(Channel::new());
+        let screen = Rc::new(Channel::new());
+        let recorder = Recorder::new();
+        let machine = machine(
+            Box::new(RecordingInput::new(joystick.clone(), &recorder)),
+            Box::new(RecordingOutput::new(screen.clone(), &recorder)),
+            &recorder,
+        );
+
+        Arcade {
+            machine,
+            joystick,
+            screen,
+            recorder,
+            state: GameState::default(),
+            finished: false,
+        }
+    }
+
+    // Run the game until it needs the joystick moved, or it's over, returning
+    // what changed on the screen.
+    pub fn run(&mut self) -> Vec<Update> {
+        if !self.finished {
+            self.finished = self.machine.execute() == State::Complete;
+            if self.finished {
+                self.recorder.halt();
+            }
+        }
+
+        let mut updates = vec![];
+        while let (Some(x), Some(y), Some(value)) =
+            (self.screen.get(), self.screen.get(), self.screen.get())
+        {
+            let update = if (x, y) == (-1, 0) {
+                Update::Score(value)
+            } else {
+                Update::Tile([x as u16, y as u16], Tile::try_from(value).unwrap())
+            };
+            self.state.update(update);
+            updates.push(update);
+        }
+        updates
+    }
+
+    // -1 for left, 1 for right, and 0 to leave the paddle where it is.
+    pub fn tilt(&mut self, direction: i64) {
+        self.joystick.put(direction);
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Whether the game was won or lost, once it's finished.
+    pub fn outcome(&self) -> Option<Outcome> {
+        match (self.finished, self.state.blocks()) {
+            (false, _) => None,
+            (true, 0) => Some(Outcome::Won),
+            (true, _) => Some(Outcome::Lost),
+        }
+    }
+
+    // Where the joystick stopped doing what it did in the transcript, when
+    // running from one.
+    pub fn divergence(&self) -> Option<&str> {
+        match &self.machine {
+            Machine::Game(_) => None,
+            Machine::Stub(stub) => stub.divergence(),
+        }
+    }
+
+    // Everything the game has read from the joystick and drawn so far.
+    pub fn transcript(&self) -> Transcript {
+        self.recorder.transcript()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arcade() {
+        // Draws a paddle and a score, then draws whatever the joystick says
+        // in the top left corner.
+        let program = Program::parse(
+            "104,1,104,2,104,3,104,-1,104,0,104,7,3,100,104,0,104,0,4,100,99".as_bytes(),
+        )
+        .unwrap();
+        let mut arcade = Arcade::new(&program, false);
+
+        assert_eq!(
+            arcade.run(),
+            [Update::Tile([1, 2], Tile::Paddle), Update::Score(7)]
+        );
+        assert!(!arcade.is_finished());
+        assert_eq!(arcade.state().size(), [2, 3]);
+        assert_eq!(arcade.state().framebuffer(), [0, 0, 0, 0, 0, 3]);
+        assert_eq!(arcade.state().paddle_position, [1, 2]);
+        assert_eq!(arcade.state().paddle_moves, 1);
+        assert_eq!(arcade.outcome(), None);
+
+        arcade.tilt(1);
+        assert_eq!(arcade.run(), [Update::Tile([0, 0], Tile::Wall)]);
+        assert!(arcade.is_finished());
+        assert_eq!(arcade.state().framebuffer(), [1, 0, 0, 0, 0, 3]);
+        assert_eq!(arcade.state().render(), "# \n  \n -\n");
+        assert_eq!(arcade.outcome(), Some(Outcome::Won));
+        assert!(arcade.run().is_empty());
+
+        let transcript = arcade.transcript();
+        assert!(transcript.patches.is_empty());
+        assert!(transcript.halted);
+        assert_eq!(transcript.inputs().collect::<Vec<_>>(), [1]);
+        assert_eq!(transcript.outputs().count(), 9);
+    }
+
+    #[test]
+    fn stub() {
+        let program = Program::parse(
+            "104,1,104,2,104,3,104,-1,104,0,104,7,3,100,104,0,104,0,4,100,99".as_bytes(),
+        )
+        .unwrap();
+        let mut arcade = Arcade::new(&program, false);
+        let mut updates = arcade.run();
+        arcade.tilt(1);
+        updates.extend(arcade.run());
+        let transcript = arcade.transcript();
+
+        let mut stub = Arcade::stub(transcript.clone());
+        let mut replayed = stub.run();
+        assert_eq!(stub.state().paddle_position, [1, 2]);
+        stub.tilt(1);
+        replayed.extend(stub.run());
+        assert_eq!(replayed, updates);
+        assert!(stub.is_finished());
+        assert_eq!(stub.divergence(), None);
+        assert_eq!(stub.transcript(), transcript);
+
+        let mut stub = Arcade::stub(transcript);
+        stub.run();
+        stub.tilt(-1);
+        assert!(stub.run().is_empty());
+        assert!(!stub.is_finished());
+        assert_eq!(
+            stub.divergence(),
+            Some("Given -1 where the transcript has 1 after 6 values")
+        );
+    }
+
+    #[test]
+    fn lost() {
+        // Draws two blocks and the ball, then breaks one and moves the ball
+        // along before halting.
+        let program = Program::parse(
+            "104,0,104,0,104,2,104,1,104,0,104,2,104,0,104,1,104,4,\
+             104,0,104,0,104,0,104,0,104,1,104,0,104,1,104,1,104,4,99"
+                .as_bytes(),
+        )
+        .unwrap();
+        let mut arcade = Arcade::new(&program, false);
+        arcade.run();
+
+        assert_eq!(arcade.outcome(), Some(Outcome::Lost));
+        assert_eq!(arcade.state().blocks(), 1);
+        assert_eq!(arcade.state().ball_moves, 2);
+        assert_eq!(arcade.state().render(), " X\n O\n");
+    }
+
+    #[test]
+    fn autopilot_follows_ball() {
+        let mut state = GameState {
+            paddle_position: [10, 20],
+            ball_position: [5, 10],
+            ball_velocity: [1, 1],
+            ..GameState::default()
+        };
+        // The ball reaches the row above the paddle at x = 14.
+        assert_eq!(autopilot(&state), 1);
+
+        state.ball_position = [15, 10];
+        state.ball_velocity = [-1, 1];
+        assert_eq!(autopilot(&state), -1);
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+use std::fs;
+use std::io::{stdout, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+use termion::raw::IntoRawMode;
+use termion::{clear, color, cursor};
+
+use common::{CommonOpts, Events, Flag};
+
+use arcade::game::{self, Arcade, GameState, Outcome, Tile, Update};
+use arcade::program::Program;
+use arcade::transcript::{Playback, Transcript};
+
+struct Ticker {
+    interval: Duration,
+    next_tick: Instant,
+}
+
+impl Ticker {
+    fn new(interval: Duration) -> Self {
+        Ticker {
+            interval,
+            next_tick: Instant::now()
+        }
+    }
+
+    fn wait(&mut self) {
+        let tick = self.next().unwrap();
+        while Instant::now() < tick {
+        }
+    }
+}
+
+impl Iterator for Ticker {
+    type Item = Instant;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tick = self.next_tick;
+        self.next_tick += self.interval;
+        Some(tick)
+    }
+}
+
+trait Screen {
+    fn clear(&mut self);
+    fn set_tile(&mut self, position: [u16; 2], tile: Tile);
+    fn display_score(&mut self, score: i64);
+}
+
+impl<T: Write> Screen for T {
+    fn clear(&mut self) {
+        let _ = write!(self, "{}", clear::All);
+    }
+
+    fn set_tile(&mut self, [x, y]: [u16; 2], tile: Tile) {
+        let _ = write!(self, "{}{}", cursor::Goto(x + 1, y + 1), Glyph(tile));
+        let _ = self.flush();
+    }
+
+    fn display_score(&mut self, score: i64) {
+        let _ = write!(self, "{}{}{}Score: {}{}", cursor::Goto(1, 24), clear::CurrentLine, color::Fg(color::Blue), score, color::Fg(color::Reset));
+        let _ = self.flush();
+    }
+}
+
+struct ScreenBuffer {}
+
+impl Screen for ScreenBuffer {
+    fn clear(&mut self) {}
+
+    fn set_tile(&mut self, [x, y]: [u16; 2], tile: Tile) {
+        if tile == Tile::Ball || tile == Tile::Paddle {
+            println!("Output: ({}, {}): {:?}", x, y, tile);
+        }
+    }
+
+    fn display_score(&mut self, score: i64) {
+        println!("Score: {}", score);
+    }
+}
+
+// A screen which shows nothing, for when only the score is wanted.
+struct Hidden {}
+
+impl Screen for Hidden {
+    fn clear(&mut self) {}
+
+    fn set_tile(&mut self, _position: [u16; 2], _tile: Tile) {}
+
+    fn display_score(&mut self, _score: i64) {}
+}
+
+// A screen which also writes every change made to it as an event.
+struct Logged<S> {
+    screen: S,
+    events: Events,
+}
+
+impl<S: Screen> Screen for Logged<S> {
+    fn clear(&mut self) {
+        self.screen.clear();
+        let _ = self.events.write("clear", &[]);
+    }
+
+    fn set_tile(&mut self, [x, y]: [u16; 2], tile: Tile) {
+        let name = format!("{:?}", tile);
+        let fields = [("x", x.into()), ("y", y.into()), ("tile", name.as_str().into())];
+        let _ = self.events.write("tile", &fields);
+        self.screen.set_tile([x, y], tile);
+    }
+
+    fn display_score(&mut self, score: i64) {
+        let _ = self.events.write("score", &[("score", score.into())]);
+        self.screen.display_score(score);
+    }
+}
+
+// How a tile looks in the terminal.
+struct Glyph(Tile);
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Tile::*;
+        match self.0 {
+            Empty => write!(f, " "),
+            Wall => write!(f, "\u{2588}"),
+            Block => write!(f, "{}X{}", color::Fg(color::Red), color::Fg(color::Reset)),
+            Paddle => write!(f, "\u{2588}"),
+            Ball => write!(f, "O"),
+        }
+    }
+}
+
+fn print_position(state: &GameState, position: &[u16; 2]) {
+    for y in position[1]-1..position[1]+2 {
+        for x in position[0]-1..position[0]+2 {
+            let tile = state.cells.get(&[x, y]).cloned().unwrap_or_default();
+            print!("{}", Glyph(tile));
+        }
+        println!();
+    }
+}
+
+// Check the ball went where the autopilot thought it would.
+fn check_prediction(last_state: &GameState, state: &GameState) {
+    let mut expected_state = last_state.clone();
+    game::step(&mut expected_state);
+
+    if last_state.ball_position[1] < last_state.paddle_position[1] - 1 && expected_state.ball_position != state.ball_position {
+        println!("Was ({}, {}):", last_state.ball_velocity[0], last_state.ball_velocity[1]);
+        print_position(last_state, &last_state.ball_position);
+        println!("Expected:");
+        print_position(&expected_state, &last_state.ball_position);
+        println!("Got:");
+        print_position(state, &last_state.ball_position);
+        panic!("Unexpected state change");
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Write every value passed to and from the game to a transcript
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+
+    /// Check the game does exactly what a transcript says, instead of playing
+    #[structopt(long, parse(from_os_str), conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    /// Play against the screen from a transcript instead of running the game
+    #[structopt(long, parse(from_os_str), conflicts_with = "replay")]
+    stub: Option<PathBuf>,
+
+    /// Write every change to the screen to this file, one JSON object per line
+    #[structopt(long, parse(from_os_str))]
+    events: Option<PathBuf>,
+
+    /// Write the screen as it was when the game ended to this file, as text
+    #[structopt(long, parse(from_os_str))]
+    board: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn run<T: Screen>(mut arcade: Arcade, mut screen: T, speed: Option<u64>) -> Arcade {
+    let mut ticker = speed.map(|rate| Ticker::new(Duration::from_nanos(1_000_000_000 / rate)));
+    let mut last_state: Option<GameState> = None;
+
+    screen.clear();
+    loop {
+        for update in arcade.run() {
+            match update {
+                Update::Tile(position, tile) => screen.set_tile(position, tile),
+                Update::Score(score) => screen.display_score(score),
+            }
+        }
+
+        if arcade.is_finished() || arcade.divergence().is_some() {
+            break;
+        }
+
+        let state = arcade.state();
+        if let Some(ref last_state) = last_state {
+            check_prediction(last_state, state);
+        }
+        last_state = Some(state.clone());
+
+        if let Some(ref mut ticker) = ticker {
+            ticker.wait();
+        }
+        arcade.tilt(game::autopilot(state));
+    }
+
+    arcade
+}
+
+fn show(arcade: Arcade, screen: impl Screen, events: Option<Events>, speed: Option<u64>) -> Arcade {
+    match events {
+        Some(events) => run(arcade, Logged { screen, events }, speed),
+        None => run(arcade, screen, speed),
+    }
+}
+
+// Whether the game was won, the final score, and how much the ball and paddle
+// moved getting there.
+fn report(arcade: &Arcade) -> String {
+    let state = arcade.state();
+    let result = match arcade.outcome() {
+        Some(Outcome::Won) => "Won".to_string(),
+        Some(Outcome::Lost) => format!("Lost with {} blocks left", state.blocks()),
+        None => "Stopped before the end".to_string(),
+    };
+    format!("{}, scoring {}, after the ball moved {} times and the paddle {} times",
+            result, state.score, state.ball_moves, state.paddle_moves)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    if let Err(message) = opts.common.unsupported(&[Flag::Part]) {
+        fail(&message);
+    }
+
+    // A stub doesn't need the game, so only read it if it's going to be run.
+    let arcade = match opts.stub {
+        Some(ref path) => Transcript::load(path).map(Arcade::stub),
+        None => {
+            let input = opts.common.input().unwrap_or_else(|message| fail(&message));
+            let program = Program::parse(input).unwrap();
+
+            if let Some(ref path) = opts.replay {
+                match Transcript::load(path).and_then(|transcript| Playback::new(transcript).replay(&program)) {
+                    Ok(count) => println!("Replayed {} values", count),
+                    Err(message) => fail(&message),
+                }
+                return;
+            }
+
+            Ok(Arcade::new(&program, true))
+        }
+    };
+    let arcade = arcade.unwrap_or_else(|message| fail(&message));
+    let events = opts.events.as_ref().map(|path| Events::create(path));
+    let events = events.transpose().unwrap_or_else(|message| fail(&message));
+
+    let arcade = if opts.common.answers_only() {
+        show(arcade, Hidden {}, events, None)
+    } else if !opts.common.debug {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        let arcade = show(arcade, screen, events, opts.common.speed());
+        println!("{}", cursor::Goto(1, 25));
+        arcade
+    } else {
+        let screen = ScreenBuffer {};
+        show(arcade, screen, events, opts.common.speed())
+    };
+
+    if let Some(divergence) = arcade.divergence() {
+        fail(&format!("The autopilot went its own way: {}", divergence));
+    }
+
+    if let Some(ref path) = opts.record {
+        if let Err(message) = arcade.transcript().save(path) {
+            fail(&message);
+        }
+    }
+
+    if let Some(ref path) = opts.board {
+        if let Err(error) = fs::write(path, arcade.state().render()) {
+            fail(&format!("Can't write {}: {}", path.display(), error));
+        }
+    }
+
+    if opts.common.answers_only() {
+        opts.common.print_answers(&[("score", arcade.state().score.into())]);
+    } else {
+        println!("{}", report(&arcade));
+    }
+}
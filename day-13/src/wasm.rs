@@ -0,0 +1,57 @@
+// The arcade as seen from JavaScript, for drawing on a canvas: see
+// web/index.html.
+
+use wasm_bindgen::prelude::*;
+
+use crate::game::{self, Arcade};
+use crate::program::Program;
+
+#[wasm_bindgen]
+pub struct Game {
+    arcade: Arcade,
+}
+
+#[wasm_bindgen]
+impl Game {
+    #[wasm_bindgen(constructor)]
+    pub fn new(program: &str, free_play: bool) -> Result<Game, JsValue> {
+        let program = Program::parse(program.as_bytes())
+            .map_err(|error| JsValue::from_str(&format!("{:?}", error)))?;
+        Ok(Game {
+            arcade: Arcade::new(&program, free_play),
+        })
+    }
+
+    // Run until the game wants the joystick moved, returning false once it's
+    // over.
+    pub fn step(&mut self) -> bool {
+        self.arcade.run();
+        !self.arcade.is_finished()
+    }
+
+    pub fn tilt(&mut self, direction: i32) {
+        self.arcade.tilt(direction.into());
+    }
+
+    pub fn autopilot(&self) -> i32 {
+        game::autopilot(self.arcade.state()) as i32
+    }
+
+    pub fn score(&self) -> f64 {
+        self.arcade.state().score as f64
+    }
+
+    pub fn width(&self) -> u32 {
+        self.arcade.state().size()[0].into()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.arcade.state().size()[1].into()
+    }
+
+    // One byte per tile, a row at a time: 0 empty, 1 wall, 2 block, 3 paddle
+    // and 4 ball.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        self.arcade.state().framebuffer()
+    }
+}
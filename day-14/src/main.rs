@@ -0,0 +1,695 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, stdout, BufRead, Write};
+use std::iter::FromIterator;
+use std::process;
+use structopt::StructOpt;
+
+use common::compare::compare;
+use common::{CommonOpts, Flag};
+
+#[derive(Default)]
+struct ChemicalBook {
+    chemicals: HashMap<String, Chemical>,
+    names: Vec<String>,
+}
+
+impl ChemicalBook {
+    fn lookup(&mut self, name: &str) -> Chemical {
+        if let Some(chemical) = self.chemicals.get(name).cloned() {
+            chemical
+        } else {
+            let chemical = Chemical::new(self.names.len());
+            self.chemicals.insert(name.to_string(), chemical);
+            self.names.push(name.to_string());
+            chemical
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<Chemical> {
+        self.chemicals.get(name).cloned()
+    }
+
+    fn get_name(&self, chemical: &Chemical) -> &str {
+        &self.names[chemical.0]
+    }
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Chemical(usize);
+
+impl Chemical {
+    fn new(index: usize) -> Self {
+        assert!(index < 64);
+        Chemical(index)
+    }
+}
+
+#[derive(Clone)]
+struct Quantity {
+    chemical: Chemical,
+    quantity: usize,
+}
+
+impl Quantity {
+    fn new(chemical: Chemical, quantity: usize) -> Self {
+        Quantity { chemical, quantity }
+    }
+
+    fn parse(s: &str, book: &mut ChemicalBook) -> Result<Self, Error> {
+        let mut parts = s.splitn(2, ' ');
+        let quantity = parts.next().unwrap().parse()?;
+        let chemical = parts
+            .next()
+            .map(|name| book.lookup(name))
+            .ok_or(Error("No chemical".to_string()))?;
+        Ok(Quantity { chemical, quantity })
+    }
+}
+
+#[derive(Clone)]
+struct Quantities([usize;64]);
+
+impl FromIterator<Quantity> for Quantities {
+    fn from_iter<I: IntoIterator<Item = Quantity>>(quantities: I) -> Self {
+        let mut entries = [0;64];
+
+        for quantity in quantities {
+            entries[quantity.chemical.0] += quantity.quantity;
+        }
+
+        Quantities(entries)
+    }
+}
+
+impl From<Quantity> for Quantities {
+    fn from(quantity: Quantity) -> Self {
+        let mut entries = [0;64];
+        entries[quantity.chemical.0] = quantity.quantity;
+        Quantities(entries)
+    }
+}
+
+impl Quantities {
+    fn get(&self, chemical: &Chemical) -> usize {
+        self.0[chemical.0]
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = Quantity> + 'a {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, quantity)| **quantity > 0)
+            .map(|(index, quantity)| Quantity::new(Chemical::new(index), *quantity))
+    }
+
+    fn add(&mut self, quantity: &Quantity) {
+        self.0[quantity.chemical.0] += quantity.quantity;
+    }
+
+    fn take(&mut self, chemical: &Chemical) -> usize {
+        std::mem::replace(&mut self.0[chemical.0], 0)
+    }
+}
+
+#[derive(Clone)]
+struct Reaction {
+    input: Quantities,
+    output: Quantity,
+}
+
+impl Reaction {
+    fn num_required(&self, quantity: usize) -> usize {
+        quantity.div_ceil(self.output.quantity)
+    }
+
+    fn parse(s: &str, book: &mut ChemicalBook) -> Result<Self, Error> {
+        let mut parts = s.splitn(2, "=>");
+        let input: Quantities = parts
+            .next()
+            .unwrap()
+            .split(',')
+            .map(str::trim)
+            .map(|quantity| Quantity::parse(quantity, book))
+            .collect::<Result<Quantities, _>>()?;
+        let output = parts
+            .next()
+            .ok_or(Error("No output".to_string()))
+            .map(str::trim)
+            .and_then(|quantity| Quantity::parse(quantity, book))?;
+        Ok(Reaction { input, output })
+    }
+}
+
+struct Reactions {
+    book: ChemicalBook,
+    reactions: HashMap<Chemical, Reaction>,
+    order: Vec<Chemical>,
+}
+
+impl Reactions {
+    fn new(book: ChemicalBook, reactions: Vec<Reaction>) -> Self {
+        let reactions: HashMap<Chemical, Reaction> = reactions
+            .into_iter()
+            .map(|reaction| (reaction.output.chemical, reaction))
+            .collect();
+
+        let order = topological_order(&reactions);
+        Reactions {
+            book,
+            reactions,
+            order,
+        }
+    }
+
+    fn chemical(&self, name: &str) -> Option<Chemical> {
+        self.book.get(name)
+    }
+
+    fn name(&self, chemical: &Chemical) -> &str {
+        self.book.get_name(chemical)
+    }
+
+    fn write_dot(&self, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "digraph reactions {{")?;
+        writeln!(output, "    rankdir=LR;")?;
+
+        for chemical in self.order.iter() {
+            let name = self.name(chemical);
+            match (name, self.reactions.get(chemical)) {
+                ("ORE", _) => writeln!(
+                    output,
+                    "    \"{}\" [shape=box, style=filled, fillcolor=gray];",
+                    name
+                )?,
+                ("FUEL", Some(reaction)) => writeln!(
+                    output,
+                    "    \"{}\" [label=\"{}\\n({})\", shape=box, style=filled, fillcolor=orange];",
+                    name, name, reaction.output.quantity
+                )?,
+                (_, Some(reaction)) => writeln!(
+                    output,
+                    "    \"{}\" [label=\"{}\\n({})\"];",
+                    name, name, reaction.output.quantity
+                )?,
+                (_, None) => writeln!(output, "    \"{}\";", name)?,
+            }
+        }
+
+        for chemical in self.order.iter() {
+            if let Some(reaction) = self.reactions.get(chemical) {
+                for quantity in reaction.input.iter() {
+                    writeln!(
+                        output,
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                        self.name(&quantity.chemical),
+                        self.name(chemical),
+                        quantity.quantity
+                    )?;
+                }
+            }
+        }
+
+        writeln!(output, "}}")
+    }
+}
+
+// Order the chemicals so that every chemical comes before all of the
+// chemicals used to produce it.
+fn topological_order(reactions: &HashMap<Chemical, Reaction>) -> Vec<Chemical> {
+    fn visit(
+        chemical: Chemical,
+        reactions: &HashMap<Chemical, Reaction>,
+        visited: &mut HashSet<Chemical>,
+        order: &mut Vec<Chemical>,
+    ) {
+        if !visited.insert(chemical) {
+            return;
+        }
+
+        if let Some(reaction) = reactions.get(&chemical) {
+            for quantity in reaction.input.iter() {
+                visit(quantity.chemical, reactions, visited, order);
+            }
+        }
+
+        order.push(chemical);
+    }
+
+    let mut visited = HashSet::new();
+    let mut order = Vec::new();
+    for chemical in reactions.keys() {
+        visit(*chemical, reactions, &mut visited, &mut order);
+    }
+
+    order.reverse();
+    order
+}
+
+// The ways of working out how much of one chemical it takes to make another.
+#[derive(Clone, Copy)]
+enum Solver {
+    Naive,
+    Topological,
+}
+
+impl Reactions {
+    // Work back from the desired amount of a chemical to the raw materials
+    // needed to make it, or to `from` if that's made by a reaction itself,
+    // returning them along with the surplus of each chemical left over from
+    // running whole reactions.
+    fn expand(&self, from: Chemical, to: Chemical, amount: usize) -> (Quantities, Quantities) {
+        let mut needed: Quantities = Quantity::new(to, amount).into();
+        let mut surplus = Quantities([0; 64]);
+
+        for chemical in self.order.iter() {
+            let reaction = match self.reactions.get(chemical) {
+                Some(reaction) if *chemical != from => reaction,
+                _ => continue,
+            };
+
+            let num_needed = needed.take(chemical);
+            if num_needed == 0 {
+                continue;
+            }
+
+            let num_reactions = reaction.num_required(num_needed);
+            surplus.add(&Quantity::new(
+                *chemical,
+                num_reactions * reaction.output.quantity - num_needed,
+            ));
+
+            for quantity in reaction.input.iter() {
+                needed.add(&Quantity::new(
+                    quantity.chemical,
+                    quantity.quantity * num_reactions,
+                ));
+            }
+        }
+
+        (needed, surplus)
+    }
+
+    fn to_get(&self, from: Chemical, to: Chemical, amount: usize) -> Option<usize> {
+        let (needed, _) = self.expand(from, to, amount);
+
+        if needed.iter().all(|quantity| quantity.chemical == from) {
+            Some(needed.get(&from))
+        } else {
+            None
+        }
+    }
+
+    // The original way of working back to the raw materials: undo the
+    // reaction for any chemical which none of the remaining reactions use,
+    // until there's nothing but `from` left.  Kept to check `to_get` against.
+    fn to_get_naive(&self, from: Chemical, to: Chemical, amount: usize) -> Option<usize> {
+        let mut reactions = self.reactions.clone();
+        reactions.remove(&from);
+        let mut needed: Quantities = Quantity::new(to, amount).into();
+
+        while !needed.iter().all(|quantity| quantity.chemical == from) {
+            let chemical = reactions
+                .keys()
+                .find(|chemical| {
+                    !reactions
+                        .values()
+                        .any(|reaction| reaction.input.get(chemical) > 0)
+                })
+                .cloned()?;
+
+            let reaction = reactions.remove(&chemical).unwrap();
+            let num_reactions = reaction.num_required(needed.take(&chemical));
+            for quantity in reaction.input.iter() {
+                needed.add(&Quantity::new(
+                    quantity.chemical,
+                    quantity.quantity * num_reactions,
+                ));
+            }
+        }
+
+        Some(needed.get(&from))
+    }
+
+    fn cost(&self, solver: Solver, from: Chemical, to: Chemical, amount: usize) -> Option<usize> {
+        match solver {
+            Solver::Naive => self.to_get_naive(from, to, amount),
+            Solver::Topological => self.to_get(from, to, amount),
+        }
+    }
+
+    fn surplus(&self, from: Chemical, to: Chemical, amount: usize) -> Vec<Quantity> {
+        let (_, surplus) = self.expand(from, to, amount);
+
+        self.order
+            .iter()
+            .map(|chemical| Quantity::new(*chemical, surplus.get(chemical)))
+            .filter(|quantity| quantity.quantity > 0)
+            .collect()
+    }
+
+    fn amortized_cost(&self, from: Chemical, to: Chemical, amount: usize) -> Option<f64> {
+        self.to_get(from, to, amount)
+            .map(|cost| cost as f64 / amount as f64)
+    }
+
+    fn write_report(
+        &self,
+        output: &mut impl Write,
+        from_name: &str,
+        to_name: &str,
+        amount: usize,
+    ) -> io::Result<()> {
+        let no_way = format!("No way to make {} from {}", to_name, from_name);
+        let (from, to) = match (self.chemical(from_name), self.chemical(to_name)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return writeln!(output, "{}", no_way),
+        };
+
+        let cost = match self.to_get(from, to, amount) {
+            Some(cost) => cost,
+            None => return writeln!(output, "{}", no_way),
+        };
+
+        writeln!(output, "{}: {}", to_name, amount)?;
+        writeln!(output, "{} required: {}", from_name, cost)?;
+        if let Some(amortized) = self.amortized_cost(from, to, amount) {
+            writeln!(output, "{} per {}: {:.3}", from_name, to_name, amortized)?;
+        }
+
+        writeln!(output, "Surplus:")?;
+        for quantity in self.surplus(from, to, amount) {
+            writeln!(
+                output,
+                "    {}: {}",
+                self.name(&quantity.chemical),
+                quantity.quantity
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn num_required(&self, solver: Solver, from: &str, to: &str, amount: usize) -> Option<usize> {
+        self.cost(solver, self.chemical(from)?, self.chemical(to)?, amount)
+    }
+
+    fn num_obtained(&self, solver: Solver, from: &str, amount: usize, to: &str) -> Option<usize> {
+        let from = self.chemical(from)?;
+        let to = self.chemical(to)?;
+
+        let mut output = 1;
+        while self.cost(solver, from, to, output)? <= amount {
+            output *= 2;
+        }
+
+        let mut lower = output / 2;
+        let mut higher = output;
+
+        while higher > lower + 1 {
+            let middle = (higher + lower) / 2;
+            if self.cost(solver, from, to, middle)? <= amount {
+                lower = middle;
+            } else {
+                higher = middle;
+            }
+        }
+
+        Some(lower)
+    }
+
+    fn answer(&self, solver: Solver, query: &Query) -> Option<usize> {
+        match *query {
+            Query::Cost { from, to, amount } => self.num_required(solver, from, to, amount),
+            Query::Yield { from, amount, to } => self.num_obtained(solver, from, amount, to),
+        }
+    }
+}
+
+// A question about the reactions, with the chemicals given by name.
+enum Query<'a> {
+    // How much of `from` it takes to make `amount` of `to`.
+    Cost { from: &'a str, to: &'a str, amount: usize },
+    // How much of `to` can be made from `amount` of `from`.
+    Yield { from: &'a str, amount: usize, to: &'a str },
+}
+
+fn read_reactions(input: &mut impl BufRead) -> Result<Reactions, Error> {
+    let mut book = ChemicalBook::default();
+    let reactions = input
+        .lines()
+        .map(Result::unwrap)
+        .map(|line| Reaction::parse(line.trim(), &mut book))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Reactions::new(book, reactions))
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Amount of ORE, or of the chemical given by --from, available
+    #[structopt(default_value = "1000000000000")]
+    available: usize,
+
+    /// The chemical to make things from
+    #[structopt(long, default_value = "ORE")]
+    from: String,
+
+    /// The chemical to make
+    #[structopt(long, default_value = "FUEL")]
+    to: String,
+
+    /// Work out how much of --from it takes to make this much of --to, rather than how much of
+    /// --to can be made
+    #[structopt(long, conflicts_with = "part")]
+    make: Option<usize>,
+
+    /// Print the reaction graph in Graphviz DOT format
+    #[structopt(long)]
+    dot: bool,
+
+    /// Report the surplus and amortized cost of making this much of --to from --from
+    #[structopt(long)]
+    report: Option<usize>,
+
+    /// Solve with both the naive and the topological solver, and compare them
+    #[structopt(long, conflicts_with_all = &["dot", "report", "quiet", "json"])]
+    compare: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    if let Err(message) = opts.common.unsupported(&[Flag::Speed, Flag::Debug]) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+
+    let reactions = opts
+        .common
+        .input()
+        .map_err(Error)
+        .and_then(|mut input| read_reactions(&mut input));
+    let reactions = match reactions {
+        Ok(reactions) => reactions,
+        Err(Error(message)) => {
+            eprintln!("Failed to read reactions: {}", message);
+            process::exit(1);
+        }
+    };
+
+    if opts.dot {
+        reactions.write_dot(&mut stdout().lock()).unwrap();
+        return;
+    }
+
+    for name in [&opts.from, &opts.to].iter() {
+        if reactions.chemical(name).is_none() {
+            eprintln!("There's no chemical called {}", name);
+            process::exit(1);
+        }
+    }
+
+    if let Some(amount) = opts.report {
+        reactions
+            .write_report(&mut stdout().lock(), &opts.from, &opts.to, amount)
+            .unwrap();
+        return;
+    }
+
+    let (from, to) = (opts.from.as_str(), opts.to.as_str());
+    let query = match (opts.make, opts.common.part(2)) {
+        (Some(amount), _) => Query::Cost { from, to, amount },
+        (None, 1) => Query::Cost { from, to, amount: 1 },
+        (None, _) => Query::Yield { from, amount: opts.available, to },
+    };
+    let solve = |solver| reactions.answer(solver, &query);
+
+    if opts.compare {
+        let naive = ("naive", || solve(Solver::Naive));
+        let topological = ("topological", || solve(Solver::Topological));
+        if !compare(naive, topological) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    match solve(Solver::Topological) {
+        Some(answer) if opts.common.answers_only() => {
+            opts.common.print_answers(&[("answer", answer.into())])
+        }
+        Some(answer) => println!("{}", answer),
+        None => println!("No way to make {} from {}", to, from),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_1() {
+        let input = r#"157 ORE => 5 NZVS
+        165 ORE => 6 DCFZ
+        44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+        12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+        179 ORE => 7 PSHF
+        177 ORE => 5 HKGWZ
+        7 DCFZ, 7 PSHF => 2 XJWVT
+        165 ORE => 2 GPVTF
+        3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT"#;
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let reactions = read_reactions(&mut reader).unwrap();
+
+        for solver in [Solver::Topological, Solver::Naive].iter() {
+            let num_obtained = reactions.num_obtained(*solver, "ORE", 1000000000000, "FUEL");
+            assert_eq!(num_obtained, Some(82892753));
+        }
+    }
+
+    #[test]
+    fn test_to_get() {
+        let input = r#"10 ORE => 10 A
+        1 ORE => 1 B
+        7 A, 1 B => 1 C
+        7 A, 1 C => 1 D
+        7 A, 1 D => 1 E
+        7 A, 1 E => 1 FUEL"#;
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let reactions = read_reactions(&mut reader).unwrap();
+
+        let ore = reactions.chemical("ORE").unwrap();
+        let fuel = reactions.chemical("FUEL").unwrap();
+        assert_eq!(reactions.to_get(ore, fuel, 1), Some(31));
+        assert_eq!(reactions.to_get_naive(ore, fuel, 1), Some(31));
+        assert_eq!(reactions.to_get_naive(fuel, ore, 1), None);
+    }
+
+    #[test]
+    fn test_surplus() {
+        let input = r#"9 ORE => 2 A
+        8 ORE => 3 B
+        7 ORE => 5 C
+        3 A, 4 B => 1 AB
+        5 B, 7 C => 1 BC
+        4 C, 1 A => 1 CA
+        2 AB, 3 BC, 4 CA => 1 FUEL"#;
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let reactions = read_reactions(&mut reader).unwrap();
+
+        let ore = reactions.chemical("ORE").unwrap();
+        let fuel = reactions.chemical("FUEL").unwrap();
+        let b = reactions.chemical("B").unwrap();
+        let c = reactions.chemical("C").unwrap();
+
+        let surplus: HashMap<_, _> = reactions
+            .surplus(ore, fuel, 1)
+            .into_iter()
+            .map(|quantity| (quantity.chemical, quantity.quantity))
+            .collect();
+        assert_eq!(surplus.len(), 2);
+        assert_eq!(surplus[&b], 1);
+        assert_eq!(surplus[&c], 3);
+
+        assert_eq!(reactions.amortized_cost(ore, fuel, 1), Some(165.0));
+    }
+
+    #[test]
+    fn test_dot() {
+        let input = r#"9 ORE => 2 A
+        3 A => 1 FUEL"#;
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let reactions = read_reactions(&mut reader).unwrap();
+
+        let mut output = Vec::new();
+        reactions.write_dot(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("digraph reactions {"));
+        assert!(output.contains("\"ORE\" [shape=box, style=filled, fillcolor=gray];"));
+        assert!(output.contains("\"FUEL\" [label=\"FUEL\\n(1)\", shape=box, style=filled, fillcolor=orange];"));
+        assert!(output.contains("\"ORE\" -> \"A\" [label=\"9\"];"));
+        assert!(output.contains("\"A\" -> \"FUEL\" [label=\"3\"];"));
+    }
+
+    #[test]
+    fn queries() {
+        let input = r#"10 ORE => 10 A
+        1 ORE => 1 B
+        7 A, 1 B => 1 C
+        7 A, 1 C => 1 D
+        7 A, 1 D => 1 E
+        7 A, 1 E => 1 FUEL"#;
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let reactions = read_reactions(&mut reader).unwrap();
+
+        for solver in [Solver::Topological, Solver::Naive].iter() {
+            let answer = |query| reactions.answer(*solver, &query);
+            let cost = |from, to, amount| answer(Query::Cost { from, to, amount });
+            let made = |from, amount, to| answer(Query::Yield { from, amount, to });
+
+            assert_eq!(cost("ORE", "FUEL", 1), Some(31));
+            assert_eq!(cost("ORE", "C", 2), Some(22));
+            assert_eq!(cost("A", "E", 1), None);
+            assert_eq!(cost("ORE", "NOTHING", 1), None);
+            assert_eq!(made("ORE", 100, "B"), Some(100));
+            assert_eq!(made("ORE", 21, "C"), Some(1));
+        }
+
+        // Working back from a chemical which is made by a reaction itself.
+        let input = r#"10 ORE => 10 A
+        7 A => 1 B
+        3 B => 1 FUEL"#;
+
+        let mut reader = BufReader::new(input.as_bytes());
+        let reactions = read_reactions(&mut reader).unwrap();
+
+        for solver in [Solver::Topological, Solver::Naive].iter() {
+            let answer = |query| reactions.answer(*solver, &query);
+            let cost = |from, to, amount| answer(Query::Cost { from, to, amount });
+            let made = |from, amount, to| answer(Query::Yield { from, amount, to });
+
+            assert_eq!(cost("A", "FUEL", 1), Some(21));
+            assert_eq!(cost("B", "FUEL", 2), Some(6));
+            assert_eq!(cost("ORE", "FUEL", 1), Some(30));
+            assert_eq!(made("A", 42, "FUEL"), Some(2));
+            assert_eq!(made("B", 2, "FUEL"), Some(0));
+        }
+    }
+}
\ No newline at end of file
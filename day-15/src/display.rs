@@ -0,0 +1,71 @@
+use std::fmt;
+use std::io::Write;
+
+use termion::{clear, cursor};
+
+use common::Events;
+
+pub trait Screen {
+    fn clear(&mut self);
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, position: [u16; 2], tile: T);
+}
+
+impl<W: Write> Screen for W {
+    fn clear(&mut self) {
+        let _ = write!(self, "{}", clear::All);
+    }
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, [x, y]: [u16; 2], tile: T) {
+        let _ = write!(self, "{}{}", cursor::Goto(x + 1, y + 1), tile);
+        let _ = self.flush();
+    }
+}
+
+pub struct ScreenBuffer {}
+
+impl Screen for ScreenBuffer {
+    fn clear(&mut self) {}
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, [x, y]: [u16; 2], tile: T) {
+        println!("Output: ({}, {}): {:?}", x, y, tile);
+    }
+}
+
+// A screen which shows nothing, for when only the answer is wanted.
+pub struct Hidden {}
+
+impl Screen for Hidden {
+    fn clear(&mut self) {}
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, _position: [u16; 2], _tile: T) {}
+}
+
+// A screen which also writes every change made to it as an event.
+pub struct Logged<S> {
+    screen: S,
+    events: Events,
+}
+
+impl<S: Screen> Logged<S> {
+    pub fn new(screen: S, events: Events) -> Self {
+        Logged { screen, events }
+    }
+}
+
+impl<S: Screen> Screen for Logged<S> {
+    fn clear(&mut self) {
+        self.screen.clear();
+        let _ = self.events.write("clear", &[]);
+    }
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, [x, y]: [u16; 2], tile: T) {
+        let name = format!("{:?}", tile);
+        let fields = [
+            ("x", x.into()),
+            ("y", y.into()),
+            ("tile", name.as_str().into()),
+        ];
+        let _ = self.events.write("tile", &fields);
+        self.screen.set_tile([x, y], tile);
+    }
+}
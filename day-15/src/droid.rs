@@ -0,0 +1,348 @@
+// The repair droid on its own, without a terminal: tell it which way to move
+// and it remembers what it found there.
+
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::process::{Channel, Input, Output, Process, State};
+use crate::program::Program;
+use crate::transcript::{Recorder, RecordingInput, RecordingOutput, Stub, Transcript};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    North = 1,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    pub fn all() -> impl Iterator<Item = Direction> {
+        (1..5).map(Direction::try_from).map(Result::unwrap)
+    }
+
+    pub fn between(from: Position, to: Position) -> Option<Direction> {
+        Direction::all().find(|direction| from.moved(*direction) == to)
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Direction::*;
+        match self {
+            North => write!(f, "N"),
+            South => write!(f, "S"),
+            West => write!(f, "W"),
+            East => write!(f, "E"),
+        }
+    }
+}
+
+impl TryFrom<i64> for Direction {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        use Direction::*;
+        match value {
+            1 => Ok(North),
+            2 => Ok(South),
+            3 => Ok(West),
+            4 => Ok(East),
+            _ => Err(format!("Unknown direction {}", value)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    HitWall,
+    Moved,
+    FoundOxygenMachine,
+}
+
+impl TryFrom<i64> for Status {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        use Status::*;
+        match value {
+            0 => Ok(HitWall),
+            1 => Ok(Moved),
+            2 => Ok(FoundOxygenMachine),
+            _ => Err(format!("Unknown status {}", value)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Position {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Position {
+    pub fn origin() -> Self {
+        Position { x: 0, y: 0 }
+    }
+
+    pub fn moved(&self, direction: Direction) -> Position {
+        use Direction::*;
+        let mut position = *self;
+        match direction {
+            North => position.y -= 1,
+            East => position.x += 1,
+            South => position.y += 1,
+            West => position.x -= 1,
+        }
+        position
+    }
+
+    pub fn length(self) -> usize {
+        self.x.unsigned_abs() as usize + self.y.unsigned_abs() as usize
+    }
+
+    pub fn offset(self, other: Position) -> Position {
+        Position {
+            x: other.x - self.x,
+            y: other.y - self.y,
+        }
+    }
+
+    pub fn distance(self, other: Position) -> usize {
+        self.offset(other).length()
+    }
+}
+
+// The screen is big enough for the whole maze with the start in the middle.
+pub const SCREEN_SIZE: u16 = 50;
+
+impl From<Position> for [u16; 2] {
+    fn from(position: Position) -> Self {
+        let middle = (SCREEN_SIZE / 2) as i64;
+        [(position.x + middle) as u16, (position.y + middle) as u16]
+    }
+}
+
+// What the droid knows about each cell of the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Unexplored,
+    Open,
+    Wall,
+    OxygenMachine,
+    Droid,
+}
+
+type Controls = RecordingInput<Rc<Channel<i64>>>;
+type Reports = RecordingOutput<Rc<Channel<i64>>>;
+
+// What the droid's controls are connected to: the droid itself, or a stub
+// replaying a transcript of it.
+enum Machine {
+    Droid(Process<Controls, Reports>),
+    Stub(Stub<Controls, Reports>),
+}
+
+impl Machine {
+    fn execute(&mut self) -> State {
+        match self {
+            Machine::Droid(process) => process.execute(),
+            Machine::Stub(stub) => stub.execute(),
+        }
+    }
+}
+
+pub struct Droid {
+    machine: Machine,
+    input: Rc<Channel<i64>>,
+    output: Rc<Channel<i64>>,
+    recorder: Rc<Recorder>,
+    robot: Position,
+    oxygen_machine: Option<Position>,
+    occupied: HashMap<Position, bool>,
+}
+
+impl Droid {
+    pub fn new(program: &Program) -> Self {
+        Droid::connected_to(|controls, reports| {
+            Machine::Droid(Process::new("ROBOT", program, controls, reports))
+        })
+    }
+
+    // A droid which reports what it did in a transcript instead of running the
+    // program, to try out whatever is steering it.
+    pub fn stub(transcript: Transcript) -> Self {
+        Droid::connected_to(|controls, reports| {
+            Machine::Stub(Stub::new(transcript, controls, reports))
+        })
+    }
+
+    fn connected_to(machine: impl FnOnce(Controls, Reports) -> Machine) -> Self {
+        let robot = Position::origin();
+        let mut occupied = HashMap::new();
+        occupied.insert(robot, false);
+
+        let input = Rc::new(Channel::new());
+        let output = Rc::new(Channel::new());
+        let recorder = Recorder::new();
+        let machine = machine(
+            RecordingInput::new(input.clone(), &recorder),
+            RecordingOutput::new(output.clone(), &recorder),
+        );
+
+        Droid {
+            machine,
+            input,
+            output,
+            recorder,
+            robot,
+            oxygen_machine: None,
+            occupied,
+        }
+    }
+
+    pub fn step(&mut self, direction: Direction) -> Result<Status, String> {
+        self.input.put(direction as i64);
+
+        if self.machine.execute() == State::Complete {
+            return Err("The droid's program finished".to_string());
+        }
+
+        let status: Status = match (self.output.get(), &self.machine) {
+            (Some(status), _) => status.try_into()?,
+            (None, Machine::Stub(stub)) if stub.divergence().is_some() => {
+                return Err(stub.divergence().unwrap().to_string())
+            }
+            (None, _) => return Err("The droid didn't say what happened".to_string()),
+        };
+        let position = self.robot.moved(direction);
+        self.occupied.insert(position, status == Status::HitWall);
+
+        if status != Status::HitWall {
+            self.robot = position;
+        }
+
+        if status == Status::FoundOxygenMachine {
+            self.oxygen_machine = Some(position);
+        }
+
+        Ok(status)
+    }
+
+    pub fn position(&self) -> Position {
+        self.robot
+    }
+
+    pub fn oxygen_machine(&self) -> Option<Position> {
+        self.oxygen_machine
+    }
+
+    // Whether each cell found so far is a wall.
+    pub fn explored(&self) -> &HashMap<Position, bool> {
+        &self.occupied
+    }
+
+    pub fn cell(&self, position: Position) -> Cell {
+        if position == self.robot {
+            Cell::Droid
+        } else if Some(position) == self.oxygen_machine {
+            Cell::OxygenMachine
+        } else {
+            match self.occupied.get(&position) {
+                Some(true) => Cell::Wall,
+                Some(false) => Cell::Open,
+                None => Cell::Unexplored,
+            }
+        }
+    }
+
+    // Every move the droid has been told to make, and what it said back.
+    pub fn transcript(&self) -> Transcript {
+        self.recorder.transcript()
+    }
+
+    // Every cell of the screen, a row at a time.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        let middle = (SCREEN_SIZE / 2) as i64;
+        let size = SCREEN_SIZE as i64;
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| Position { x, y }))
+            .map(|position| {
+                let position = Position {
+                    x: position.x - middle,
+                    y: position.y - middle,
+                };
+                self.cell(position) as u8
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn droid() {
+        // A wall to the north, the oxygen machine to the east, and open space
+        // everywhere else.
+        let program = Program::parse(
+            "3,100,1008,100,1,101,1005,101,20,1008,100,4,101,1001,101,1,101,1105,1,24,\
+             1101,0,0,101,4,101,1105,1,0"
+                .as_bytes(),
+        )
+        .unwrap();
+        let mut droid = Droid::new(&program);
+
+        assert_eq!(droid.step(Direction::North), Ok(Status::HitWall));
+        assert_eq!(droid.position(), Position::origin());
+        assert_eq!(droid.step(Direction::East), Ok(Status::FoundOxygenMachine));
+        assert_eq!(droid.position(), Position { x: 1, y: 0 });
+        assert_eq!(droid.oxygen_machine(), Some(Position { x: 1, y: 0 }));
+        assert_eq!(droid.step(Direction::West), Ok(Status::Moved));
+
+        assert_eq!(droid.cell(Position { x: 0, y: -1 }), Cell::Wall);
+        assert_eq!(droid.cell(Position::origin()), Cell::Droid);
+        assert_eq!(droid.cell(Position { x: 1, y: 0 }), Cell::OxygenMachine);
+        assert_eq!(droid.cell(Position { x: 0, y: 1 }), Cell::Unexplored);
+
+        let framebuffer = droid.framebuffer();
+        let size = SCREEN_SIZE as usize;
+        assert_eq!(framebuffer.len(), size * size);
+        let [x, y]: [u16; 2] = Position::origin().into();
+        let index = y as usize * size + x as usize;
+        assert_eq!(framebuffer[index], Cell::Droid as u8);
+        assert_eq!(framebuffer[index + 1], Cell::OxygenMachine as u8);
+        assert_eq!(framebuffer[index - size], Cell::Wall as u8);
+
+        let transcript = droid.transcript();
+        assert_eq!(transcript.inputs().collect::<Vec<_>>(), [1, 4, 3]);
+        assert_eq!(transcript.outputs().collect::<Vec<_>>(), [0, 2, 1]);
+    }
+
+    #[test]
+    fn stub() {
+        let transcript: Transcript = "in 1\nout 0\nin 4\nout 2\nin 2\nout 1\n".parse().unwrap();
+
+        let mut droid = Droid::stub(transcript.clone());
+        assert_eq!(droid.step(Direction::North), Ok(Status::HitWall));
+        assert_eq!(droid.step(Direction::East), Ok(Status::FoundOxygenMachine));
+        assert_eq!(droid.step(Direction::South), Ok(Status::Moved));
+        assert_eq!(droid.position(), Position { x: 1, y: 1 });
+        assert_eq!(droid.cell(Position { x: 0, y: -1 }), Cell::Wall);
+        assert_eq!(droid.cell(Position { x: 1, y: 0 }), Cell::OxygenMachine);
+        assert_eq!(droid.transcript(), transcript);
+        assert_eq!(
+            droid.step(Direction::South),
+            Err("The droid didn't say what happened".to_string())
+        );
+
+        let mut droid = Droid::stub(transcript);
+        assert_eq!(
+            droid.step(Direction::West),
+            Err("Given 3 where the transcript has 1 after 0 values".to_string())
+        );
+    }
+}
@@ -0,0 +1,9 @@
+// The repair droid and the Intcode computer it runs on, with nothing to do
+// with the terminal, so that they can be built for the browser as well.
+
+pub mod droid;
+
+pub use intcode::{process, program, transcript};
+
+#[cfg(target_arch = "wasm32")]
+mod wasm;
@@ -0,0 +1,738 @@
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::fs::File;
+use std::io::{stdout, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use itertools::Itertools;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use structopt::StructOpt;
+use termion::event::Key;
+use termion::get_tty;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+
+mod utils;
+mod display;
+mod pathfinding;
+
+use common::{Answer, CommonOpts, Events};
+use display::{Hidden, Logged, Screen ,ScreenBuffer};
+use oxygen::droid::{Direction, Droid, Position, Status};
+use oxygen::process::{Input, Output, Process, State, Channel};
+use oxygen::program::Program;
+use oxygen::transcript::{Playback, Transcript};
+use pathfinding::astar;
+use termion::{color, cursor};
+use utils::Ticker;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+enum Tile {
+    #[default]
+    Empty,
+    Wall,
+    OxygenMachine,
+    Oxygen,
+    Robot,
+    Route,
+    Heat(u8),
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Tile::*;
+        match self {
+            Empty => write!(f, "{} {}", color::Bg(color::LightGreen), color::Bg(color::Reset)),
+            Wall => write!(f, "\u{2588}"),
+            Robot => write!(f, "{}X{}", color::Fg(color::Red), color::Fg(color::Reset)),
+            OxygenMachine => write!(f, "{}O{}", color::Fg(color::Blue), color::Fg(color::Reset)),
+            Oxygen => write!(f, "{} {}", color::Bg(color::LightBlue), color::Bg(color::Reset)),
+            Route => write!(f, "{} {}", color::Bg(color::Yellow), color::Bg(color::Reset)),
+            Heat(level) => write!(
+                f,
+                "{} {}",
+                color::Bg(color::Rgb(*level, 0, 255 - *level)),
+                color::Bg(color::Reset)
+            ),
+        }
+    }
+}
+
+struct Map {
+    occupied: HashMap<Position, bool>,
+    robot: Position,
+    oxygen_machine: Position,
+}
+
+impl Map {
+    fn parse(input: impl BufRead) -> Result<Self, Error> {
+        let mut occupied = HashMap::new();
+        let mut robot = None;
+        let mut oxygen_machine = None;
+
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line?.chars().enumerate() {
+                let position = Position { x: x as i64, y: y as i64 };
+                match c {
+                    '#' => {
+                        occupied.insert(position, true);
+                    }
+                    '.' => {
+                        occupied.insert(position, false);
+                    }
+                    'S' => {
+                        occupied.insert(position, false);
+                        robot = Some(position);
+                    }
+                    'O' => {
+                        occupied.insert(position, false);
+                        oxygen_machine = Some(position);
+                    }
+                    ' ' => {}
+                    _ => return Err(format!("Unknown tile {:?}", c).into()),
+                }
+            }
+        }
+
+        let robot = robot.ok_or_else(|| Error("No start position".to_string()))?;
+        let oxygen_machine =
+            oxygen_machine.ok_or_else(|| Error("No oxygen machine".to_string()))?;
+
+        // Keep the start at the origin so the map lines up on screen.
+        Ok(Map {
+            occupied: occupied
+                .into_iter()
+                .map(|(position, occupied)| (robot.offset(position), occupied))
+                .collect(),
+            robot: Position::origin(),
+            oxygen_machine: robot.offset(oxygen_machine),
+        })
+    }
+
+    fn write(&self, output: &mut impl Write) -> Result<(), Error> {
+        let min_x = self.occupied.keys().map(|p| p.x).min().unwrap_or(0);
+        let max_x = self.occupied.keys().map(|p| p.x).max().unwrap_or(0);
+        let min_y = self.occupied.keys().map(|p| p.y).min().unwrap_or(0);
+        let max_y = self.occupied.keys().map(|p| p.y).max().unwrap_or(0);
+
+        for y in min_y..=max_y {
+            let line: String = (min_x..=max_x)
+                .map(|x| {
+                    let position = Position { x, y };
+                    match self.occupied.get(&position) {
+                        Some(true) => '#',
+                        Some(false) if position == self.robot => 'S',
+                        Some(false) if position == self.oxygen_machine => 'O',
+                        Some(false) => '.',
+                        None => ' ',
+                    }
+                })
+                .collect();
+            writeln!(output, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<Self, Error> {
+        Map::parse(BufReader::new(File::open(path)?))
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        self.write(&mut File::create(path)?)
+    }
+
+    fn distances(&self, from: Position) -> HashMap<Position, usize> {
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(from, 0);
+        queue.push_back(from);
+
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for next in Direction::all().map(|direction| position.moved(direction)) {
+                if !self.occupied.get(&next).cloned().unwrap_or(true)
+                    && !distances.contains_key(&next)
+                {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    fn route(&self, from: Position, to: Position) -> Vec<Position> {
+        astar(
+            from,
+            to,
+            |position| {
+                Direction::all()
+                    .map(move |direction| position.moved(direction))
+                    .filter(|position| !self.occupied.get(position).cloned().unwrap_or(true))
+            },
+            |position| position.distance(to),
+        )
+        .unwrap_or_default()
+    }
+}
+
+struct Summary {
+    moves: Vec<Direction>,
+    distance: usize,
+    fill_time: usize,
+    open_cells: usize,
+    dead_ends: usize,
+    longest_path: usize,
+}
+
+impl Summary {
+    fn new(map: &Map) -> Self {
+        let route = map.route(map.robot, map.oxygen_machine);
+        let moves = std::iter::once(map.robot)
+            .chain(route.iter().cloned())
+            .zip(route.iter().cloned())
+            .map(|(from, to)| Direction::between(from, to).unwrap())
+            .collect();
+
+        let open: Vec<_> = map
+            .occupied
+            .iter()
+            .filter(|(_, occupied)| !**occupied)
+            .map(|(position, _)| *position)
+            .collect();
+
+        let dead_ends = open
+            .iter()
+            .filter(|position| {
+                Direction::all()
+                    .filter(|direction| {
+                        !map.occupied
+                            .get(&position.moved(*direction))
+                            .cloned()
+                            .unwrap_or(true)
+                    })
+                    .count()
+                    == 1
+            })
+            .count();
+
+        let longest_path = open
+            .iter()
+            .map(|position| map.distances(*position).values().cloned().max().unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+
+        Summary {
+            moves,
+            distance: route.len(),
+            fill_time: map
+                .distances(map.oxygen_machine)
+                .values()
+                .cloned()
+                .max()
+                .unwrap_or(0),
+            open_cells: open.len(),
+            dead_ends,
+            longest_path,
+        }
+    }
+}
+
+impl Summary {
+    // The answer to part 1, part 2, or both if no part is given, along with
+    // everything else there is to know about the map if `everything` is set.
+    fn answers(&self, part: Option<u8>, everything: bool) -> Vec<(&'static str, Answer)> {
+        let mut answers = vec![];
+        if part != Some(2) {
+            answers.push(("distance", self.distance.into()));
+        }
+        if part != Some(1) {
+            answers.push(("fill_time", self.fill_time.into()));
+        }
+        if everything {
+            answers.push(("moves", self.moves.iter().join("").into()));
+            answers.push(("open_cells", self.open_cells.into()));
+            answers.push(("dead_ends", self.dead_ends.into()));
+            answers.push(("longest_path", self.longest_path.into()));
+        }
+        answers
+    }
+
+    // The answers written out in full.
+    fn report(&self, part: Option<u8>) -> String {
+        let mut report = String::new();
+        if part != Some(2) {
+            report += &format!(
+                "The oxygen machine is {} moves from the start: {}\n",
+                self.distance,
+                self.moves.iter().join("")
+            );
+        }
+        if part != Some(1) {
+            report += &format!("Oxygen fills the area in {} minutes\n", self.fill_time);
+        }
+        report += &format!(
+            "There are {} open cells, {} of them dead ends, and the furthest apart are {} moves \
+             apart\n",
+            self.open_cells, self.dead_ends, self.longest_path
+        );
+        report
+    }
+}
+
+fn display_map(map: &Map, screen: &mut impl Screen) {
+    for (position, occupied) in map.occupied.iter() {
+        screen.set_tile((*position).into(), if *occupied { Tile::Wall } else { Tile::Empty })
+    }
+}
+
+fn show_route(map: &Map, route: &[Position], screen: &mut impl Screen, speed: Option<u64>) {
+    display_map(map, screen);
+    screen.set_tile(map.oxygen_machine.into(), Tile::OxygenMachine);
+
+    let mut ticker = speed.map(Ticker::with_rate);
+    let mut previous = map.robot;
+
+    for position in route.iter().cloned() {
+        screen.set_tile(previous.into(), Tile::Route);
+        screen.set_tile(position.into(), Tile::Robot);
+        previous = position;
+
+        if let Some(ref mut ticker) = ticker {
+            ticker.wait();
+        }
+    }
+}
+
+fn show_heat_map(map: &Map, distances: &HashMap<Position, usize>, screen: &mut impl Screen) {
+    display_map(map, screen);
+
+    let max_distance = distances.values().cloned().max().unwrap_or(0).max(1);
+    for (position, distance) in distances.iter() {
+        let level = (distance * 255 / max_distance) as u8;
+        screen.set_tile((*position).into(), Tile::Heat(level));
+    }
+
+    screen.set_tile(map.robot.into(), Tile::Robot);
+    screen.set_tile(map.oxygen_machine.into(), Tile::OxygenMachine);
+}
+
+fn fill_map(map: &Map, screen: &mut impl Screen, speed: Option<u64>) {
+    display_map(map, screen);
+
+    let mut ticker = speed.map(Ticker::with_rate);
+    let mut oxgenated = HashSet::new();
+    let mut next = vec![map.oxygen_machine];
+
+    while !next.is_empty() {
+        let mut next_turn: Vec<_> = vec![];
+        for position in next {
+            oxgenated.insert(position);
+            screen.set_tile(position.into(), Tile::Oxygen);
+
+            for direction in Direction::all() {
+                let position = position.moved(direction);
+                if !map.occupied.get(&position).cloned().unwrap_or(true) && !oxgenated.contains(&position) {
+                    next_turn.push(position);
+                }
+            }
+        }
+
+        next = next_turn;
+
+        if let Some(ref mut ticker) = ticker {
+            ticker.wait();
+        }
+    }
+}
+
+// Explore every branch of the maze at once by forking the droid's process
+// at each junction rather than walking back to unexplored areas.
+fn explore(program: &Program, screen: &mut impl Screen, speed: Option<u64>) -> Option<Map> {
+    let mut ticker = speed.map(Ticker::with_rate);
+
+    let mut occupied = HashMap::new();
+    let mut oxygen_machine = None;
+    occupied.insert(Position::origin(), false);
+
+    let input = Channel::new();
+    let output = Channel::new();
+    let droid = Process::new("ROBOT", program, &input, &output).fork((), ());
+    let mut droids = vec![(Position::origin(), droid)];
+
+    while !droids.is_empty() {
+        let mut next_droids = vec![];
+
+        for (position, droid) in droids {
+            if Some(position) == oxygen_machine {
+                screen.set_tile(position.into(), Tile::OxygenMachine);
+            } else {
+                screen.set_tile(position.into(), Tile::Empty);
+            }
+
+            for direction in Direction::all() {
+                let target = position.moved(direction);
+                if occupied.contains_key(&target) {
+                    continue;
+                }
+
+                let mut process = droid.fork(&input, &output);
+                input.put(direction as i64);
+
+                let state = process.execute();
+                assert_eq!(state, State::Blocked);
+
+                let status: Status = output.get().unwrap().try_into().unwrap();
+                occupied.insert(target, status == Status::HitWall);
+
+                if status == Status::HitWall {
+                    screen.set_tile(target.into(), Tile::Wall);
+                } else {
+                    if status == Status::FoundOxygenMachine {
+                        oxygen_machine = Some(target);
+                    }
+
+                    screen.set_tile(target.into(), Tile::Robot);
+                    next_droids.push((target, process.fork((), ())));
+                }
+            }
+        }
+
+        droids = next_droids;
+
+        if let Some(ref mut ticker) = ticker {
+            ticker.wait();
+        }
+    }
+
+    oxygen_machine.map(|oxygen_machine| Map {
+        occupied,
+        robot: Position::origin(),
+        oxygen_machine,
+    })
+}
+
+// Move the droid one step, showing what it found.
+fn move_droid(droid: &mut Droid, direction: Direction, screen: &mut impl Screen) -> Result<(), Error> {
+    let previous = droid.position();
+    let target = previous.moved(direction);
+    if droid.step(direction)? == Status::HitWall {
+        screen.set_tile(target.into(), Tile::Wall);
+    } else if Some(previous) == droid.oxygen_machine() {
+        screen.set_tile(previous.into(), Tile::OxygenMachine);
+        screen.set_tile(target.into(), Tile::Robot);
+    } else {
+        screen.set_tile(previous.into(), Tile::Empty);
+        screen.set_tile(target.into(), Tile::Robot);
+    }
+    Ok(())
+}
+
+fn explored_map(droid: &Droid) -> Option<Map> {
+    droid.oxygen_machine().map(|oxygen_machine| Map {
+        occupied: droid.explored().clone(),
+        robot: Position::origin(),
+        oxygen_machine,
+    })
+}
+
+// Move the droid with the arrow keys until q is pressed, writing every move
+// and the droid's reply to `record` if given.
+fn explore_manually(
+    program: &Program,
+    screen: &mut impl Screen,
+    record: Option<&Path>,
+) -> Result<Option<Map>, Error> {
+    let mut droid = Droid::new(program);
+    screen.set_tile(droid.position().into(), Tile::Robot);
+
+    for key in get_tty().unwrap().keys() {
+        let direction = match key.unwrap() {
+            Key::Up => Direction::North,
+            Key::Down => Direction::South,
+            Key::Left => Direction::West,
+            Key::Right => Direction::East,
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => break,
+            _ => continue,
+        };
+
+        move_droid(&mut droid, direction, screen)?;
+    }
+
+    if let Some(path) = record {
+        droid.transcript().save(path)?;
+    }
+
+    Ok(explored_map(&droid))
+}
+
+// Make the moves from a transcript again, with the droid's replies coming
+// from the transcript rather than the program.
+fn follow(transcript: Transcript, screen: &mut impl Screen, speed: Option<u64>) -> Result<Option<Map>, Error> {
+    let mut ticker = speed.map(Ticker::with_rate);
+    let moves: Vec<_> = transcript.inputs().map(Direction::try_from).collect::<Result<_, _>>()?;
+
+    let mut droid = Droid::stub(transcript);
+    screen.set_tile(droid.position().into(), Tile::Robot);
+
+    for direction in moves {
+        move_droid(&mut droid, direction, screen)?;
+
+        if let Some(ref mut ticker) = ticker {
+            ticker.wait();
+        }
+    }
+
+    Ok(explored_map(&droid))
+}
+
+fn get_map(screen: &mut impl Screen, opts: &Opts) -> Result<Map, Error> {
+    if let Some(ref path) = opts.load_map {
+        return Map::load(path);
+    }
+
+    let map = if let Some(ref path) = opts.stub {
+        follow(Transcript::load(path)?, screen, opts.common.speed())?
+    } else if opts.manual {
+        let program = read_program(opts)?;
+        explore_manually(&program, screen, opts.record.as_deref())?
+    } else {
+        let program = read_program(opts)?;
+        explore(&program, screen, opts.common.speed())
+    }
+    .ok_or_else(|| Error("Oxygen machine not found".to_string()))?;
+
+    if let Some(ref path) = opts.save_map {
+        map.save(path)?;
+    }
+
+    Ok(map)
+}
+
+fn show(mut screen: impl Screen, opts: &Opts) -> Result<(Map, Summary), Error> {
+    screen.clear();
+    let map = get_map(&mut screen, opts)?;
+
+    screen.clear();
+    if let Some(ref origin) = opts.heat_map {
+        let from = match origin {
+            HeatMapOrigin::Start => map.robot,
+            HeatMapOrigin::Oxygen => map.oxygen_machine,
+        };
+        show_heat_map(&map, &map.distances(from), &mut screen);
+    } else {
+        let route = map.route(map.robot, map.oxygen_machine);
+        show_route(&map, &route, &mut screen, opts.common.speed());
+        screen.clear();
+        fill_map(&map, &mut screen, opts.common.speed());
+    }
+
+    let summary = Summary::new(&map);
+    Ok((map, summary))
+}
+
+// The row of the screen just below the map, 1-based like the cursor.
+fn below(map: &Map) -> u16 {
+    let bottom = map.occupied.keys().map(|position| position.y).max().unwrap_or(0);
+    let [_, y] = <[u16; 2]>::from(Position { x: 0, y: bottom });
+    y + 2
+}
+
+fn run(screen: impl Screen, opts: &Opts) -> Result<(Map, Summary), Error> {
+    match opts.events {
+        Some(ref path) => show(Logged::new(screen, Events::create(path)?), opts),
+        None => show(screen, opts),
+    }
+}
+
+#[derive(Debug)]
+enum HeatMapOrigin {
+    Start,
+    Oxygen,
+}
+
+impl FromStr for HeatMapOrigin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(HeatMapOrigin::Start),
+            "oxygen" => Ok(HeatMapOrigin::Oxygen),
+            _ => Err(format!("Unknown heat map origin {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Explore the map manually using the arrow keys
+    #[structopt(short, long, conflicts_with_all = &["quiet", "json"])]
+    manual: bool,
+
+    /// Save the explored map to a file
+    #[structopt(long, parse(from_os_str))]
+    save_map: Option<PathBuf>,
+
+    /// Load a previously saved map instead of exploring
+    #[structopt(long, parse(from_os_str))]
+    load_map: Option<PathBuf>,
+
+    /// Write every move and the droid's reply to a transcript when exploring manually
+    #[structopt(long, parse(from_os_str), requires = "manual")]
+    record: Option<PathBuf>,
+
+    /// Check the droid does exactly what a transcript says, instead of exploring
+    #[structopt(long, parse(from_os_str), conflicts_with_all = &["manual", "load-map"])]
+    replay: Option<PathBuf>,
+
+    /// Make the moves from a transcript, with the droid's replies taken from it too
+    #[structopt(long, parse(from_os_str), conflicts_with_all = &["manual", "load-map", "replay"])]
+    stub: Option<PathBuf>,
+
+    /// Show the distance of each cell from the start or the oxygen machine
+    #[structopt(
+        long,
+        possible_values = &["start", "oxygen"],
+        conflicts_with_all = &["quiet", "json"]
+    )]
+    heat_map: Option<HeatMapOrigin>,
+
+    /// Write every change to the screen to this file, one JSON object per line
+    #[structopt(long, parse(from_os_str))]
+    events: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn read_program(opts: &Opts) -> Result<Program, Error> {
+    Ok(Program::parse(opts.common.input()?).unwrap())
+}
+
+fn replay(path: &Path, opts: &Opts) -> Result<usize, Error> {
+    let program = read_program(opts)?;
+    Ok(Playback::new(Transcript::load(path)?).replay(&program)?)
+}
+
+fn main() {
+    let opts = Opts::from_args();
+
+    if let Some(ref path) = opts.replay {
+        match replay(path, &opts) {
+            Ok(count) => println!("Replayed {} values", count),
+            Err(Error(message)) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Both parts are answered unless one is picked.
+    let part = opts.common.part;
+
+    if opts.common.answers_only() {
+        match run(Hidden {}, &opts) {
+            Ok((_, summary)) => {
+                let answers = summary.answers(part, opts.common.json);
+                opts.common.print_answers(&answers)
+            }
+            Err(Error(message)) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let result = if !opts.common.debug {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        let result = run(screen, &opts);
+        // Carry on below the map, or from the top if there isn't one.
+        let row = result.as_ref().map_or(1, |(map, _)| below(map));
+        print!("{}", cursor::Goto(1, row));
+        result
+    } else {
+        let screen = ScreenBuffer {};
+        run(screen, &opts)
+    };
+
+    match result {
+        Ok((_, summary)) => print!("{}", summary.report(part)),
+        Err(Error(message)) => println!("{}", message),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn save_and_load_map() {
+        let text = " ##\n#.O#\n#S##\n ##\n";
+
+        let map = Map::parse(text.as_bytes()).unwrap();
+        assert_eq!(map.robot, Position::origin());
+        assert_eq!(map.oxygen_machine, Position { x: 1, y: -1 });
+        assert_eq!(
+            map.route(map.robot, map.oxygen_machine),
+            vec![Position { x: 0, y: -1 }, Position { x: 1, y: -1 }]
+        );
+
+        let distances = map.distances(map.oxygen_machine);
+        assert_eq!(distances[&map.robot], 2);
+        assert_eq!(distances.values().max(), Some(&2));
+
+        let summary = Summary::new(&map);
+        assert_eq!(summary.moves, vec![Direction::North, Direction::East]);
+        assert_eq!(summary.distance, 2);
+        assert_eq!(summary.fill_time, 2);
+        assert_eq!(summary.open_cells, 3);
+        assert_eq!(summary.dead_ends, 2);
+        assert_eq!(summary.longest_path, 2);
+
+        let mut output = Vec::new();
+        map.write(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), text);
+    }
+
+    #[test]
+    fn parts() {
+        let map = Map::parse(" ##\n#.O#\n#S##\n ##\n".as_bytes()).unwrap();
+        let summary = Summary::new(&map);
+
+        let names = |part, everything| -> Vec<&str> {
+            summary
+                .answers(part, everything)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        assert_eq!(names(None, false), ["distance", "fill_time"]);
+        assert_eq!(names(Some(1), false), ["distance"]);
+        assert_eq!(
+            names(Some(2), true),
+            ["fill_time", "moves", "open_cells", "dead_ends", "longest_path"]
+        );
+        assert_eq!(summary.answers(None, true)[2].1, "NE".into());
+
+        assert_eq!(
+            summary.report(Some(1)),
+            "The oxygen machine is 2 moves from the start: NE\nThere are 3 open cells, 2 of \
+             them dead ends, and the furthest apart are 2 moves apart\n"
+        );
+        assert!(summary.report(None).contains("Oxygen fills the area in 2 minutes\n"));
+    }
+}
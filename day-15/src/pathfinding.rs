@@ -0,0 +1,116 @@
+use std::cmp::{Ord, Ordering};
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+#[derive(Eq, PartialEq)]
+struct Entry<N> {
+    node: N,
+    distance: usize,
+    estimate: usize,
+}
+
+impl<N: Eq> Ord for Entry<N> {
+    fn cmp(&self, other: &Entry<N>) -> Ordering {
+        (self.distance + self.estimate)
+            .cmp(&(other.distance + other.estimate))
+            .reverse()
+    }
+}
+
+impl<N: Eq> PartialOrd for Entry<N> {
+    fn partial_cmp(&self, other: &Entry<N>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Find the shortest path from `start` to `goal` where every step has a cost
+/// of one.  `heuristic` must never overestimate the remaining distance.
+///
+/// Returns the nodes along the path, excluding `start`.
+pub fn astar<N, F, I, H>(start: N, goal: N, neighbours: F, heuristic: H) -> Option<Vec<N>>
+where
+    N: Copy + Eq + Hash,
+    F: Fn(N) -> I,
+    I: IntoIterator<Item = N>,
+    H: Fn(N) -> usize,
+{
+    let mut heap = BinaryHeap::new();
+    let mut previous: HashMap<N, Option<N>> = HashMap::new();
+    let mut distances: HashMap<N, usize> = HashMap::new();
+
+    heap.push(Entry {
+        node: start,
+        distance: 0,
+        estimate: heuristic(start),
+    });
+    previous.insert(start, None);
+    distances.insert(start, 0);
+
+    while let Some(Entry { node, distance, .. }) = heap.pop() {
+        if node == goal {
+            let mut path = vec![];
+            let mut current = node;
+            while let Some(Some(node)) = previous.get(&current) {
+                path.push(current);
+                current = *node;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        if distance > distances[&node] {
+            continue;
+        }
+
+        for next in neighbours(node) {
+            let next_distance = distance + 1;
+            if distances
+                .get(&next)
+                .map(|&known| next_distance < known)
+                .unwrap_or(true)
+            {
+                distances.insert(next, next_distance);
+                previous.insert(next, Some(node));
+                heap.push(Entry {
+                    node: next,
+                    distance: next_distance,
+                    estimate: heuristic(next),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_shortest_path() {
+        let grid = ["#######", "#.....#", "#.###.#", "#.#...#", "#######"];
+        let open = |(x, y): (usize, usize)| grid[y].as_bytes()[x] == b'.';
+
+        let path = astar(
+            (1, 3),
+            (3, 3),
+            |(x, y)| {
+                vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .into_iter()
+                    .filter(|&position| open(position))
+            },
+            |(x, y)| ((x as isize - 3).abs() + (y as isize - 3).abs()) as usize,
+        )
+        .unwrap();
+
+        assert_eq!(path.len(), 10);
+        assert_eq!(path.last(), Some(&(3, 3)));
+    }
+
+    #[test]
+    fn no_path() {
+        let path = astar(0, 5, |n: i32| if n > -3 { vec![n - 1] } else { vec![] }, |_| 0);
+        assert_eq!(path, None);
+    }
+}
@@ -0,0 +1,43 @@
+// The repair droid as seen from JavaScript, for drawing on a canvas: see
+// web/index.html.
+
+use std::convert::TryFrom;
+
+use wasm_bindgen::prelude::*;
+
+use crate::droid::{self, Direction, Droid};
+use crate::program::Program;
+
+#[wasm_bindgen]
+pub struct Game {
+    droid: Droid,
+}
+
+#[wasm_bindgen]
+impl Game {
+    #[wasm_bindgen(constructor)]
+    pub fn new(program: &str) -> Result<Game, JsValue> {
+        let program = Program::parse(program.as_bytes())
+            .map_err(|error| JsValue::from_str(&format!("{:?}", error)))?;
+        Ok(Game {
+            droid: Droid::new(&program),
+        })
+    }
+
+    // Move the droid 1 north, 2 south, 3 west or 4 east, returning 0 if it
+    // hit a wall, 1 if it moved and 2 if it found the oxygen machine.
+    pub fn step(&mut self, direction: i32) -> Result<u8, JsValue> {
+        let direction = Direction::try_from(i64::from(direction)).map_err(JsValue::from)?;
+        Ok(self.droid.step(direction).map_err(JsValue::from)? as u8)
+    }
+
+    pub fn size(&self) -> u32 {
+        droid::SCREEN_SIZE.into()
+    }
+
+    // One byte per cell, a row at a time: 0 unexplored, 1 open, 2 wall,
+    // 3 oxygen machine and 4 the droid.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        self.droid.framebuffer()
+    }
+}
@@ -0,0 +1,320 @@
+use std::io::Read;
+use std::iter::repeat_n;
+use std::path::PathBuf;
+use rayon::prelude::*;
+use structopt::StructOpt;
+
+use common::{input, CommonOpts, Flag};
+
+const CHUNK_SIZE: usize = 4096;
+const BASE_PATTERN: [i32; 4] = [0, 1, 0, -1];
+
+// The digits of the signal, which can be split across lines or spaced out.
+fn read_sequence(mut input: impl Read) -> Result<Box<[i32]>, String> {
+    let mut text = String::new();
+    input
+        .read_to_string(&mut text)
+        .map_err(|error| format!("Can't read the signal: {}", error))?;
+
+    let sequence = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .enumerate()
+        .map(|(index, c)| {
+            c.to_digit(10)
+                .map(|digit| digit as i32)
+                .ok_or_else(|| {
+                    format!("Character {} of the signal, {:?}, isn't a digit", index + 1, c)
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if sequence.is_empty() {
+        return Err("The signal is empty".to_string());
+    }
+    Ok(sequence.into_boxed_slice())
+}
+
+fn step(pattern: &[i32], sequence: &[i32]) -> Box<[i32]> {
+    (0..sequence.len())
+        .into_par_iter()
+        .map(|index| {
+            let pattern = pattern.iter().flat_map(|d| repeat_n(d, index+1)).cycle().skip(1);
+            let value: i32 = sequence.iter().zip(pattern).map(|(x, y)| x * y).sum();
+            value.abs() % 10
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+fn sequence_string(sequence: &[i32]) -> String {
+    sequence.iter().map(|c| c.to_string()).collect()
+}
+
+fn value(digits: &[i32]) -> usize {
+    Iterator::zip(digits.iter(), (0..digits.len()).rev()).map(|(d, e)| *d as usize * 10_usize.pow(e as u32)).sum::<usize>()
+}
+
+fn run_phases(pattern: &[i32], sequence: &[i32], phases: usize) -> Box<[i32]> {
+    let mut sequence: Box<[i32]> = sequence.into();
+
+    for _ in 0..phases {
+        sequence = step(pattern, &sequence);
+    }
+
+    sequence[..8].into()
+}
+
+// Once the offset is in the second half of the signal every digit from the
+// offset onwards is just the sum of the digits after it, as the pattern is
+// zero before the digit and one for the rest of the signal.
+//
+// The sum is done in chunks: first the total of each chunk is found in
+// parallel, then each chunk is summed in parallel starting from the total
+// of the chunks after it.
+fn suffix_sum_phase(sequence: &mut [i32]) {
+    let chunk_size = (sequence.len() / rayon::current_num_threads()).max(CHUNK_SIZE);
+
+    let totals: Vec<i32> = sequence
+        .par_chunks(chunk_size)
+        .map(|chunk| chunk.iter().sum::<i32>() % 10)
+        .collect();
+
+    let mut carries = vec![0; totals.len()];
+    for index in (0..totals.len().saturating_sub(1)).rev() {
+        carries[index] = (carries[index + 1] + totals[index + 1]) % 10;
+    }
+
+    sequence
+        .par_chunks_mut(chunk_size)
+        .zip(carries)
+        .for_each(|(chunk, carry)| {
+            let mut total = carry;
+            for digit in chunk.iter_mut().rev() {
+                total = (total + *digit) % 10;
+                *digit = total;
+            }
+        });
+}
+
+// Digit `i` only depends on the digits from `i` onwards, so the digits
+// after `offset` can be computed without the rest of the signal.  Each
+// digit is a sum of alternating runs of `i + 1` digits, which can be read
+// from a table of partial sums, giving O(n log n) work per phase.
+fn partial_sum_phase(sequence: &mut [i32], offset: usize) {
+    let mut partial_sums = Vec::with_capacity(sequence.len() + 1);
+    partial_sums.push(0i64);
+    for digit in sequence.iter() {
+        partial_sums.push(partial_sums.last().unwrap() + *digit as i64);
+    }
+
+    let length = sequence.len();
+    let sum = |start: usize, end: usize| partial_sums[end.min(length)] - partial_sums[start.min(length)];
+
+    sequence.par_iter_mut().enumerate().for_each(|(index, digit)| {
+        let run = offset + index + 1;
+        let mut start = index;
+        let mut total = 0;
+
+        while start < length {
+            total += sum(start, start + run);
+            total -= sum(start + 2 * run, start + 3 * run);
+            start += 4 * run;
+        }
+
+        *digit = (total.abs() % 10) as i32;
+    });
+}
+
+// The suffix sum is only valid when the offset is in the second half of
+// the signal; otherwise fall back to the (much slower) partial sum method.
+// Both rely on the base pattern, so any other pattern has to run every
+// phase over the whole signal.
+fn get_offset(
+    pattern: &[i32],
+    sequence: &[i32],
+    repetitions: usize,
+    offset: usize,
+    phases: usize,
+) -> Box<[i32]> {
+    let length = repetitions * sequence.len();
+
+    if pattern != BASE_PATTERN {
+        let mut full_sequence: Box<[i32]> = sequence.iter().cycle().take(length).cloned().collect();
+        for _ in 0..phases {
+            full_sequence = step(pattern, &full_sequence);
+        }
+        return full_sequence[offset..offset + 8].into();
+    }
+
+    let mut full_sequence: Vec<i32> = sequence.iter().cycle().take(length).skip(offset).cloned().collect();
+
+    for _ in 0..phases {
+        if offset * 2 >= length {
+            suffix_sum_phase(&mut full_sequence);
+        } else {
+            partial_sum_phase(&mut full_sequence, offset);
+        }
+    }
+
+    full_sequence.truncate(8);
+    full_sequence.into_boxed_slice()
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Number of phases to run
+    phases: usize,
+
+    /// Read the signal from this file, the same as --input
+    #[structopt(parse(from_os_str), conflicts_with = "input")]
+    signal: Option<PathBuf>,
+
+    /// Number of times the signal is repeated in part 2
+    #[structopt(short, long, default_value = "10000")]
+    repetitions: usize,
+
+    /// Base pattern, as a comma separated list
+    #[structopt(long, default_value = "0,1,0,-1", use_delimiter = true, allow_hyphen_values = true)]
+    pattern: Vec<i32>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let sequence = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .and_then(|_| match &opts.signal {
+            Some(path) => input::open(Some(path)),
+            None => opts.common.input(),
+        })
+        .and_then(read_sequence);
+    let sequence = match sequence {
+        Ok(sequence) => sequence,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    let part = opts.common.part(2);
+    let needed = if part == 1 { 8 } else { 7 };
+    if sequence.len() < needed {
+        eprintln!("The signal has {} digits, but needs at least {}", sequence.len(), needed);
+        std::process::exit(1);
+    }
+
+    let result = if part == 1 {
+        run_phases(&opts.pattern, &sequence, opts.phases)
+    } else {
+        let offset: usize = value(&sequence[..7]);
+        let length = sequence.len() * opts.repetitions;
+        if offset + 8 > length {
+            eprintln!("The message offset {} is past the end of the signal", offset);
+            std::process::exit(1);
+        }
+        get_offset(&opts.pattern, &sequence, opts.repetitions, offset, opts.phases)
+    };
+
+    if opts.common.answers_only() {
+        opts.common.print_answers(&[("message", sequence_string(&result).into())]);
+    } else {
+        println!("{}", &sequence_string(&result));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short() {
+        let input = vec![1, 2, 3, 4, 5, 6, 7, 8];
+
+        let output = step(&[0, 1, 0, -1], &input);
+
+        assert_eq!(&*output, &[4, 8, 2, 2, 6, 1, 5, 8]);
+
+        let output = step(&[0, 1, 0, -1], &output);
+
+        assert_eq!(&*output, &[3, 4, 0, 4, 0, 4, 3, 8]);
+    }
+
+    #[test]
+    fn partial_sums() {
+        let input = vec![1, 2, 3, 4, 5, 6, 7, 8];
+
+        let mut output = input.clone();
+        partial_sum_phase(&mut output, 0);
+        assert_eq!(&*output, &*step(&[0, 1, 0, -1], &input));
+
+        let mut output = input[3..].to_vec();
+        partial_sum_phase(&mut output, 3);
+        assert_eq!(&*output, &step(&[0, 1, 0, -1], &input)[3..]);
+    }
+
+    #[test]
+    fn suffix_sums() {
+        let input: Vec<i32> = (0..10_000).map(|index| (index * 7 + 3) % 10).collect();
+
+        let mut expected = input.clone();
+        let mut total = 0;
+        for index in (0..expected.len()).rev() {
+            total += expected[index];
+            expected[index] = total % 10;
+        }
+
+        let mut output = input;
+        suffix_sum_phase(&mut output);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn other_pattern() {
+        let input = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let pattern = [1, 0, -1, 0];
+
+        let output = step(&pattern, &step(&pattern, &[input.clone(), input.clone()].concat()));
+
+        assert_eq!(&*get_offset(&pattern, &input, 2, 4, 2), &output[4..12]);
+    }
+
+    #[test]
+    fn repetitions() {
+        let input: Vec<i32> = "03036732577212944063491565474664"
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as i32)
+            .collect();
+
+        let output = get_offset(&BASE_PATTERN, &input, 10_000, value(&input[..7]), 100);
+
+        assert_eq!(sequence_string(&output), "84462026");
+    }
+
+    #[test]
+    fn read() {
+        let sequence = read_sequence("1234\n 5678\r\n90\n".as_bytes()).unwrap();
+        assert_eq!(&*sequence, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
+
+        assert_eq!(
+            read_sequence("12\n3x4".as_bytes()).err(),
+            Some("Character 4 of the signal, 'x', isn't a digit".to_string())
+        );
+        assert_eq!(read_sequence(" \n".as_bytes()).err(), Some("The signal is empty".to_string()));
+    }
+
+    #[test]
+    fn part_1() {
+        let input: Vec<i32> = "80871224585914546619083218645595"
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as i32)
+            .collect();
+
+        let output = run_phases(&BASE_PATTERN, &input, 100);
+
+        assert_eq!(sequence_string(&output), "24176176");
+    }
+}
\ No newline at end of file
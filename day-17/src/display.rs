@@ -8,6 +8,10 @@ pub trait Screen {
     fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, position: [u16; 2], tile: T);
     fn goto(&mut self, position: [u16; 2]);
     fn print(&mut self, text: String);
+    // Remember where the cursor is, to go back there after drawing
+    // somewhere else.
+    fn save_cursor(&mut self);
+    fn restore_cursor(&mut self);
 }
 
 impl<W: Write> Screen for W {
@@ -28,6 +32,15 @@ impl<W: Write> Screen for W {
     fn print(&mut self, text: String) {
         print!("{}\r\n", text);
     }
+
+    fn save_cursor(&mut self) {
+        let _ = write!(self, "{}", cursor::Save);
+    }
+
+    fn restore_cursor(&mut self) {
+        let _ = write!(self, "{}", cursor::Restore);
+        let _ = self.flush();
+    }
 }
 
 pub struct ScreenBuffer {}
@@ -44,4 +57,25 @@ impl Screen for ScreenBuffer {
     fn print(&mut self, text: String) {
         println!("{}\n", text);
     }
+
+    fn save_cursor(&mut self) {}
+
+    fn restore_cursor(&mut self) {}
+}
+
+// A screen which shows nothing, for when only the answers are wanted.
+pub struct Hidden {}
+
+impl Screen for Hidden {
+    fn clear(&mut self) {}
+
+    fn set_tile<T: fmt::Display + fmt::Debug>(&mut self, _: [u16; 2], _: T) {}
+
+    fn goto(&mut self, _: [u16; 2]) {}
+
+    fn print(&mut self, _: String) {}
+
+    fn save_cursor(&mut self) {}
+
+    fn restore_cursor(&mut self) {}
 }
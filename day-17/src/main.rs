@@ -0,0 +1,1077 @@
+use std::char;
+use std::collections::{HashMap, HashSet};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::fs;
+use std::io::stdout;
+use std::iter::once;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use termion::raw::IntoRawMode;
+
+mod display;
+mod utils;
+
+use common::{CommonOpts, Flag};
+use display::{Hidden, Screen, ScreenBuffer};
+use intcode::process::{Channel, Execute, Output, Process, State};
+use intcode::program::Program;
+use intcode::transcript::{Playback, Recorder, RecordingInput, RecordingOutput, Transcript};
+use termion::{color, cursor};
+use utils::Ticker;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Movement {
+    Left,
+    Forward,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Instruction {
+    Left,
+    Forward(usize),
+    Right,
+}
+
+impl From<Movement> for Instruction {
+    fn from(movement: Movement) -> Self {
+        match movement {
+            Movement::Left => Instruction::Left,
+            Movement::Forward => Instruction::Forward(1),
+            Movement::Right => Instruction::Right,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Instruction::*;
+        match self {
+            Left => write!(f, "L"),
+            Forward(num) => write!(f, "{}", num),
+            Right => write!(f, "R"),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Instructions<T>(Vec<T>);
+
+impl Instructions<Instruction> {
+    fn new(moves: impl IntoIterator<Item = Movement>) -> Self {
+        let mut instructions = vec![];
+        let mut current_forward = None;
+
+        for movement in moves {
+            if movement == Movement::Forward {
+                current_forward = Some(current_forward.unwrap_or_default() + 1)
+            } else {
+                if let Some(num) = current_forward {
+                    instructions.push(Instruction::Forward(num));
+                    current_forward = None;
+                }
+                instructions.push(movement.into());
+            }
+        }
+
+        if let Some(num) = current_forward {
+            instructions.push(Instruction::Forward(num));
+        }
+
+        Instructions(instructions)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Instructions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for instruction in self.0.iter() {
+            if !first {
+                write!(f, ",")?;
+            } else {
+                first = false;
+            }
+            write!(f, "{}", instruction)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<i64> for Direction {
+    type Error = Error;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        use Direction::*;
+        match value {
+            0 => Ok(North),
+            1 => Ok(East),
+            2 => Ok(South),
+            3 => Ok(West),
+            _ => Err(format!("Unknown direction {}", value).into()),
+        }
+    }
+}
+
+impl Direction {
+    fn all() -> impl Iterator<Item = Direction> {
+        (0..4).map(Direction::try_from).map(Result::unwrap)
+    }
+
+    fn turn(self, movement: Movement) -> Self {
+        let offset = match movement {
+            Movement::Left => 3,
+            Movement::Forward => 0,
+            Movement::Right => 1,
+        };
+
+        (((self as i64) + offset) % 4).try_into().unwrap()
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+enum Tile {
+    #[default]
+    Empty,
+    Scaffolding,
+    Robot(Direction),
+    // Scaffolding the robot has been over.
+    Visited,
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Direction::*;
+        use Tile::*;
+        match self {
+            Empty => write!(f, " "),
+            Scaffolding => write!(f, "#"),
+            Robot(North) => write!(f, "^"),
+            Robot(East) => write!(f, ">"),
+            Robot(South) => write!(f, "v"),
+            Robot(West) => write!(f, "<"),
+            Visited => write!(f, "{}#{}", color::Fg(color::Green), color::Fg(color::Reset)),
+        }
+    }
+}
+
+impl TryFrom<char> for Tile {
+    type Error = Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        use Direction::*;
+        use Tile::*;
+        match value {
+            '#' => Ok(Scaffolding),
+            '.' => Ok(Empty),
+            '^' => Ok(Robot(North)),
+            '>' => Ok(Robot(East)),
+            'v' => Ok(Robot(South)),
+            '<' => Ok(Robot(West)),
+            _ => Err(format!("Unknown tile {}", value).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Position {
+    x: i64,
+    y: i64,
+}
+
+#[allow(dead_code)]
+impl Position {
+    fn origin() -> Self {
+        Position { x: 0, y: 0 }
+    }
+
+    fn moved(&self, direction: Direction) -> Position {
+        use Direction::*;
+        let mut position = *self;
+        match direction {
+            North => position.y -= 1,
+            East => position.x += 1,
+            South => position.y += 1,
+            West => position.x -= 1,
+        }
+        position
+    }
+
+    fn adjacent(self) -> impl Iterator<Item = Position> {
+        Direction::all().map(move |direction| self.moved(direction))
+    }
+
+    fn length(self) -> usize {
+        self.x.unsigned_abs() as usize + self.y.unsigned_abs() as usize
+    }
+
+    fn offset(self, other: Position) -> Position {
+        Position {
+            x: other.x - self.x,
+            y: other.y - self.y,
+        }
+    }
+
+    fn distance(self, other: Position) -> usize {
+        self.offset(other).length()
+    }
+}
+
+impl From<Position> for [u16; 2] {
+    fn from(position: Position) -> Self {
+        [position.x as u16, position.y as u16]
+    }
+}
+
+#[derive(Clone)]
+struct Robot {
+    position: Position,
+    direction: Direction,
+}
+
+struct Map {
+    occupied: HashMap<Position, bool>,
+    robot: Robot,
+}
+
+#[allow(dead_code)]
+impl Map {
+    // Pass `visit` each route which visits all of the scaffolding, crossing
+    // straight over or turning at intersections, until it returns false or
+    // `limit` routes have been found.  The route which always prefers to go
+    // straight comes first.  Returns how many routes were visited.
+    fn each_route(&self, limit: usize, mut visit: impl FnMut(&[Movement]) -> bool) -> usize {
+        struct Search<'a> {
+            visit: &'a mut dyn FnMut(&[Movement]) -> bool,
+            count: usize,
+            limit: usize,
+            stopped: bool,
+        }
+
+        fn search(
+            map: &Map,
+            robot: Robot,
+            visits: &mut HashMap<Position, usize>,
+            remaining: usize,
+            route: &mut Vec<Movement>,
+            state: &mut Search,
+        ) {
+            if state.stopped || state.count >= state.limit {
+                return;
+            }
+
+            let mut moved = false;
+
+            for movement in [Movement::Forward, Movement::Left, Movement::Right].iter() {
+                let direction = robot.direction.turn(*movement);
+                let position = robot.position.moved(direction);
+
+                if !map.has_scaffolding(position) {
+                    continue;
+                }
+
+                let capacity = if map.is_intersection(position) { 2 } else { 1 };
+                let count = visits.get(&position).cloned().unwrap_or_default();
+                if count >= capacity {
+                    continue;
+                }
+
+                moved = true;
+                visits.insert(position, count + 1);
+                if *movement != Movement::Forward {
+                    route.push(*movement);
+                }
+                route.push(Movement::Forward);
+
+                let remaining = if count == 0 { remaining - 1 } else { remaining };
+                search(
+                    map,
+                    Robot {
+                        position,
+                        direction,
+                    },
+                    visits,
+                    remaining,
+                    route,
+                    state,
+                );
+
+                route.pop();
+                if *movement != Movement::Forward {
+                    route.pop();
+                }
+                visits.insert(position, count);
+            }
+
+            if !moved && remaining == 0 {
+                state.count += 1;
+                state.stopped = !(state.visit)(route);
+            }
+        }
+
+        let num_scaffolding = self
+            .occupied
+            .keys()
+            .filter(|position| self.has_scaffolding(**position))
+            .count();
+
+        let mut visits = HashMap::new();
+        visits.insert(self.robot.position, 1);
+
+        let mut state = Search {
+            visit: &mut visit,
+            count: 0,
+            limit,
+            stopped: false,
+        };
+        search(
+            self,
+            self.robot.clone(),
+            &mut visits,
+            num_scaffolding - 1,
+            &mut vec![],
+            &mut state,
+        );
+        state.count
+    }
+
+    fn is_intersection(&self, position: Position) -> bool {
+        Direction::all().all(|direction| self.has_scaffolding(position.moved(direction)))
+    }
+
+    fn has_scaffolding(&self, position: Position) -> bool {
+        !self.occupied.get(&position).cloned().unwrap_or(true)
+    }
+
+    fn intersections<'a>(&'a self) -> impl Iterator<Item = Position> + 'a {
+        let positions: Vec<_> = self.occupied.keys().collect();
+        positions.into_iter().cloned().filter(move |position| {
+            self.has_scaffolding(*position) && self.is_intersection(*position)
+        })
+    }
+}
+
+fn build_map(output: &[i64]) -> Map {
+    let mut occupied = HashMap::new();
+    let mut robot = None;
+
+    let mut position = Position::origin();
+
+    for item in output
+        .iter()
+        .map(|v| *v as u32)
+        .map(char::from_u32)
+        .map(Option::unwrap)
+    {
+        if item == '\n' {
+            position.x = 0;
+            position.y += 1;
+        } else {
+            let tile: Tile = item.try_into().unwrap();
+            occupied.insert(position, tile == Tile::Empty);
+
+            if let Tile::Robot(direction) = tile {
+                robot = Some(Robot {
+                    position,
+                    direction,
+                });
+            }
+
+            position.x += 1;
+        }
+    }
+
+    Map {
+        occupied,
+        robot: robot.unwrap(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    A,
+    B,
+    C,
+}
+
+impl Function {
+    fn all() -> impl Iterator<Item = Function> {
+        [Function::A, Function::B, Function::C].iter().cloned()
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Function::*;
+        match self {
+            A => write!(f, "A"),
+            B => write!(f, "B"),
+            C => write!(f, "C"),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RobotProgram {
+    functions: Vec<Instructions<Instruction>>,
+    calls: Instructions<Function>,
+}
+
+impl RobotProgram {
+    fn parse(text: &str) -> Result<Self, Error> {
+        let lines: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+
+        if lines.len() != 4 {
+            return Err(format!(
+                "Expected a main routine and three functions, found {} lines",
+                lines.len()
+            )
+            .into());
+        }
+
+        for (number, line) in lines.iter() {
+            if !line.is_ascii() {
+                return Err(format!("Line {}: not ASCII", number).into());
+            }
+
+            if line.len() > MAX_FUNCTION_LENGTH {
+                return Err(format!(
+                    "Line {}: {} characters, limit is {}",
+                    number,
+                    line.len(),
+                    MAX_FUNCTION_LENGTH
+                )
+                .into());
+            }
+        }
+
+        let (number, main) = lines[0];
+        let calls = main
+            .split(',')
+            .map(|call| match call {
+                "A" => Ok(Function::A),
+                "B" => Ok(Function::B),
+                "C" => Ok(Function::C),
+                _ => Err(Error(format!("Line {}: unknown function {:?}", number, call))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let functions = lines[1..]
+            .iter()
+            .map(|(number, function)| {
+                function
+                    .split(',')
+                    .map(|instruction| match instruction {
+                        "L" => Ok(Instruction::Left),
+                        "R" => Ok(Instruction::Right),
+                        num => match num.parse() {
+                            Ok(num) if num > 0 => Ok(Instruction::Forward(num)),
+                            _ => Err(Error(format!(
+                                "Line {}: unknown instruction {:?}",
+                                number, instruction
+                            ))),
+                        },
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Instructions)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RobotProgram {
+            functions,
+            calls: Instructions(calls),
+        })
+    }
+
+    fn encode<'a>(&'a self) -> impl Iterator<Item = u8> + 'a {
+        once(self.calls.to_string())
+            .chain(self.functions.iter().map(|f| f.to_string()))
+            .flat_map(|seq| seq.as_bytes().to_vec().into_iter().chain(once(b'\n')))
+    }
+}
+
+const MAX_FUNCTION_LENGTH: usize = 20;
+const MAX_CALLS: usize = 10;
+const MAX_ROUTES: usize = 10_000;
+
+// Search for ways of splitting the route into calls to at most three
+// functions, stopping once `limit` have been found.  Functions are assigned
+// in order, so the first call is always to A, and the first call that isn't
+// to A is always to B.
+fn collapse(sequence: &[Movement], limit: usize) -> Vec<RobotProgram> {
+    fn search<'a>(
+        sequence: &'a [Movement],
+        index: usize,
+        functions: &mut Vec<&'a [Movement]>,
+        calls: &mut Vec<Function>,
+        solutions: &mut Vec<RobotProgram>,
+        seen: &mut HashSet<Vec<u8>>,
+        limit: usize,
+    ) {
+        if solutions.len() >= limit {
+            return;
+        }
+
+        if index == sequence.len() {
+            let mut functions: Vec<_> = functions
+                .iter()
+                .map(|moves| Instructions::new(moves.iter().cloned()))
+                .collect();
+            while functions.len() < 3 {
+                functions.push(functions[0].clone());
+            }
+
+            let program = RobotProgram {
+                functions,
+                calls: Instructions(calls.clone()),
+            };
+            if seen.insert(program.encode().collect()) {
+                solutions.push(program);
+            }
+            return;
+        }
+
+        if calls.len() == MAX_CALLS {
+            return;
+        }
+
+        for (function, moves) in Function::all().zip(functions.clone()) {
+            if sequence[index..].starts_with(moves) {
+                calls.push(function);
+                search(sequence, index + moves.len(), functions, calls, solutions, seen, limit);
+                calls.pop();
+            }
+        }
+
+        if let Some(function) = Function::all().nth(functions.len()) {
+            for end in index + 1..=sequence.len() {
+                let moves = &sequence[index..end];
+                if Instructions::new(moves.iter().cloned()).to_string().len() > MAX_FUNCTION_LENGTH {
+                    break;
+                }
+
+                functions.push(moves);
+                calls.push(function);
+                search(sequence, end, functions, calls, solutions, seen, limit);
+                calls.pop();
+                functions.pop();
+            }
+        }
+    }
+
+    let mut solutions = vec![];
+    search(
+        sequence,
+        0,
+        &mut vec![],
+        &mut vec![],
+        &mut solutions,
+        &mut HashSet::new(),
+        limit,
+    );
+    solutions
+}
+
+// Follows the robot through its video feed, keeping track of which
+// scaffolding it's been over.
+struct Tracker {
+    line: String,
+    frame: Vec<String>,
+    robot: Robot,
+    visited: HashSet<Position>,
+}
+
+impl Tracker {
+    fn new(robot: &Robot) -> Self {
+        Tracker {
+            line: String::new(),
+            frame: vec![],
+            robot: robot.clone(),
+            visited: once(robot.position).collect(),
+        }
+    }
+
+    // Read more of the feed, returning each tile the robot has been over
+    // since it was last seen.
+    fn watch(&mut self, values: &[i64]) -> Vec<Position> {
+        let mut visited = vec![];
+        for &value in values {
+            match char::from_u32(value as u32) {
+                Some('\n') if self.line.is_empty() => visited.extend(self.end_frame()),
+                Some('\n') => self.frame.push(std::mem::take(&mut self.line)),
+                Some(c) if value < 128 => self.line.push(c),
+                // The dust collected at the end.
+                _ => {}
+            }
+        }
+        visited
+    }
+
+    fn end_frame(&mut self) -> Vec<Position> {
+        // The prompts for the robot's program come through the feed as well,
+        // so only lines which could be part of the map count.
+        let frame = std::mem::take(&mut self.frame);
+        let robot = frame
+            .iter()
+            .filter(|line| line.chars().all(|c| "#.^>v<X".contains(c)))
+            .enumerate()
+            .find_map(|(y, line)| {
+                line.chars().enumerate().find_map(|(x, c)| match Tile::try_from(c) {
+                    Ok(Tile::Robot(direction)) => Some(Robot {
+                        position: Position {
+                            x: x as i64,
+                            y: y as i64,
+                        },
+                        direction,
+                    }),
+                    _ => None,
+                })
+            });
+        let robot = match robot {
+            Some(robot) => robot,
+            None => return vec![],
+        };
+
+        // The robot only ever moves in a straight line between frames.
+        let mut passed = vec![];
+        let mut position = self.robot.position;
+        while position != robot.position {
+            let next = position.moved(robot.direction);
+            if next.distance(robot.position) >= position.distance(robot.position) {
+                break;
+            }
+            position = next;
+            passed.push(position);
+        }
+        if position != robot.position {
+            passed.push(robot.position);
+        }
+        self.visited.extend(passed.iter().cloned());
+        self.robot = robot;
+        passed
+    }
+
+    // What fraction of the scaffolding on the map has been visited.
+    fn coverage(&self, map: &Map) -> (usize, usize) {
+        let scaffolding: HashSet<_> = map
+            .occupied
+            .iter()
+            .filter(|(_, occupied)| !**occupied)
+            .map(|(position, _)| *position)
+            .collect();
+        (scaffolding.intersection(&self.visited).count(), scaffolding.len())
+    }
+}
+
+fn read_map(program: &Program) -> Map {
+    let input = Channel::new();
+    let output = Channel::new();
+
+    let mut process = Process::new("Camera", program, &input, &output);
+    let state = process.execute();
+    assert_eq!(state, State::Complete);
+
+    let result: Vec<_> = output.into();
+
+    build_map(&result)
+}
+
+// How many instructions the robot runs between looking at its video feed.
+const TIME_SLICE: usize = 10_000;
+
+// Run the robot, passing everything it outputs to `watch` as it goes, and
+// returning the dust it collected and a transcript of the run.
+fn run_program(
+    program: &Program,
+    robot_program: &RobotProgram,
+    video: bool,
+    mut watch: impl FnMut(&[i64]),
+) -> (i64, Transcript) {
+    let input = Channel::new();
+    let output = Channel::new();
+
+    for b in robot_program.encode() {
+        input.put(b as i64);
+    }
+
+    input.put(if video { 'y' } else { 'n' } as i64);
+    input.put('\n' as i64);
+
+    let recorder = Recorder::new();
+    let mut process = Process::new(
+        "Robot",
+        program,
+        RecordingInput::new(&input, &recorder),
+        RecordingOutput::new(&output, &recorder),
+    );
+    process.set(0, 2);
+    recorder.set(0, 2);
+
+    let mut dust = 0;
+    loop {
+        let state = process.execute_for(TIME_SLICE);
+        let values = output.drain();
+        dust = values.last().cloned().unwrap_or(dust);
+        watch(&values);
+
+        match state {
+            State::Yielded => {}
+            State::Complete => break,
+            State::Blocked => panic!("The robot wanted more input"),
+        }
+    }
+    recorder.halt();
+
+    (dust, recorder.transcript())
+}
+
+#[allow(dead_code)]
+fn display_map(map: &Map, screen: &mut impl Screen) {
+    for (position, occupied) in map.occupied.iter() {
+        screen.set_tile(
+            (*position).into(),
+            if *occupied {
+                Tile::Empty
+            } else {
+                Tile::Scaffolding
+            },
+        )
+    }
+    screen.set_tile(map.robot.position.into(), Tile::Robot(map.robot.direction));
+
+    let max_y = map.occupied.keys().map(|p| p.y).max().unwrap();
+    screen.goto([0, max_y as u16 + 1]);
+}
+
+// Show the scaffolding and the robot's route, returning the alignment
+// parameter and the dust collected.
+fn run(
+    program: &Program,
+    mut screen: impl Screen,
+    speed: Option<u64>,
+    robot_program: Option<&Path>,
+    record: Option<&Path>,
+    video: bool,
+) -> Result<(i64, i64), Error> {
+    screen.clear();
+    let map = read_map(program);
+    display_map(&map, &mut screen);
+
+    let alignment: i64 = map.intersections().map(|Position { x, y }| x * y).sum();
+    screen.print(format!("Alignment: {}", alignment));
+
+    let robot_program = if let Some(path) = robot_program {
+        let robot_program = fs::read_to_string(path)
+            .map_err(Error::from)
+            .and_then(|text| RobotProgram::parse(&text));
+
+        match robot_program {
+            Ok(robot_program) => robot_program,
+            Err(Error(message)) => return Err(format!("Invalid program: {}", message).into()),
+        }
+    } else {
+        // Stop at the first route which compresses, rather than finding
+        // every route up front.
+        let mut found = None;
+        let count = map.each_route(MAX_ROUTES, |route| {
+            found = collapse(route, 1)
+                .into_iter()
+                .next()
+                .map(|robot_program| (route.to_vec(), robot_program));
+            found.is_none()
+        });
+
+        if let Some((route, robot_program)) = found {
+            screen.print(format!("Route {}", count));
+            screen.print(format!("{}", Instructions::new(route.into_iter())));
+            robot_program
+        } else {
+            return Err(format!(
+                "None of the {} routes fit within the function limits",
+                count
+            )
+            .into());
+        }
+    };
+
+    screen.print(format!("A: {}", robot_program.functions[0]));
+    screen.print(format!("B: {}", robot_program.functions[1]));
+    screen.print(format!("C: {}", robot_program.functions[2]));
+    screen.print(format!("Calls: {}", robot_program.calls));
+
+    // Show where the robot's been on the map as it goes, putting the cursor
+    // back where the text was being written.
+    let mut ticker = speed.map(Ticker::with_rate);
+    let mut tracker = Tracker::new(&map.robot);
+    let (dust, transcript) = run_program(program, &robot_program, video, |values| {
+        let previous = tracker.robot.position;
+        let visited = tracker.watch(values);
+        if visited.is_empty() {
+            return;
+        }
+        screen.save_cursor();
+        screen.set_tile(previous.into(), Tile::Visited);
+        for position in visited {
+            screen.set_tile(position.into(), Tile::Visited);
+        }
+        screen.set_tile(tracker.robot.position.into(), Tile::Robot(tracker.robot.direction));
+        screen.restore_cursor();
+        if let Some(ref mut ticker) = ticker {
+            ticker.wait();
+        }
+    });
+    screen.print(format!("Dust collected: {}", dust));
+
+    if video {
+        let (visited, total) = tracker.coverage(&map);
+        screen.print(format!(
+            "Visited {} of {} scaffolding tiles ({:.1}%){}",
+            visited,
+            total,
+            visited as f64 * 100.0 / total as f64,
+            if visited == total { "" } else { ", some were missed" }
+        ));
+    }
+
+    if let Some(path) = record {
+        transcript.save(path)?;
+    }
+
+    Ok((alignment, dust))
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Run the main routine and functions A, B and C from a file
+    #[structopt(long, parse(from_os_str))]
+    program: Option<PathBuf>,
+
+    /// Write every value passed to and from the robot to a transcript
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+
+    /// Watch the robot's video feed as it cleans, showing where it's been and checking it visits
+    /// all of the scaffolding
+    #[structopt(long, conflicts_with_all = &["quiet", "json"])]
+    video: bool,
+
+    /// Check the robot does exactly what a transcript says, instead of running it
+    #[structopt(long, parse(from_os_str), conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let input = opts
+        .common
+        .unsupported(&[Flag::Part])
+        .and_then(|_| opts.common.input());
+    let program = match input {
+        Ok(input) => Program::parse(input).unwrap(),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(ref path) = opts.replay {
+        match Transcript::load(path).and_then(|transcript| Playback::new(transcript).replay(&program)) {
+            Ok(count) => println!("Replayed {} values", count),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let robot_program = opts.program.as_deref();
+    let record = opts.record.as_deref();
+    let speed = opts.common.speed();
+    let answers = if opts.common.answers_only() {
+        run(&program, Hidden {}, speed, robot_program, record, opts.video)
+    } else if !opts.common.debug {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        run(&program, screen, speed, robot_program, record, opts.video)
+    } else {
+        let screen = ScreenBuffer {};
+        run(&program, screen, speed, robot_program, record, opts.video)
+    };
+
+    match answers {
+        Ok((alignment, dust)) if opts.common.answers_only() => opts
+            .common
+            .print_answers(&[("alignment", alignment.into()), ("dust", dust.into())]),
+        Ok(_) => {}
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_route(route: &str) -> Vec<Movement> {
+        route
+            .split(',')
+            .flat_map(|instruction| match instruction {
+                "L" => vec![Movement::Left],
+                "R" => vec![Movement::Right],
+                num => vec![Movement::Forward; num.parse().unwrap()],
+            })
+            .collect()
+    }
+
+    fn expand(program: &RobotProgram) -> String {
+        program
+            .calls
+            .0
+            .iter()
+            .map(|call| match call {
+                Function::A => program.functions[0].to_string(),
+                Function::B => program.functions[1].to_string(),
+                Function::C => program.functions[2].to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn collapse_example() {
+        let route = parse_route("R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2");
+
+        let solutions = collapse(&route, usize::MAX);
+        assert_eq!(collapse(&route, 1).len(), 1);
+        assert!(!solutions.is_empty());
+
+        for program in solutions.iter() {
+            assert!(program.calls.0.len() <= MAX_CALLS);
+            assert!(program
+                .functions
+                .iter()
+                .all(|function| function.to_string().len() <= MAX_FUNCTION_LENGTH));
+            assert_eq!(parse_route(&expand(program)), route);
+        }
+    }
+
+    #[test]
+    fn parse_program() {
+        let program = RobotProgram::parse("A,B,C,B,A,C\nR,8,R,8\nR,4,R,4,R,8\nL,6,L,2\n").unwrap();
+        assert_eq!(expand(&program), "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2");
+
+        let error = RobotProgram::parse("A,B,C\nR,8\nR,4,X\nL,6\n").err().unwrap();
+        assert_eq!(error.0, "Line 3: unknown instruction \"X\"");
+
+        let error = RobotProgram::parse("A,B,C\nR,8,R,8,R,8,R,8,R,8,R,8\nR,4\nL,6\n").err().unwrap();
+        assert_eq!(error.0, "Line 2: 23 characters, limit is 20");
+
+        assert!(RobotProgram::parse("A,B,D\nR,8\nR,4\nL,6\n").is_err());
+        assert!(RobotProgram::parse("A,B\nR,8\nR,4\n").is_err());
+    }
+
+    #[test]
+    fn alternative_routes() {
+        let text = "#######...#####\n\
+                    #.....#...#...#\n\
+                    #.....#...#...#\n\
+                    ......#...#...#\n\
+                    ......#...###.#\n\
+                    ......#.....#.#\n\
+                    ^########...#.#\n\
+                    ......#.#...#.#\n\
+                    ......#########\n\
+                    ........#...#..\n\
+                    ....#########..\n\
+                    ....#...#......\n\
+                    ....#...#......\n\
+                    ....#...#......\n\
+                    ....#####......\n";
+        let output: Vec<i64> = text.bytes().map(|b| b as i64).collect();
+        let map = build_map(&output);
+
+        let mut routes = vec![];
+        let count = map.each_route(MAX_ROUTES, |route| {
+            routes.push(route.to_vec());
+            true
+        });
+        assert_eq!(count, routes.len());
+        assert!(routes.len() > 1);
+        assert_eq!(map.each_route(MAX_ROUTES, |_| false), 1);
+        assert_eq!(
+            Instructions::new(routes[0].iter().cloned()).to_string(),
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"
+        );
+
+        let num_scaffolding = text.chars().filter(|c| *c == '#' || *c == '^').count();
+        for route in routes.iter() {
+            let mut robot = map.robot.clone();
+            let mut visited = HashSet::new();
+            visited.insert(robot.position);
+            for movement in route.iter() {
+                robot.direction = robot.direction.turn(*movement);
+                if *movement == Movement::Forward {
+                    robot.position = robot.position.moved(robot.direction);
+                    assert!(map.has_scaffolding(robot.position));
+                    visited.insert(robot.position);
+                }
+            }
+            assert_eq!(visited.len(), num_scaffolding);
+        }
+    }
+
+    #[test]
+    fn collapse_infeasible() {
+        let route = parse_route("R,10,L,11,R,12,L,13,R,14,L,15,R,16,L,17,R,18,L,19,R,20,L,21,R,22,L,23,R,24,L,25,R,26,L,27,R,28,L,29");
+
+        assert!(collapse(&route, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn tracker() {
+        let map = build_map(&"#>##\n".bytes().map(i64::from).collect::<Vec<_>>());
+        let mut tracker = Tracker::new(&map.robot);
+
+        let feed: Vec<i64> = "Main:\nContinuous video feed?\n#>##\n\n###>\n"
+            .bytes()
+            .map(i64::from)
+            .collect();
+        // The robot hasn't moved yet in the first frame.
+        assert!(tracker.watch(&feed).is_empty());
+        assert_eq!(tracker.coverage(&map), (1, 4));
+
+        assert_eq!(
+            tracker.watch(&[i64::from(b'\n'), 1234]),
+            vec![Position { x: 2, y: 0 }, Position { x: 3, y: 0 }]
+        );
+        assert_eq!(tracker.coverage(&map), (3, 4));
+    }
+}
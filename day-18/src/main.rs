@@ -0,0 +1,1092 @@
+use rayon::prelude::*;
+use std::cmp::{Ord, Ordering, PartialOrd};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, stdout, BufRead, Write};
+use std::iter::FromIterator;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+use termion::cursor;
+use termion::raw::IntoRawMode;
+
+mod display;
+mod strategy;
+mod utils;
+
+use common::compare::compare;
+use common::{Checkpoint, CommonOpts};
+use display::{Screen, ScreenBuffer};
+use strategy::{Dijkstra, Stats, Strategy};
+use utils::Ticker;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+struct Key(char);
+
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    fn all() -> impl Iterator<Item = Direction> {
+        (0..4).map(Direction::try_from).map(Result::unwrap)
+    }
+}
+
+impl TryFrom<u32> for Direction {
+    type Error = Error;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        use Direction::*;
+        match value {
+            0 => Ok(North),
+            1 => Ok(East),
+            2 => Ok(South),
+            3 => Ok(West),
+            _ => Err(format!("Unknown direction {}", value).into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Position {
+    x: isize,
+    y: isize,
+}
+
+#[allow(dead_code)]
+impl Position {
+    fn new(x: isize, y: isize) -> Self {
+        Position { x, y }
+    }
+
+    fn origin() -> Self {
+        Position { x: 0, y: 0 }
+    }
+
+    fn moved(&self, direction: Direction) -> Position {
+        use Direction::*;
+        let mut position = *self;
+        match direction {
+            North => position.y -= 1,
+            East => position.x += 1,
+            South => position.y += 1,
+            West => position.x -= 1,
+        }
+        position
+    }
+
+    fn adjacent(self) -> impl Iterator<Item = Position> {
+        Direction::all().map(move |direction| self.moved(direction))
+    }
+
+    fn length(self) -> usize {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    fn offset(self, other: Position) -> Position {
+        Position {
+            x: other.x - self.x,
+            y: other.y - self.y,
+        }
+    }
+
+    fn distance(self, other: Position) -> usize {
+        self.offset(other).length()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Empty,
+    Wall,
+    Door(Key),
+}
+
+struct Map {
+    tiles: HashMap<Position, Tile>,
+    keys: HashMap<Position, Key>,
+    start: Vec<Position>,
+}
+
+#[allow(dead_code)]
+impl Map {
+    fn read(input: impl BufRead) -> Self {
+        let mut tiles = HashMap::new();
+        let mut keys = HashMap::new();
+        let mut start = vec![];
+
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.unwrap().chars().enumerate() {
+                let position = Position::new(x as isize, y as isize);
+
+                tiles.insert(
+                    position,
+                    if c == '#' {
+                        Tile::Wall
+                    } else if c.is_ascii_uppercase() {
+                        Tile::Door(Key(c.to_ascii_lowercase()))
+                    } else {
+                        Tile::Empty
+                    },
+                );
+
+                if c.is_ascii_lowercase() {
+                    keys.insert(position, Key(c));
+                }
+
+                if c == '@' {
+                    start.push(position);
+                }
+            }
+        }
+
+        Map { tiles, keys, start }
+    }
+
+    // Split the area around a single start into four vaults, each with its
+    // own robot, as in part 2 of the puzzle.
+    fn split_vaults(&mut self) -> Result<(), Error> {
+        if self.start.len() != 1 {
+            return Err(format!("Expected a single start, found {}", self.start.len()).into());
+        }
+
+        let centre = self.start[0];
+        self.tiles.insert(centre, Tile::Wall);
+        for position in centre.adjacent() {
+            self.tiles.insert(position, Tile::Wall);
+        }
+
+        self.start = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+            .iter()
+            .map(|(x, y)| Position::new(centre.x + x, centre.y + y))
+            .collect();
+
+        Ok(())
+    }
+
+    fn is_open(&self, position: Position) -> bool {
+        self.tiles.get(&position).cloned().unwrap_or(Tile::Wall) != Tile::Wall
+    }
+}
+
+// A set of keys, one bit per letter.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+struct KeySet(u32);
+
+impl KeySet {
+    fn with(self, key: Key) -> Self {
+        KeySet(self.0 | 1 << (key.0 as u32 - 'a' as u32))
+    }
+
+    fn union(self, other: KeySet) -> Self {
+        KeySet(self.0 | other.0)
+    }
+
+    fn is_subset(self, other: KeySet) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    fn contains(self, key: Key) -> bool {
+        KeySet::default().with(key).is_subset(self)
+    }
+}
+
+impl fmt::Display for KeySet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in 'a'..='z' {
+            if self.contains(Key(c)) {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<Key> for KeySet {
+    fn from_iter<T: IntoIterator<Item = Key>>(iter: T) -> Self {
+        iter.into_iter().fold(KeySet::default(), KeySet::with)
+    }
+}
+
+// The shortest path from one point of interest to a key, along with the
+// doors it passes through and the keys it picks up on the way.
+struct Edge {
+    to: usize,
+    distance: usize,
+    doors: KeySet,
+    keys: KeySet,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct State {
+    robots: Vec<usize>,
+    keys: KeySet,
+}
+
+#[derive(PartialEq, Eq)]
+struct Entry {
+    distance: usize,
+    state: State,
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.cmp(&other.distance).reverse()
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// How far the search has got: the shortest distance found so far to each
+// state, and the states waiting to be expanded.
+struct Search {
+    distances: HashMap<State, usize>,
+    heap: BinaryHeap<Entry>,
+}
+
+impl Search {
+    fn new(graph: &Graph) -> Self {
+        let start = State {
+            robots: (0..graph.num_robots).collect(),
+            keys: KeySet::default(),
+        };
+
+        let mut distances = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        distances.insert(start.clone(), 0);
+        heap.push(Entry {
+            distance: 0,
+            state: start,
+        });
+
+        Search { distances, heap }
+    }
+
+    // A line giving the number of robots and points in the graph, so that
+    // the search isn't picked up again on a different map, then a line for
+    // each state with the distance to it, the point each robot is at and the
+    // bits of the keys collected.  Those waiting to be expanded are "queued"
+    // and the rest "known".
+    fn write(&self, graph: &Graph, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "graph {} {}", graph.num_robots, graph.points.len())?;
+
+        let mut write_state = |kind, distance, state: &State| {
+            let robots: Vec<String> = state.robots.iter().map(usize::to_string).collect();
+            writeln!(output, "{} {} {} {}", kind, distance, robots.join(","), state.keys.0)
+        };
+        for (state, distance) in self.distances.iter() {
+            write_state("known", *distance, state)?;
+        }
+        for entry in self.heap.iter() {
+            if entry.distance == self.distances[&entry.state] {
+                write_state("queued", entry.distance, &entry.state)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse(text: &str, graph: &Graph) -> Result<Self, Error> {
+        let mut lines = text.lines();
+        let header = format!("graph {} {}", graph.num_robots, graph.points.len());
+        if lines.next() != Some(header.as_str()) {
+            return Err("The checkpoint is for a different map".into());
+        }
+
+        let mut search = Search {
+            distances: HashMap::new(),
+            heap: BinaryHeap::new(),
+        };
+        for line in lines {
+            let error = || format!("Bad line in checkpoint: {:?}", line);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() != 4 {
+                return Err(error().into());
+            }
+
+            let distance: usize = words[1].parse().map_err(|_| error())?;
+            let robots = words[2]
+                .split(',')
+                .map(|point| point.parse().ok().filter(|&point| point < graph.points.len()))
+                .collect::<Option<Vec<usize>>>()
+                .filter(|robots| robots.len() == graph.num_robots)
+                .ok_or_else(error)?;
+            let keys = words[3]
+                .parse()
+                .ok()
+                .map(KeySet)
+                .filter(|keys| keys.is_subset(graph.all_keys))
+                .ok_or_else(error)?;
+            let state = State { robots, keys };
+
+            match words[0] {
+                "known" => {
+                    search.distances.insert(state, distance);
+                }
+                "queued" => search.heap.push(Entry { distance, state }),
+                _ => return Err(error().into()),
+            }
+        }
+
+        if search.heap.iter().any(|entry| !search.distances.contains_key(&entry.state)) {
+            return Err("The checkpoint queues states it doesn't know the distance to".into());
+        }
+        Ok(search)
+    }
+}
+
+// The map reduced to the distances between the start positions and keys.
+struct Graph {
+    points: Vec<Position>,
+    keys: Vec<Key>,
+    num_robots: usize,
+    edges: Vec<Vec<Edge>>,
+    all_keys: KeySet,
+}
+
+impl Graph {
+    fn new(map: &Map) -> Self {
+        let mut keys: Vec<(Position, Key)> = map.keys.iter().map(|(p, k)| (*p, *k)).collect();
+        keys.sort_by_key(|(_, key)| *key);
+
+        let points: Vec<Position> = map
+            .start
+            .iter()
+            .cloned()
+            .chain(keys.iter().map(|(position, _)| *position))
+            .collect();
+        let indices: HashMap<Position, usize> = points
+            .iter()
+            .enumerate()
+            .map(|(index, position)| (*position, index))
+            .collect();
+
+        let edges = points
+            .par_iter()
+            .map(|&from| {
+                let mut edges = vec![];
+                let mut visited = HashSet::new();
+                let mut queue = VecDeque::new();
+                visited.insert(from);
+                queue.push_back((from, 0, KeySet::default(), KeySet::default()));
+
+                while let Some((position, distance, mut doors, mut keys)) = queue.pop_front() {
+                    if let Some(Tile::Door(key)) = map.tiles.get(&position) {
+                        doors = doors.with(*key);
+                    }
+
+                    if let Some(key) = map.keys.get(&position) {
+                        keys = keys.with(*key);
+                        if position != from {
+                            edges.push(Edge {
+                                to: indices[&position],
+                                distance,
+                                doors,
+                                keys,
+                            });
+                        }
+                    }
+
+                    for next in position.adjacent() {
+                        if map.is_open(next) && visited.insert(next) {
+                            queue.push_back((next, distance + 1, doors, keys));
+                        }
+                    }
+                }
+
+                edges
+            })
+            .collect();
+
+        Graph {
+            points,
+            keys: keys.iter().map(|(_, key)| *key).collect(),
+            num_robots: map.start.len(),
+            edges,
+            all_keys: keys.iter().map(|(_, key)| *key).collect(),
+        }
+    }
+
+    fn name(&self, point: usize) -> String {
+        if point >= self.num_robots {
+            self.keys[point - self.num_robots].0.to_string()
+        } else if self.num_robots == 1 {
+            "@".to_string()
+        } else {
+            format!("@{}", point)
+        }
+    }
+
+    // Each path is the same in both directions, so it's only written once,
+    // from the point that comes first.
+    fn write_dot(&self, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "graph keys {{")?;
+
+        for point in 0..self.points.len() {
+            if point < self.num_robots {
+                writeln!(
+                    output,
+                    "    \"{}\" [shape=box, style=filled, fillcolor=orange];",
+                    self.name(point)
+                )?;
+            } else {
+                writeln!(output, "    \"{}\";", self.name(point))?;
+            }
+        }
+
+        for (from, edges) in self.edges.iter().enumerate() {
+            for edge in edges.iter().filter(|edge| edge.to > from) {
+                if edge.doors == KeySet::default() {
+                    writeln!(
+                        output,
+                        "    \"{}\" -- \"{}\" [label=\"{}\"];",
+                        self.name(from),
+                        self.name(edge.to),
+                        edge.distance
+                    )?;
+                } else {
+                    writeln!(
+                        output,
+                        "    \"{}\" -- \"{}\" [label=\"{}\\n{}\", style=dashed];",
+                        self.name(from),
+                        self.name(edge.to),
+                        edge.distance,
+                        door_names(edge.doors)
+                    )?;
+                }
+            }
+        }
+
+        writeln!(output, "}}")
+    }
+
+    // Every state reachable by moving one robot to a key it hasn't collected
+    // yet, along with the distance moved.
+    fn successors<'a>(&'a self, state: &'a State) -> impl Iterator<Item = (State, usize)> + 'a {
+        state
+            .robots
+            .iter()
+            .enumerate()
+            .flat_map(move |(robot, &from)| {
+                self.edges[from]
+                    .iter()
+                    .filter(move |edge| {
+                        edge.doors.is_subset(state.keys) && !edge.keys.is_subset(state.keys)
+                    })
+                    .map(move |edge| {
+                        let mut robots = state.robots.clone();
+                        robots[robot] = edge.to;
+                        let next = State {
+                            robots,
+                            keys: state.keys.union(edge.keys),
+                        };
+                        (next, edge.distance)
+                    })
+            })
+    }
+
+    // Dijkstra over the positions of the robots and the keys collected so
+    // far.  All states at the current shortest distance are expanded
+    // together in parallel, then merged into the table of distances.
+    //
+    // `progress` is called with one of the states from each layer, along
+    // with its distance and the number of states left on the frontier.
+    fn shortest_path(&self, progress: impl FnMut(&State, usize, usize)) -> Option<usize> {
+        self.search(Search::new(self), None, progress)
+    }
+
+    // Plain Dijkstra, expanding one state at a time.  This is how the search
+    // worked before the layers were expanded in parallel, and is kept to
+    // check that against.
+    fn shortest_path_sequential(&self) -> Option<usize> {
+        Dijkstra.shortest_path(self, &mut Stats::default())
+    }
+
+    // Carry on with a search, saving it to the checkpoint between layers.
+    fn search(
+        &self,
+        mut search: Search,
+        mut checkpoint: Option<&mut Checkpoint>,
+        mut progress: impl FnMut(&State, usize, usize),
+    ) -> Option<usize> {
+        loop {
+            if let Some(checkpoint) = checkpoint.as_mut() {
+                checkpoint.update(|output| search.write(self, output));
+            }
+
+            let Entry { distance, state } = search.heap.pop()?;
+            let mut batch = vec![state];
+            while search
+                .heap
+                .peek()
+                .map(|entry| entry.distance == distance)
+                .unwrap_or(false)
+            {
+                batch.push(search.heap.pop().unwrap().state);
+            }
+            batch.retain(|state| distance <= search.distances[state]);
+
+            if let Some(state) = batch.first() {
+                progress(state, distance, search.heap.len());
+            }
+
+            if batch.iter().any(|state| state.keys == self.all_keys) {
+                return Some(distance);
+            }
+
+            let successors: Vec<(State, usize)> = batch
+                .par_iter()
+                .flat_map_iter(|state| self.successors(state))
+                .collect();
+
+            for (next, moved) in successors {
+                let next_distance = distance + moved;
+
+                if search
+                    .distances
+                    .get(&next)
+                    .map(|&known| next_distance < known)
+                    .unwrap_or(true)
+                {
+                    search.distances.insert(next.clone(), next_distance);
+                    search.heap.push(Entry {
+                        distance: next_distance,
+                        state: next,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn get_all_keys(map: &Map, quiet: bool) -> Option<usize> {
+    let graph = Graph::new(map);
+    if !quiet {
+        println!("Computed nodes");
+    }
+    graph.shortest_path(|_, _, _| {})
+}
+
+// Like `get_all_keys`, but saving the search as it goes, and picking up from
+// the last save if `resume` is set.
+fn get_all_keys_checkpointed(
+    map: &Map,
+    checkpoint: &mut Checkpoint,
+    resume: bool,
+    quiet: bool,
+) -> Result<Option<usize>, Error> {
+    let graph = Graph::new(map);
+    if !quiet {
+        println!("Computed nodes");
+    }
+
+    let search = if resume {
+        Search::parse(&checkpoint.load()?, &graph)?
+    } else {
+        Search::new(&graph)
+    };
+    Ok(graph.search(search, Some(checkpoint), |_, _, _| {}))
+}
+
+// Search with each of the strategies in turn, saying how much work each one
+// did, and fail if they don't all agree.
+fn get_all_keys_with(map: &Map, names: &[String], quiet: bool) -> Result<Option<usize>, Error> {
+    let graph = Graph::new(map);
+    let mut answer = None;
+
+    for (index, name) in names.iter().enumerate() {
+        let strategy = strategy::by_name(name).ok_or(format!("Unknown strategy {}", name))?;
+        let mut stats = Stats::default();
+        let started = Instant::now();
+        let distance = strategy.shortest_path(&graph, &mut stats);
+
+        if !quiet {
+            let found = match distance {
+                Some(distance) => format!("distance {}", distance),
+                None => "no solution".to_string(),
+            };
+            println!(
+                "{}: {}, {} states expanded, {} generated, in {:.3}s",
+                name,
+                found,
+                stats.expanded,
+                stats.generated,
+                started.elapsed().as_secs_f64()
+            );
+        }
+
+        if index > 0 && distance != answer {
+            return Err(Error(format!("{} disagrees with {}", name, names[0])));
+        }
+        answer = distance;
+    }
+
+    Ok(answer)
+}
+
+fn door_names(doors: KeySet) -> String {
+    doors.to_string().to_ascii_uppercase()
+}
+
+// Explain why the keys can't all be collected.
+fn diagnose(map: &Map) -> Vec<String> {
+    let mut problems = vec![];
+
+    if map.start.is_empty() {
+        problems.push("No start position".to_string());
+    }
+
+    let all_keys: KeySet = map.keys.values().cloned().collect();
+    let mut missing: Vec<Key> = map
+        .tiles
+        .values()
+        .filter_map(|tile| match tile {
+            Tile::Door(key) if !all_keys.contains(*key) => Some(*key),
+            _ => None,
+        })
+        .collect();
+    missing.sort();
+    for key in missing {
+        problems.push(format!("Door {} has no key", key.0.to_ascii_uppercase()));
+    }
+
+    // Regions of open tiles that no robot can get to, whatever doors are
+    // open.
+    let mut seen: HashSet<Position> = HashSet::new();
+    let mut regions = vec![];
+    let mut open: Vec<Position> = map
+        .tiles
+        .keys()
+        .cloned()
+        .filter(|position| map.is_open(*position))
+        .collect();
+    open.sort_by_key(|position| (position.y, position.x));
+    for &origin in open.iter() {
+        if !seen.insert(origin) {
+            continue;
+        }
+
+        let mut region = vec![origin];
+        let mut queue = VecDeque::new();
+        queue.push_back(origin);
+        while let Some(position) = queue.pop_front() {
+            for next in position.adjacent() {
+                if map.is_open(next) && seen.insert(next) {
+                    region.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if !region.iter().any(|position| map.start.contains(position)) {
+            regions.push((origin, region));
+        }
+    }
+
+    for (origin, region) in regions {
+        let keys: KeySet = region
+            .iter()
+            .filter_map(|position| map.keys.get(position))
+            .cloned()
+            .collect();
+        if keys == KeySet::default() {
+            problems.push(format!(
+                "Region at ({}, {}) isn't connected to any start",
+                origin.x, origin.y
+            ));
+        } else {
+            problems.push(format!(
+                "Region at ({}, {}) isn't connected to any start, keys {} can't be reached",
+                origin.x, origin.y, keys
+            ));
+        }
+    }
+
+    // Collect every key that can be reached until no more doors open, then
+    // report whatever is still locked away.
+    let graph = Graph::new(map);
+    let mut reachable: Vec<(Key, KeySet)> = (0..graph.num_robots)
+        .flat_map(|start| graph.edges[start].iter())
+        .map(|edge| (map.keys[&graph.points[edge.to]], edge.doors))
+        .collect();
+    reachable.sort_by_key(|(key, _)| *key);
+
+    let mut collected = KeySet::default();
+    loop {
+        let next = reachable
+            .iter()
+            .filter(|(_, doors)| doors.is_subset(collected))
+            .fold(collected, |collected, (key, _)| collected.with(*key));
+        if next == collected {
+            break;
+        }
+        collected = next;
+    }
+
+    for (key, doors) in reachable {
+        if collected.contains(key) {
+            continue;
+        }
+
+        if doors.contains(key) {
+            problems.push(format!("Key {} is behind its own door", key.0));
+        } else {
+            problems.push(format!(
+                "Key {} is locked behind doors {}",
+                key.0,
+                door_names(KeySet(doors.0 & !collected.0))
+            ));
+        }
+    }
+
+    problems
+}
+
+fn draw_tile(map: &Map, screen: &mut impl Screen, position: Position, keys: KeySet) {
+    let symbol = match map.tiles.get(&position) {
+        Some(Tile::Wall) => '#',
+        Some(Tile::Door(key)) if !keys.contains(*key) => key.0.to_ascii_uppercase(),
+        _ => match map.keys.get(&position) {
+            Some(key) if !keys.contains(*key) => key.0,
+            _ => '.',
+        },
+    };
+    screen.set_tile([position.x as u16, position.y as u16], symbol);
+}
+
+// Search for the shortest route while showing the robots and collected keys
+// from the latest layer of the search.
+fn show_search(map: &Map, mut screen: impl Screen, speed: Option<u64>) -> Option<usize> {
+    let graph = Graph::new(map);
+    let status_row = map
+        .tiles
+        .keys()
+        .map(|position| position.y)
+        .max()
+        .unwrap_or(0) as u16
+        + 1;
+    let mut ticker = speed.map(Ticker::with_rate);
+
+    screen.clear();
+    for position in map.tiles.keys() {
+        draw_tile(map, &mut screen, *position, KeySet::default());
+    }
+
+    let mut robots = vec![];
+    let distance = graph.shortest_path(|state, distance, frontier| {
+        let interesting = map
+            .keys
+            .keys()
+            .chain(map.tiles.iter().filter_map(|(position, tile)| match tile {
+                Tile::Door(_) => Some(position),
+                _ => None,
+            }));
+        for position in robots.iter().chain(interesting) {
+            draw_tile(map, &mut screen, *position, state.keys);
+        }
+
+        robots = state
+            .robots
+            .iter()
+            .map(|&point| graph.points[point])
+            .collect();
+        for position in robots.iter() {
+            screen.set_tile([position.x as u16, position.y as u16], '@');
+        }
+
+        screen.goto([0, status_row]);
+        screen.print(format!(
+            "Distance: {:<8} Frontier: {:<10}",
+            distance, frontier
+        ));
+        screen.print(format!("Keys: {:<26}", state.keys));
+
+        if let Some(ticker) = ticker.as_mut() {
+            ticker.wait();
+        }
+    });
+
+    screen.goto([0, status_row + 2]);
+    match distance {
+        Some(distance) => screen.print(format!("Distance: {}", distance)),
+        None => {
+            screen.print("No solution possible".to_string());
+            for problem in diagnose(map) {
+                screen.print(problem);
+            }
+        }
+    }
+
+    distance
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Print the graph of keys in Graphviz DOT format
+    #[structopt(long, conflicts_with_all = &["quiet", "json"])]
+    dot: bool,
+
+    /// Show the search as it progresses
+    #[structopt(short, long, conflicts_with_all = &["quiet", "json"])]
+    visualize: bool,
+
+    /// Save the search to this file every so often
+    #[structopt(long, parse(from_os_str), conflicts_with_all = &["dot", "visualize"])]
+    checkpoint: Option<PathBuf>,
+
+    /// Seconds between saving the search
+    #[structopt(long, default_value = "60")]
+    checkpoint_interval: u64,
+
+    /// Carry on from the search saved in the checkpoint instead of starting again
+    #[structopt(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Search both one state at a time and a layer at a time, and compare them
+    #[structopt(
+        long,
+        conflicts_with_all = &["dot", "visualize", "checkpoint", "quiet", "json"]
+    )]
+    compare: bool,
+
+    /// Search with these strategies, as a comma separated list, reporting how many states each
+    /// one expands
+    #[structopt(
+        long,
+        possible_values = strategy::NAMES,
+        use_delimiter = true,
+        conflicts_with_all = &["dot", "visualize", "checkpoint", "compare"]
+    )]
+    strategy: Vec<String>,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let mut map = match opts.common.input() {
+        Ok(input) => Map::read(input),
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+
+    if opts.common.part(1) == 2 && map.start.len() == 1 {
+        if let Err(Error(message)) = map.split_vaults() {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    }
+
+    if opts.dot {
+        Graph::new(&map).write_dot(&mut stdout().lock()).unwrap();
+    } else if opts.compare {
+        let graph = Graph::new(&map);
+        let sequential = ("sequential", || graph.shortest_path_sequential());
+        let layered = ("layered", || graph.shortest_path(|_, _, _| {}));
+        if !compare(sequential, layered) {
+            process::exit(1);
+        }
+    } else if opts.visualize && opts.common.debug {
+        show_search(&map, ScreenBuffer {}, opts.common.speed());
+    } else if opts.visualize {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        show_search(&map, screen, opts.common.speed());
+    } else {
+        let distance = match opts.checkpoint {
+            None if !opts.strategy.is_empty() => {
+                get_all_keys_with(&map, &opts.strategy, opts.common.answers_only())
+            }
+            Some(ref path) => {
+                let interval = Duration::from_secs(opts.checkpoint_interval);
+                let mut checkpoint = Checkpoint::new(path, interval);
+                get_all_keys_checkpointed(&map, &mut checkpoint, opts.resume, opts.common.answers_only())
+            }
+            None => Ok(get_all_keys(&map, opts.common.answers_only())),
+        };
+
+        match distance {
+            Ok(Some(distance)) if opts.common.answers_only() => {
+                opts.common.print_answers(&[("distance", distance.into())])
+            }
+            Ok(Some(distance)) => println!("Distance: {}", distance),
+            Ok(None) if opts.common.answers_only() => {
+                eprintln!("No solution possible");
+                process::exit(1);
+            }
+            Ok(None) => {
+                println!("No solution possible");
+                for problem in diagnose(&map) {
+                    println!("{}", problem);
+                }
+            }
+            Err(Error(message)) => {
+                eprintln!("{}", message);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn shortest(map: &str) -> Option<usize> {
+        let map = Map::read(map.as_bytes());
+        let distance = get_all_keys(&map, true);
+        assert_eq!(Graph::new(&map).shortest_path_sequential(), distance);
+        distance
+    }
+
+    #[test]
+    fn single_robot() {
+        let map = "\
+########################
+#f.D.E.e.C.b.A.@.a.B.c.#
+######################.#
+#d.....................#
+########################";
+        assert_eq!(shortest(map), Some(86));
+
+        let map = "\
+#################
+#i.G..c...e..H.p#
+########.########
+#j.A..b...f..D.o#
+########@########
+#k.E..a...g..B.n#
+########.########
+#l.F..d...h..C.m#
+#################";
+        assert_eq!(shortest(map), Some(136));
+    }
+
+    #[test]
+    fn checkpoint() {
+        let map = "\
+########################
+#f.D.E.e.C.b.A.@.a.B.c.#
+######################.#
+#d.....................#
+########################";
+        let graph = Graph::new(&Map::read(map.as_bytes()));
+        let path = std::env::temp_dir().join(format!("advent-18-{}.checkpoint", process::id()));
+
+        // Saving after every layer leaves the one before the last.
+        let mut checkpoint = Checkpoint::new(&path, Duration::from_secs(0));
+        let search = Search::new(&graph);
+        assert_eq!(graph.search(search, Some(&mut checkpoint), |_, _, _| {}), Some(86));
+
+        let text = checkpoint.load().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut first_distance = None;
+        let search = Search::parse(&text, &graph).unwrap();
+        let distance = graph.search(search, None, |_, distance, _| {
+            first_distance.get_or_insert(distance);
+        });
+        assert_eq!(distance, Some(86));
+        assert!(first_distance.unwrap() > 0);
+
+        let other = Graph::new(&Map::read("#####\n#@.a#\n#####".as_bytes()));
+        assert!(Search::parse(&text, &other).is_err());
+        assert!(Search::parse("graph 1 7\nknown 0 9 0\n", &graph).is_err());
+    }
+
+    #[test]
+    fn multiple_robots() {
+        let map = "\
+#############
+#g#f.D#..h#l#
+#F###e#E###.#
+#dCba@#@BcIJ#
+#############
+#nK.L@#@G...#
+#M###N#H###.#
+#o#m..#i#jk.#
+#############";
+        assert_eq!(shortest(map), Some(72));
+    }
+
+    #[test]
+    fn unreachable_key() {
+        let map = "\
+#######
+#@.A.a#
+#######";
+        assert_eq!(shortest(map), None);
+    }
+
+    #[test]
+    fn split_vaults() {
+        let mut map = Map::read(
+            "\
+#######
+#a.#Cd#
+##...##
+##.@.##
+##...##
+#cB#Ab#
+#######"
+                .as_bytes(),
+        );
+        map.split_vaults().unwrap();
+        assert_eq!(map.start.len(), 4);
+        assert_eq!(get_all_keys(&map, true), Some(8));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let map = "\
+###########
+#@.A.a.B.b#
+#####.#####
+#c.D..#e.f#
+###########";
+        assert_eq!(
+            diagnose(&Map::read(map.as_bytes())),
+            vec![
+                "Door D has no key",
+                "Region at (7, 3) isn't connected to any start, keys ef can't be reached",
+                "Key a is behind its own door",
+                "Key b is behind its own door",
+                "Key c is locked behind doors AD",
+            ]
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let map = Map::read("#########\n#b.A.@.a#\n#########".as_bytes());
+
+        let mut output = Vec::new();
+        Graph::new(&map).write_dot(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("graph keys {"));
+        assert!(output.contains("\"@\" [shape=box, style=filled, fillcolor=orange];"));
+        assert!(output.contains("\"@\" -- \"a\" [label=\"2\"];"));
+        assert!(output.contains("\"@\" -- \"b\" [label=\"4\\nA\", style=dashed];"));
+        assert!(output.contains("\"a\" -- \"b\" [label=\"6\\nA\", style=dashed];"));
+    }
+}
@@ -0,0 +1,345 @@
+// Ways of searching the graph for the shortest route to collect every key,
+// which can be swapped for one another to compare how much work they do.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::{Graph, KeySet, State};
+
+// How much work a search did.
+#[derive(Debug, Default)]
+pub struct Stats {
+    // States whose successors were looked at.
+    pub expanded: usize,
+    // Successors looked at, including any already seen.
+    pub generated: usize,
+}
+
+pub trait Strategy {
+    // The shortest distance to collect every key, or None if they can't all
+    // be collected.
+    fn shortest_path(&self, graph: &Graph, stats: &mut Stats) -> Option<usize>;
+}
+
+pub const NAMES: &[&str] = &["dijkstra", "astar", "idastar"];
+
+pub fn by_name(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "dijkstra" => Some(Box::new(Dijkstra)),
+        "astar" => Some(Box::new(AStar)),
+        "idastar" => Some(Box::new(IdaStar)),
+        _ => None,
+    }
+}
+
+fn start(graph: &Graph) -> State {
+    State {
+        robots: (0..graph.num_robots).collect(),
+        keys: KeySet::default(),
+    }
+}
+
+// The distances between every pair of points, or None where there's no path
+// between them even with every door open.
+struct SpanningTree {
+    distances: Vec<Vec<Option<usize>>>,
+}
+
+impl SpanningTree {
+    fn new(graph: &Graph) -> Self {
+        let mut distances = vec![vec![None; graph.points.len()]; graph.points.len()];
+        for (from, edges) in graph.edges.iter().enumerate() {
+            for edge in edges {
+                distances[from][edge.to] = Some(edge.distance);
+            }
+        }
+        SpanningTree { distances }
+    }
+
+    // A lower bound on how much further the robots have to go: the weight of
+    // the minimum spanning tree joining each key left to one of the robots,
+    // counting the robots as a single point.  Any way of collecting the keys
+    // covers such a tree.  None if there's a key no robot can ever get to.
+    fn estimate(&self, graph: &Graph, state: &State) -> Option<usize> {
+        let mut remaining: Vec<usize> = (graph.num_robots..graph.points.len())
+            .filter(|&point| !state.keys.contains(graph.keys[point - graph.num_robots]))
+            .collect();
+
+        // How far each key not in the tree yet is from the tree.
+        let mut nearest: Vec<Option<usize>> = remaining
+            .iter()
+            .map(|&key| {
+                state
+                    .robots
+                    .iter()
+                    .filter_map(|&robot| self.distances[robot][key])
+                    .min()
+            })
+            .collect();
+
+        let mut total = 0;
+        while !remaining.is_empty() {
+            let (index, distance) = nearest
+                .iter()
+                .enumerate()
+                .filter_map(|(index, distance)| distance.map(|distance| (index, distance)))
+                .min_by_key(|(_, distance)| *distance)?;
+            total += distance;
+
+            let added = remaining.swap_remove(index);
+            nearest.swap_remove(index);
+            for (&key, nearest) in remaining.iter().zip(nearest.iter_mut()) {
+                if let Some(distance) = self.distances[added][key] {
+                    *nearest = Some(nearest.map_or(distance, |known| known.min(distance)));
+                }
+            }
+        }
+
+        Some(total)
+    }
+}
+
+// A state waiting to be expanded, ordered so the lowest estimate of the total
+// distance comes out of the heap first.
+#[derive(PartialEq, Eq)]
+struct Queued {
+    estimate: usize,
+    distance: usize,
+    state: State,
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.estimate.cmp(&other.estimate).reverse()
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Expand states in order of the distance to them plus the heuristic's
+// estimate of the distance left, skipping any the heuristic says are dead
+// ends.  The heuristic must never overestimate, or the route found may not be
+// the shortest.
+fn best_first(
+    graph: &Graph,
+    stats: &mut Stats,
+    heuristic: impl Fn(&State) -> Option<usize>,
+) -> Option<usize> {
+    let start = start(graph);
+    let mut distances = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    distances.insert(start.clone(), 0);
+    heap.push(Queued {
+        estimate: heuristic(&start)?,
+        distance: 0,
+        state: start,
+    });
+
+    while let Some(Queued { distance, state, .. }) = heap.pop() {
+        if distance > distances[&state] {
+            continue;
+        }
+
+        if state.keys == graph.all_keys {
+            return Some(distance);
+        }
+
+        stats.expanded += 1;
+        for (next, moved) in graph.successors(&state) {
+            stats.generated += 1;
+            let next_distance = distance + moved;
+            if distances.get(&next).is_some_and(|&known| next_distance >= known) {
+                continue;
+            }
+
+            if let Some(estimate) = heuristic(&next) {
+                distances.insert(next.clone(), next_distance);
+                heap.push(Queued {
+                    estimate: next_distance + estimate,
+                    distance: next_distance,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+pub struct Dijkstra;
+
+impl Strategy for Dijkstra {
+    fn shortest_path(&self, graph: &Graph, stats: &mut Stats) -> Option<usize> {
+        best_first(graph, stats, |_| Some(0))
+    }
+}
+
+pub struct AStar;
+
+impl Strategy for AStar {
+    fn shortest_path(&self, graph: &Graph, stats: &mut Stats) -> Option<usize> {
+        let tree = SpanningTree::new(graph);
+        best_first(graph, stats, |state| tree.estimate(graph, state))
+    }
+}
+
+// What a pass of IDA* found below a state: a route collecting every key
+// within the bound, or the smallest estimate over the bound to try next, if
+// there's anywhere left to go.
+enum Probe {
+    Found(usize),
+    Exceeded(Option<usize>),
+}
+
+// Depth first search, cut off where the distance plus the estimate of the
+// distance left goes over a bound, which is raised to the smallest estimate
+// that went over it until a route is found.
+pub struct IdaStar;
+
+impl Strategy for IdaStar {
+    fn shortest_path(&self, graph: &Graph, stats: &mut Stats) -> Option<usize> {
+        let tree = SpanningTree::new(graph);
+        let start = start(graph);
+        let mut bound = tree.estimate(graph, &start)?;
+        loop {
+            let mut pass = Pass {
+                graph,
+                tree: &tree,
+                bound,
+                seen: HashMap::new(),
+                stats,
+            };
+            match pass.probe(&start, 0) {
+                Probe::Found(distance) => return Some(distance),
+                Probe::Exceeded(Some(next_bound)) => bound = next_bound,
+                Probe::Exceeded(None) => return None,
+            }
+        }
+    }
+}
+
+// One pass of IDA* with a fixed bound.  States seen before in the same pass at
+// no greater distance are skipped, as everything below them has been searched
+// already.
+struct Pass<'a> {
+    graph: &'a Graph,
+    tree: &'a SpanningTree,
+    bound: usize,
+    seen: HashMap<State, usize>,
+    stats: &'a mut Stats,
+}
+
+impl<'a> Pass<'a> {
+    fn probe(&mut self, state: &State, distance: usize) -> Probe {
+        let estimate = match self.tree.estimate(self.graph, state) {
+            Some(estimate) => distance + estimate,
+            None => return Probe::Exceeded(None),
+        };
+        if estimate > self.bound {
+            return Probe::Exceeded(Some(estimate));
+        }
+        if state.keys == self.graph.all_keys {
+            return Probe::Found(distance);
+        }
+        if self.seen.get(state).is_some_and(|&known| known <= distance) {
+            return Probe::Exceeded(None);
+        }
+        self.seen.insert(state.clone(), distance);
+
+        self.stats.expanded += 1;
+        let mut next_bound: Option<usize> = None;
+        for (next, moved) in self.graph.successors(state) {
+            self.stats.generated += 1;
+            match self.probe(&next, distance + moved) {
+                Probe::Found(distance) => return Probe::Found(distance),
+                Probe::Exceeded(Some(estimate)) => {
+                    next_bound = Some(next_bound.map_or(estimate, |known| known.min(estimate)))
+                }
+                Probe::Exceeded(None) => {}
+            }
+        }
+        Probe::Exceeded(next_bound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Map;
+
+    const MAPS: &[(&str, Option<usize>)] = &[
+        (
+            "\
+########################
+#f.D.E.e.C.b.A.@.a.B.c.#
+######################.#
+#d.....................#
+########################",
+            Some(86),
+        ),
+        (
+            "\
+########################
+#@..............ac.GI.b#
+###d#e#f################
+###A#B#C################
+###g#h#i################
+########################",
+            Some(81),
+        ),
+        (
+            "\
+#############
+#g#f.D#..h#l#
+#F###e#E###.#
+#dCba@#@BcIJ#
+#############
+#nK.L@#@G...#
+#M###N#H###.#
+#o#m..#i#jk.#
+#############",
+            Some(72),
+        ),
+        ("#######\n#@.A.a#\n#######", None),
+        ("#######\n#@.#.a#\n#######", None),
+    ];
+
+    #[test]
+    fn strategies() {
+        for (map, expected) in MAPS {
+            let graph = Graph::new(&Map::read(map.as_bytes()));
+            let mut expanded = vec![];
+            for name in NAMES {
+                let mut stats = Stats::default();
+                let distance = by_name(name).unwrap().shortest_path(&graph, &mut stats);
+                assert_eq!(distance, *expected, "{} on\n{}", name, map);
+                expanded.push(stats.expanded);
+            }
+
+            // The heuristic only ever saves work.
+            if expected.is_some() {
+                assert!(expanded[1] <= expanded[0], "{:?} on\n{}", expanded, map);
+            }
+        }
+        assert!(by_name("bfs").is_none());
+    }
+
+    #[test]
+    fn estimate() {
+        let graph = Graph::new(&Map::read(MAPS[0].0.as_bytes()));
+        let tree = SpanningTree::new(&graph);
+        let start = start(&graph);
+        let estimate = tree.estimate(&graph, &start).unwrap();
+        assert!(estimate > 0 && estimate <= 86);
+
+        let done = State {
+            robots: start.robots.clone(),
+            keys: graph.all_keys,
+        };
+        assert_eq!(tree.estimate(&graph, &done), Some(0));
+    }
+}
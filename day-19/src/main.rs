@@ -0,0 +1,481 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use structopt::StructOpt;
+
+mod display;
+mod utils;
+
+use common::{Checkpoint, CommonOpts, Flag};
+use intcode::process::{Channel, Input, Output, Process, State};
+use intcode::program::Program;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+// Space left around the ship when exporting the map.
+const MARGIN: usize = 2;
+
+trait Detector {
+    fn contains(&self, x: usize, y: usize) -> bool;
+}
+
+// The drone program, paused just before it reads the position to probe so
+// that each probe only runs the remainder of the program.
+struct Beam {
+    probe: Process<(), ()>,
+}
+
+impl Beam {
+    fn new(program: &Program) -> Self {
+        let input = Channel::new();
+        let output = Channel::new();
+
+        let mut process = Process::new("Probe", program, &input, &output);
+        assert_eq!(process.execute(), State::Blocked);
+
+        Beam {
+            probe: process.fork((), ()),
+        }
+    }
+}
+
+impl Detector for Beam {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        let input = Channel::new();
+        let output = Channel::new();
+
+        let mut process = self.probe.fork(&input, &output);
+
+        input.put(x as i64);
+        input.put(y as i64);
+
+        let state = process.execute();
+        assert_eq!(state, State::Complete);
+
+        output.get().unwrap() == 1
+    }
+}
+
+// The points pulled in each row of the beam, starting from y = 0.  Both
+// edges of the beam only ever move right, so each row picks up the search
+// where the last one left off.
+struct Rows<'a, D> {
+    detector: &'a D,
+    y: usize,
+    edges: Range<usize>,
+}
+
+impl<'a, D: Detector> Rows<'a, D> {
+    fn new(detector: &'a D) -> Self {
+        Rows {
+            detector,
+            y: 0,
+            edges: 0..0,
+        }
+    }
+
+    // Carry on after the rows already scanned, which have to match the
+    // beam.
+    fn resume(detector: &'a D, rows: &[Range<usize>]) -> Result<Self, Error> {
+        // Rows the beam misses leave the edges where they were.
+        let edges = rows.iter().rev().find(|row| !row.is_empty()).cloned().unwrap_or(0..0);
+        let y = rows.iter().rposition(|row| !row.is_empty()).unwrap_or(0);
+        let matches = edges.is_empty()
+            || (detector.contains(edges.start, y) && !detector.contains(edges.end, y));
+        if !matches {
+            return Err("The rows in the checkpoint don't match the beam".into());
+        }
+
+        Ok(Rows {
+            detector,
+            y: rows.len(),
+            edges,
+        })
+    }
+}
+
+impl<'a, D: Detector> Iterator for Rows<'a, D> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let y = self.y;
+        let Range { start, end } = self.edges;
+        self.y += 1;
+
+        // Close to the emitter the beam is narrow enough to miss some rows
+        // entirely, so give up looking after a while.
+        let limit = end.max(start) + 10 * (y + 1);
+        let left = match (start..limit).find(|&x| self.detector.contains(x, y)) {
+            Some(left) => left,
+            None => return Some(start..start),
+        };
+
+        let right = (end.max(left + 1)..)
+            .find(|&x| !self.detector.contains(x, y))
+            .unwrap();
+
+        self.edges = left..right;
+        Some(left..right)
+    }
+}
+
+// Count the points pulled in the square of the given size at the emitter.
+fn count_affected(detector: &impl Detector, size: usize) -> usize {
+    Rows::new(detector)
+        .take(size)
+        .map(|row| row.end.min(size) - row.start.min(size))
+        .sum()
+}
+
+// Find the top left corner of the first square of the given size that fits
+// in the beam.
+fn closest_fit(detector: &impl Detector, side: usize) -> (usize, usize) {
+    closest_fit_from(detector, side, vec![], None).unwrap()
+}
+
+// The square which fits with its bottom edge on the last of the rows, if any.
+fn fits(rows: &[Range<usize>], side: usize) -> Option<(usize, usize)> {
+    let row = rows.last()?;
+    if rows.len() >= side && !row.is_empty() {
+        let top = rows.len() - side;
+        if rows[top].end >= row.start + side {
+            return Some((row.start, top));
+        }
+    }
+    None
+}
+
+// Like `closest_fit`, but starting from the rows already scanned and saving
+// the rows to the checkpoint as it goes.  The rows don't depend on the size
+// of the ship, so the same checkpoint works for any size.
+fn closest_fit_from(
+    detector: &impl Detector,
+    side: usize,
+    mut rows: Vec<Range<usize>>,
+    mut checkpoint: Option<&mut Checkpoint>,
+) -> Result<(usize, usize), Error> {
+    if let Some(fit) = (1..=rows.len()).find_map(|count| fits(&rows[..count], side)) {
+        return Ok(fit);
+    }
+
+    for row in Rows::resume(detector, &rows)? {
+        rows.push(row);
+        if let Some(fit) = fits(&rows, side) {
+            return Ok(fit);
+        }
+
+        if let Some(checkpoint) = checkpoint.as_mut() {
+            checkpoint.update(|output| write_rows(output, &rows));
+        }
+    }
+
+    unreachable!();
+}
+
+// The left and right edge of each row, one row per line.
+fn write_rows(output: &mut dyn Write, rows: &[Range<usize>]) -> io::Result<()> {
+    for row in rows {
+        writeln!(output, "{} {}", row.start, row.end)?;
+    }
+    Ok(())
+}
+
+fn parse_rows(text: &str) -> Result<Vec<Range<usize>>, Error> {
+    text.lines()
+        .map(|line| {
+            let edges: Vec<usize> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| format!("Bad row in checkpoint: {:?}", line))?;
+            match edges[..] {
+                [left, right] if left <= right => Ok(left..right),
+                _ => Err(format!("Bad row in checkpoint: {:?}", line).into()),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Point {
+    Empty,
+    Beam,
+    Ship,
+}
+
+impl Point {
+    fn symbol(self) -> char {
+        match self {
+            Point::Empty => '.',
+            Point::Beam => '#',
+            Point::Ship => 'O',
+        }
+    }
+
+    fn colour(self) -> [u8; 3] {
+        match self {
+            Point::Empty => [0, 0, 0],
+            Point::Beam => [128, 128, 128],
+            Point::Ship => [255, 165, 0],
+        }
+    }
+}
+
+// The beam from the emitter down past the ship, with the ship placed in it.
+fn beam_map(
+    detector: &impl Detector,
+    (ship_x, ship_y): (usize, usize),
+    side: usize,
+) -> Vec<Vec<Point>> {
+    let width = ship_x + side + MARGIN;
+    let height = ship_y + side + MARGIN;
+
+    Rows::new(detector)
+        .take(height)
+        .enumerate()
+        .map(|(y, row)| {
+            (0..width)
+                .map(|x| {
+                    if (ship_x..ship_x + side).contains(&x) && (ship_y..ship_y + side).contains(&y)
+                    {
+                        Point::Ship
+                    } else if row.contains(&x) {
+                        Point::Beam
+                    } else {
+                        Point::Empty
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn write_text(output: &mut impl Write, map: &[Vec<Point>]) -> io::Result<()> {
+    for row in map {
+        writeln!(
+            output,
+            "{}",
+            row.iter().map(|point| point.symbol()).collect::<String>()
+        )?;
+    }
+    Ok(())
+}
+
+fn write_png(output: impl Write, map: &[Vec<Point>]) -> Result<(), Error> {
+    let height = map.len();
+    let width = map.first().map(Vec::len).unwrap_or(0);
+
+    let mut encoder = png::Encoder::new(output, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = map
+        .iter()
+        .flatten()
+        .flat_map(|point| point.colour())
+        .collect();
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
+
+// Write the map as a PNG if the file name asks for one, otherwise as text.
+fn export_map(path: &Path, map: &[Vec<Point>]) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+
+    if path
+        .extension()
+        .map(|extension| extension == "png")
+        .unwrap_or(false)
+    {
+        write_png(file, map)
+    } else {
+        write_text(&mut file, map)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Length of each side of the ship
+    #[structopt(long, default_value = "100")]
+    side: usize,
+
+    /// Save the beam and ship as text, or as an image if the file ends in .png
+    #[structopt(short, long, parse(from_os_str))]
+    export: Option<PathBuf>,
+
+    /// Save the rows of the beam scanned so far to this file every so often
+    #[structopt(long, parse(from_os_str))]
+    checkpoint: Option<PathBuf>,
+
+    /// Seconds between saving the rows scanned
+    #[structopt(long, default_value = "60")]
+    checkpoint_interval: u64,
+
+    /// Carry on from the rows saved in the checkpoint instead of starting again
+    #[structopt(long, requires = "checkpoint")]
+    resume: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+// Find where the ship fits, saving the scan to the checkpoint if there is one.
+fn find_fit(beam: &Beam, opts: &Opts) -> Result<(usize, usize), Error> {
+    let path = match opts.checkpoint {
+        Some(ref path) => path,
+        None => return Ok(closest_fit(beam, opts.side)),
+    };
+
+    let mut checkpoint = Checkpoint::new(path, Duration::from_secs(opts.checkpoint_interval));
+    let rows = if opts.resume {
+        parse_rows(&checkpoint.load()?)?
+    } else {
+        vec![]
+    };
+    closest_fit_from(beam, opts.side, rows, Some(&mut checkpoint))
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let input = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .and_then(|_| opts.common.input());
+    let program = match input {
+        Ok(input) => Program::parse(input).unwrap(),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    let beam = Beam::new(&program);
+
+    if opts.common.part(2) == 1 {
+        opts.common.print_answers(&[("affected", count_affected(&beam, 50).into())]);
+        return;
+    }
+
+    let side = opts.side;
+    let (x, y) = match find_fit(&beam, &opts) {
+        Ok(corner) => corner,
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    for (corner_x, corner_y) in [
+        (x, y),
+        (x + side - 1, y),
+        (x, y + side - 1),
+        (x + side - 1, y + side - 1),
+    ]
+    .iter()
+    {
+        if !beam.contains(*corner_x, *corner_y) {
+            panic!("{}, {} outside beam", corner_x, corner_y);
+        }
+    }
+
+    if let Some(path) = opts.export {
+        if let Err(Error(message)) = export_map(&path, &beam_map(&beam, (x, y), side)) {
+            eprintln!("Failed to export map: {}", message);
+            std::process::exit(1);
+        }
+    }
+
+    opts.common.print_answers(&[("answer", (x * 10_000 + y).into())]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A beam between two straight lines, which misses the first few rows.
+    struct Cone;
+
+    impl Detector for Cone {
+        fn contains(&self, x: usize, y: usize) -> bool {
+            (x, y) == (0, 0) || (10 * x >= 7 * y && 10 * x <= 9 * y)
+        }
+    }
+
+    #[test]
+    fn rows() {
+        let rows: Vec<_> = Rows::new(&Cone).take(6).collect();
+        assert_eq!(rows, vec![0..1, 0..0, 0..0, 0..0, 3..4, 4..5]);
+    }
+
+    #[test]
+    fn affected() {
+        let expected = (0..20)
+            .flat_map(|y| (0..20).map(move |x| (x, y)))
+            .filter(|&(x, y)| Cone.contains(x, y))
+            .count();
+
+        assert_eq!(count_affected(&Cone, 20), expected);
+    }
+
+    #[test]
+    fn map() {
+        let (x, y) = closest_fit(&Cone, 2);
+        let map = beam_map(&Cone, (x, y), 2);
+
+        let mut output = Vec::new();
+        write_text(&mut output, &map).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(map.len(), y + 2 + MARGIN);
+        assert_eq!(output.lines().nth(y).unwrap().find('O'), Some(x));
+        assert_eq!(output.matches('O').count(), 4);
+    }
+
+    #[test]
+    fn fit() {
+        let fits = |x: usize, y: usize, side: usize| {
+            Cone.contains(x, y + side - 1) && Cone.contains(x + side - 1, y)
+        };
+        let expected = (0..)
+            .flat_map(|y| (0..=y).map(move |x| (x, y)))
+            .find(|&(x, y)| fits(x, y, 10))
+            .unwrap();
+
+        assert_eq!(closest_fit(&Cone, 10), expected);
+    }
+
+    #[test]
+    fn checkpoint() {
+        let name = format!("advent-19-{}.checkpoint", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let mut checkpoint = Checkpoint::new(&path, Duration::from_secs(0));
+        let expected = closest_fit(&Cone, 10);
+        let fit = closest_fit_from(&Cone, 10, vec![], Some(&mut checkpoint));
+        assert_eq!(fit.unwrap(), expected);
+
+        let rows = parse_rows(&checkpoint.load().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rows, Rows::new(&Cone).take(rows.len()).collect::<Vec<_>>());
+
+        // A smaller ship fits in the rows already scanned, a bigger one needs
+        // more of them.
+        let resume = |side, rows: &[Range<usize>]| {
+            closest_fit_from(&Cone, side, rows.to_vec(), None).unwrap()
+        };
+        assert_eq!(resume(10, &rows), expected);
+        assert_eq!(resume(4, &rows), closest_fit(&Cone, 4));
+        assert_eq!(resume(12, &rows[..5]), closest_fit(&Cone, 12));
+
+        assert!(closest_fit_from(&Cone, 10, vec![0..1, 0..0, 5..9], None).is_err());
+        assert!(parse_rows("1 2\n3\n").is_err());
+    }
+}
@@ -0,0 +1,934 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+use std::process;
+use std::io::{self, stdout, BufRead, Write};
+use structopt::StructOpt;
+use termion::event::Key;
+use termion::raw::IntoRawMode;
+use termion::{color, cursor};
+
+mod utils;
+
+use common::display::{read_keys, Screen};
+use common::{CommonOpts, Flag};
+use utils::Ticker;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Wall,
+    Empty,
+}
+
+enum PortalType {
+    Up,
+    Down,
+}
+
+struct Portal {
+    label: String,
+    exit: Position,
+    portal_type: PortalType,
+}
+
+struct Route {
+    distance: usize,
+    portals: Vec<PortalStep>,
+}
+
+// A portal taken along a route, after walking `distance` steps.
+#[derive(Debug, PartialEq, Eq)]
+struct PortalStep {
+    distance: usize,
+    label: String,
+    from_level: usize,
+    to_level: usize,
+}
+
+impl Portal {
+    // Without recursion every portal stays on the same level.
+    fn traverse(&self, level: usize, recursive: bool) -> Option<(Position, usize)> {
+        if !recursive {
+            return Some((self.exit, level));
+        }
+
+        match self.portal_type {
+            PortalType::Down => Some((self.exit, level + 1)),
+            PortalType::Up if level > 0 => Some((self.exit, level - 1)),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Position {
+    x: isize,
+    y: isize,
+}
+
+impl Position {
+    fn adjacent(self) -> impl Iterator<Item = Position> {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .iter()
+            .map(move |(x, y)| Position {
+                x: self.x + x,
+                y: self.y + y,
+            })
+    }
+}
+
+struct Map {
+    tiles: HashMap<Position, Tile>,
+    portals: HashMap<Position, Portal>,
+    start: (Position, usize),
+    end: (Position, usize),
+}
+
+impl Map {
+
+    fn read(input: impl BufRead) -> Result<Map, Error> {
+        let mut label_fragments = HashMap::new();
+        let mut tiles = HashMap::new();
+
+        for (y, line) in input.lines().enumerate() {
+            let line = line?;
+            for (x, c) in line.chars().enumerate() {
+                let position = Position {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                if c == '#' {
+                    tiles.insert(position, Tile::Wall);
+                } else if c == '.' {
+                    tiles.insert(position, Tile::Empty);
+                } else if c.is_ascii_uppercase() {
+                    label_fragments.insert(position, c);
+                }
+            }
+        }
+
+        if tiles.is_empty() {
+            return Err("Empty maze".into());
+        }
+
+        let min_x = tiles.keys().cloned().map(|p| p.x).min().unwrap();
+        let min_y = tiles.keys().cloned().map(|p| p.y).min().unwrap();
+        let max_x = tiles.keys().cloned().map(|p| p.x).max().unwrap();
+        let max_y = tiles.keys().cloned().map(|p| p.y).max().unwrap();
+
+        let mut start = None;
+        let mut end = None;
+        let mut labels: HashMap<String, Vec<Position>> = HashMap::new();
+
+        while let Some(position_a) = label_fragments.keys().min().cloned() {
+            let a = label_fragments.remove(&position_a).unwrap();
+            let position_b = position_a
+                .adjacent()
+                .find(|pos| label_fragments.contains_key(pos))
+                .ok_or_else(|| {
+                    format!(
+                        "Letter {} at ({}, {}) isn't part of a label",
+                        a, position_a.x, position_a.y
+                    )
+                })?;
+            let b = label_fragments.remove(&position_b).unwrap();
+
+            let mut order = vec![(position_a, a), (position_b, b)];
+            order.sort();
+
+            let label: String = order.into_iter().map(|(_, c)| c).collect();
+            let position = [position_a, position_b]
+                .iter()
+                .filter_map(|pos| {
+                    pos.adjacent()
+                        .find(|p| tiles.get(p).cloned() == Some(Tile::Empty))
+                })
+                .next()
+                .ok_or_else(|| {
+                    format!(
+                        "Label {} at ({}, {}) isn't next to the maze",
+                        label, position_a.x, position_a.y
+                    )
+                })?;
+
+            match label.as_str() {
+                "AA" if start.is_some() => return Err("More than one AA".into()),
+                "ZZ" if end.is_some() => return Err("More than one ZZ".into()),
+                "AA" => start = Some(position),
+                "ZZ" => end = Some(position),
+                _ => labels.entry(label).or_default().push(position),
+            }
+        }
+
+        let mut portals = HashMap::new();
+        let portal_type = |p: Position| {
+            if p.x == min_x || p.x == max_x || p.y == min_y || p.y == max_y {
+                PortalType::Up
+            } else {
+                PortalType::Down
+            }
+        };
+
+        let mut labels: Vec<(String, Vec<Position>)> = labels.into_iter().collect();
+        labels.sort();
+
+        for (label, positions) in labels.iter() {
+            if positions.len() != 2 {
+                return Err(format!("Portal {} has {} ends", label, positions.len()).into());
+            }
+
+            portals.insert(positions[0], Portal {
+                label: label.clone(),
+                exit: positions[1],
+                portal_type: portal_type(positions[0]),
+            });
+            portals.insert(positions[1],  Portal {
+                label: label.clone(),
+                exit: positions[0],
+                portal_type: portal_type(positions[1]),
+            });
+        }
+
+        Ok(Map {
+            tiles,
+            portals,
+            start: (start.ok_or("No entrance AA")?, 0),
+            end: (end.ok_or("No exit ZZ")?, 0),
+        })
+    }
+
+    // How deep to search unless told otherwise: one level per pair of
+    // portals.  This is a heuristic rather than a bound, as a route could in
+    // principle need to go deeper, but it's enough for the puzzle's mazes and
+    // stops a maze with no route from being searched forever.
+    fn default_max_level(&self) -> usize {
+        self.portals.len() / 2
+    }
+
+    fn can_visit(&self, position: Position) -> bool {
+        self.tiles.get(&position).cloned().unwrap_or(Tile::Wall) == Tile::Empty
+    }
+
+    // Distances by walking from the given position to every other tile
+    // that can be reached from it.
+    fn distances(&self, from: Position) -> HashMap<Position, usize> {
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(from, 0);
+        queue.push_back(from);
+
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for next in position.adjacent() {
+                if self.can_visit(next) && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    fn shortest_route(
+        &self,
+        from: (Position, usize),
+        to: (Position, usize),
+        recursive: bool,
+        max_level: usize,
+    ) -> Option<Route> {
+        self.search(from, to, recursive, max_level, |_, _, _| {})
+    }
+
+    // Dijkstra over the portals (and the start and end) on each level, using
+    // the walking distances between them on the base maze.  Levels deeper
+    // than `max_level` aren't searched.
+    //
+    // `progress` is called with the position, level and distance of each
+    // point as it's reached.
+    fn search(
+        &self,
+        from: (Position, usize),
+        to: (Position, usize),
+        recursive: bool,
+        max_level: usize,
+        mut progress: impl FnMut(Position, usize, usize),
+    ) -> Option<Route> {
+        let graph = PortalGraph::new(self);
+        let start = (graph.indices[&from.0], from.1);
+        let goal = (graph.indices[&to.0], to.1);
+
+        let mut distances = HashMap::new();
+        let mut previous: HashMap<Node, (Node, bool)> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        distances.insert(start, 0);
+        heap.push(Reverse((0, start)));
+
+        while let Some(Reverse((distance, (point, level)))) = heap.pop() {
+            if (point, level) == goal {
+                progress(graph.points[point], level, distance);
+                return Some(self.route(&graph, distance, goal, &previous, &distances));
+            }
+
+            if distance > distances[&(point, level)] {
+                continue;
+            }
+
+            progress(graph.points[point], level, distance);
+
+            let portal = self
+                .portals
+                .get(&graph.points[point])
+                .and_then(|portal| portal.traverse(level, recursive))
+                .filter(|(_, level)| *level <= max_level)
+                .map(|(exit, level)| ((graph.indices[&exit], level), 1, true));
+
+            let walks = graph.edges[point]
+                .iter()
+                .map(|&(next, length)| ((next, level), length, false));
+
+            for (next, length, through_portal) in walks.chain(portal) {
+                let next_distance = distance + length;
+                if distances
+                    .get(&next)
+                    .map(|&known| next_distance < known)
+                    .unwrap_or(true)
+                {
+                    distances.insert(next, next_distance);
+                    previous.insert(next, ((point, level), through_portal));
+                    heap.push(Reverse((next_distance, next)));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Work back from the end of the search to find the portals taken.
+    fn route(
+        &self,
+        graph: &PortalGraph,
+        distance: usize,
+        goal: Node,
+        previous: &HashMap<Node, (Node, bool)>,
+        distances: &HashMap<Node, usize>,
+    ) -> Route {
+        let mut portals = vec![];
+        let mut current = goal;
+
+        while let Some(&(last, through_portal)) = previous.get(&current) {
+            if through_portal {
+                portals.push(PortalStep {
+                    distance: distances[&last],
+                    label: self.portals[&graph.points[last.0]].label.clone(),
+                    from_level: last.1,
+                    to_level: current.1,
+                });
+            }
+            current = last;
+        }
+
+        portals.reverse();
+        Route { distance, portals }
+    }
+}
+
+impl Map {
+    fn point_name(&self, position: Position) -> String {
+        if position == self.start.0 {
+            "AA".to_string()
+        } else if position == self.end.0 {
+            "ZZ".to_string()
+        } else {
+            let portal = &self.portals[&position];
+            match portal.portal_type {
+                PortalType::Down => format!("{} inner", portal.label),
+                PortalType::Up => format!("{} outer", portal.label),
+            }
+        }
+    }
+
+    // Walking distances are the same in both directions, so each is only
+    // written once.  The portals themselves are shown as dashed edges.
+    fn write_dot(&self, output: &mut impl Write) -> io::Result<()> {
+        let graph = PortalGraph::new(self);
+        writeln!(output, "graph portals {{")?;
+
+        for &position in graph.points.iter() {
+            let name = self.point_name(position);
+            match self.portals.get(&position) {
+                None => writeln!(
+                    output,
+                    "    \"{}\" [shape=box, style=filled, fillcolor=orange];",
+                    name
+                )?,
+                Some(portal) => writeln!(
+                    output,
+                    "    \"{}\" [label=\"{}\\n{}\"];",
+                    name,
+                    portal.label,
+                    match portal.portal_type {
+                        PortalType::Down => "inner",
+                        PortalType::Up => "outer",
+                    }
+                )?,
+            }
+        }
+
+        for (from, edges) in graph.edges.iter().enumerate() {
+            for &(to, distance) in edges.iter().filter(|(to, _)| *to > from) {
+                writeln!(
+                    output,
+                    "    \"{}\" -- \"{}\" [label=\"{}\"];",
+                    self.point_name(graph.points[from]),
+                    self.point_name(graph.points[to]),
+                    distance
+                )?;
+            }
+        }
+
+        let mut portals: Vec<(&Position, &Portal)> = self.portals.iter().collect();
+        portals.sort_by_key(|(_, portal)| &portal.label);
+
+        for (&position, portal) in portals {
+            if let PortalType::Down = portal.portal_type {
+                writeln!(
+                    output,
+                    "    \"{}\" -- \"{}\" [label=\"1\", style=dashed];",
+                    self.point_name(position),
+                    self.point_name(portal.exit)
+                )?;
+            }
+        }
+
+        writeln!(output, "}}")
+    }
+
+    // The shortest distance between every pair of points in the portal graph
+    // on the flat maze, or None where one can't be reached from the other.
+    // Portals are counted as edges of one step, and the rest found from those
+    // and the walking distances.
+    fn distance_matrix(&self) -> (Vec<Position>, Vec<Vec<Option<usize>>>) {
+        let graph = PortalGraph::new(self);
+        let size = graph.points.len();
+        let mut distances = vec![vec![None; size]; size];
+
+        for (from, edges) in graph.edges.iter().enumerate() {
+            distances[from][from] = Some(0);
+            for &(to, distance) in edges.iter() {
+                distances[from][to] = Some(distance);
+            }
+            if let Some(portal) = self.portals.get(&graph.points[from]) {
+                distances[from][graph.indices[&portal.exit]] = Some(1);
+            }
+        }
+
+        for via in 0..size {
+            let onward = distances[via].clone();
+            for row in distances.iter_mut() {
+                let first = match row[via] {
+                    Some(first) => first,
+                    None => continue,
+                };
+                for (known, second) in row.iter_mut().zip(onward.iter()) {
+                    if let Some(second) = second {
+                        if known.is_none_or(|known| first + second < known) {
+                            *known = Some(first + second);
+                        }
+                    }
+                }
+            }
+        }
+
+        (graph.points, distances)
+    }
+
+    // The distance matrix as a table, with the points in order of their names.
+    fn write_matrix(&self, output: &mut impl Write) -> io::Result<()> {
+        let (points, distances) = self.distance_matrix();
+        let mut order: Vec<(String, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(index, &position)| (self.point_name(position), index))
+            .collect();
+        order.sort();
+
+        let width = order
+            .iter()
+            .map(|(name, _)| name.len())
+            .chain(distances.iter().flatten().flatten().map(|distance| distance.to_string().len()))
+            .max()
+            .unwrap_or(0);
+
+        write!(output, "{:width$}", "", width = width)?;
+        for (name, _) in order.iter() {
+            write!(output, "  {:>width$}", name, width = width)?;
+        }
+        writeln!(output)?;
+
+        for (name, from) in order.iter() {
+            write!(output, "{:width$}", name, width = width)?;
+            for &(_, to) in order.iter() {
+                match distances[*from][to] {
+                    Some(distance) => write!(output, "  {:>width$}", distance, width = width)?,
+                    None => write!(output, "  {:>width$}", "-", width = width)?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        Ok(())
+    }
+}
+
+// A point in the portal graph, and the level it's on.
+type Node = (usize, usize);
+
+// The tiles worth stopping at, and the distances between those that can
+// reach each other without going through a portal.
+struct PortalGraph {
+    points: Vec<Position>,
+    indices: HashMap<Position, usize>,
+    edges: Vec<Vec<(usize, usize)>>,
+}
+
+impl PortalGraph {
+    fn new(map: &Map) -> Self {
+        let mut portals: Vec<Position> = map.portals.keys().cloned().collect();
+        portals.sort();
+
+        let points: Vec<Position> = vec![map.start.0, map.end.0]
+            .into_iter()
+            .chain(portals)
+            .collect();
+        let indices: HashMap<Position, usize> = points
+            .iter()
+            .enumerate()
+            .map(|(index, position)| (*position, index))
+            .collect();
+
+        let edges = points
+            .iter()
+            .map(|&from| {
+                let distances = map.distances(from);
+                let mut edges: Vec<(usize, usize)> = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &to)| to != from)
+                    .filter_map(|(index, to)| distances.get(to).map(|&distance| (index, distance)))
+                    .collect();
+                edges.sort();
+                edges
+            })
+            .collect();
+
+        PortalGraph {
+            points,
+            indices,
+            edges,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Cell {
+    Wall,
+    Empty,
+    Start,
+    End,
+    Inner,
+    Outer,
+    Visited,
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Cell::*;
+        match self {
+            Wall => write!(f, "\u{2588}"),
+            Empty => write!(f, " "),
+            Start => write!(f, "{}A{}", color::Fg(color::Green), color::Fg(color::Reset)),
+            End => write!(f, "{}Z{}", color::Fg(color::Red), color::Fg(color::Reset)),
+            Inner => write!(f, "{}O{}", color::Fg(color::Blue), color::Fg(color::Reset)),
+            Outer => write!(f, "{}O{}", color::Fg(color::Magenta), color::Fg(color::Reset)),
+            Visited => write!(f, "{}*{}", color::Bg(color::Yellow), color::Bg(color::Reset)),
+        }
+    }
+}
+
+struct Viewer<'a, S> {
+    map: &'a Map,
+    screen: S,
+    level: usize,
+    visited: HashMap<usize, Vec<Position>>,
+    status_row: u16,
+}
+
+impl<'a, S: Screen> Viewer<'a, S> {
+    fn new(map: &'a Map, screen: S) -> Self {
+        let status_row = map.tiles.keys().map(|position| position.y).max().unwrap_or(0) as u16 + 1;
+        Viewer {
+            map,
+            screen,
+            level: 0,
+            visited: HashMap::new(),
+            status_row,
+        }
+    }
+
+    fn cell(&self, position: Position) -> Cell {
+        if position == self.map.start.0 {
+            Cell::Start
+        } else if position == self.map.end.0 {
+            Cell::End
+        } else if let Some(portal) = self.map.portals.get(&position) {
+            match portal.portal_type {
+                PortalType::Down => Cell::Inner,
+                PortalType::Up => Cell::Outer,
+            }
+        } else if self.map.can_visit(position) {
+            Cell::Empty
+        } else {
+            Cell::Wall
+        }
+    }
+
+    fn set_cell(&mut self, position: Position, cell: Cell) {
+        self.screen.set_tile([position.x as u16, position.y as u16], cell);
+    }
+
+    fn draw(&mut self) {
+        self.screen.clear();
+
+        let map = self.map;
+        for &position in map.tiles.keys() {
+            let cell = self.cell(position);
+            self.set_cell(position, cell);
+        }
+
+        let visited = self.visited.get(&self.level).cloned().unwrap_or_default();
+        for position in visited {
+            self.set_cell(position, Cell::Visited);
+        }
+    }
+
+    fn visit(&mut self, position: Position, level: usize) {
+        self.visited.entry(level).or_default().push(position);
+        if level == self.level {
+            self.set_cell(position, Cell::Visited);
+        }
+    }
+
+    fn status(&mut self, text: String) {
+        self.screen.goto([0, self.status_row]);
+        self.screen.print(format!("Level: {:<6} {:<60}", self.level, text));
+        self.screen.print("Up/Down to change level, q to quit".to_string());
+    }
+
+    // Change level if asked to, returning false if the user wants to quit.
+    fn handle(&mut self, key: Key) -> bool {
+        match key {
+            Key::Up => self.level += 1,
+            Key::Down if self.level > 0 => self.level -= 1,
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return false,
+            _ => return true,
+        }
+        self.draw();
+        true
+    }
+}
+
+fn show_search(map: &Map, screen: impl Screen, speed: Option<u64>, recursive: bool, max_level: usize) {
+    let keys = read_keys();
+    let mut viewer = Viewer::new(map, screen);
+    let mut ticker = speed.map(Ticker::with_rate);
+    let mut animate = true;
+
+    viewer.draw();
+    let route = map.search(map.start, map.end, recursive, max_level, |position, level, distance| {
+        viewer.visit(position, level);
+
+        if animate {
+            while let Ok(key) = keys.try_recv() {
+                animate = viewer.handle(key);
+            }
+            viewer.status(format!("Distance: {}", distance));
+
+            if let Some(ticker) = ticker.as_mut() {
+                ticker.wait();
+            }
+        }
+    });
+
+    match route {
+        Some(route) => viewer.status(format!("Finished, distance: {}", route.distance)),
+        None => viewer.status("No route found".to_string()),
+    }
+
+    for key in keys.iter() {
+        if !viewer.handle(key) {
+            break;
+        }
+        viewer.status(String::new());
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Print the graph of portals in Graphviz DOT format
+    #[structopt(long, conflicts_with_all = &["quiet", "json"])]
+    dot: bool,
+
+    /// Print the shortest distance between every pair of portals on the flat maze
+    #[structopt(long, conflicts_with_all = &["dot", "visualize", "quiet", "json"])]
+    matrix: bool,
+
+    /// Deepest level to search, defaults to the number of pairs of portals
+    #[structopt(short, long)]
+    max_level: Option<usize>,
+
+    /// Show the search on each level of the maze
+    #[structopt(short, long, conflicts_with_all = &["quiet", "json"])]
+    visualize: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let input = opts
+        .common
+        .unsupported(&[Flag::Debug])
+        .and_then(|_| opts.common.input());
+    let input = match input {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+
+    let map = match Map::read(input) {
+        Ok(map) => map,
+        Err(Error(message)) => {
+            eprintln!("Invalid maze: {}", message);
+            process::exit(1);
+        }
+    };
+
+    if opts.dot {
+        map.write_dot(&mut stdout().lock()).unwrap();
+        return;
+    }
+
+    if opts.matrix {
+        map.write_matrix(&mut stdout().lock()).unwrap();
+        return;
+    }
+
+    let max_level = opts.max_level.unwrap_or_else(|| map.default_max_level());
+
+    let part = opts.common.part(2);
+
+    if opts.visualize {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        show_search(&map, screen, opts.common.speed(), part != 1, max_level);
+        return;
+    }
+
+    let route = match map.shortest_route(map.start, map.end, part != 1, max_level) {
+        Some(route) => route,
+        None => {
+            eprintln!("No route from AA to ZZ within {} levels", max_level);
+            process::exit(1);
+        }
+    };
+
+    if !opts.common.answers_only() {
+        for step in route.portals.iter() {
+            println!(
+                "{:>6}: {} from level {} to level {}",
+                step.distance, step.label, step.from_level, step.to_level
+            );
+        }
+    }
+
+    opts.common.print_answers(&[("distance", route.distance.into())]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "         A           
+         A           
+  #######.#########  
+  #######.........#  
+  #######.#######.#  
+  #######.#######.#  
+  #######.#######.#  
+  #####  B    ###.#  
+BC...##  C    ###.#  
+  ##.##       ###.#  
+  ##...DE  F  ###.#  
+  #####    G  ###.#  
+  #########.#####.#  
+DE..#######...###.#  
+  #.#########.###.#  
+FG..#########.....#  
+  ###########.#####  
+             Z       
+             Z       
+";
+
+    const RECURSIVE_EXAMPLE: &str = "             Z L X W       C                 
+             Z P Q B       K                 
+  ###########.#.#.#.#######.###############  
+  #...#.......#.#.......#.#.......#.#.#...#  
+  ###.#.#.#.#.#.#.#.###.#.#.#######.#.#.###  
+  #.#...#.#.#...#.#.#...#...#...#.#.......#  
+  #.###.#######.###.###.#.###.###.#.#######  
+  #...#.......#.#...#...#.............#...#  
+  #.#########.#######.#.#######.#######.###  
+  #...#.#    F       R I       Z    #.#.#.#  
+  #.###.#    D       E C       H    #.#.#.#  
+  #.#...#                           #...#.#  
+  #.###.#                           #.###.#  
+  #.#....OA                       WB..#.#..ZH
+  #.###.#                           #.#.#.#  
+CJ......#                           #.....#  
+  #######                           #######  
+  #.#....CK                         #......IC
+  #.###.#                           #.###.#  
+  #.....#                           #...#.#  
+  ###.###                           #.#.#.#  
+XF....#.#                         RF..#.#.#  
+  #####.#                           #######  
+  #......CJ                       NM..#...#  
+  ###.#.#                           #.###.#  
+RE....#.#                           #......RF
+  ###.###        X   X       L      #.#.#.#  
+  #.....#        F   Q       P      #.#.#.#  
+  ###.###########.###.#######.#########.###  
+  #.....#...#.....#.......#...#.....#.#...#  
+  #####.#.###.#######.#######.###.###.#.#.#  
+  #.......#.......#.#.#.#.#...#...#...#.#.#  
+  #####.###.#####.#.#.#.#.###.###.#.###.###  
+  #.......#.....#.#...#...............#...#  
+  #############.#.#.###.###################  
+               A O F   N                     
+               A A D   M                     
+";
+
+    #[test]
+    fn single_level() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(
+            map.shortest_route(map.start, map.end, false, 0).map(|route| route.distance),
+            Some(23)
+        );
+    }
+
+    #[test]
+    fn recursive() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(
+            map.shortest_route(map.start, map.end, true, map.default_max_level()).map(|route| route.distance),
+            Some(26)
+        );
+    }
+
+    #[test]
+    fn portals() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        let route = map.shortest_route(map.start, map.end, false, 0).unwrap();
+        let labels: Vec<String> = route
+            .portals
+            .into_iter()
+            .map(|step| step.label)
+            .collect();
+        assert_eq!(labels, vec!["BC", "DE", "FG"]);
+    }
+
+    #[test]
+    fn many_levels() {
+        let map = Map::read(RECURSIVE_EXAMPLE.as_bytes()).unwrap();
+        let route = map.shortest_route(map.start, map.end, true, map.default_max_level()).unwrap();
+        assert_eq!(route.distance, 396);
+        assert_eq!(route.portals.iter().map(|step| step.to_level).max(), Some(10));
+    }
+
+    #[test]
+    fn level_limit() {
+        let map = Map::read(RECURSIVE_EXAMPLE.as_bytes()).unwrap();
+        assert!(map.shortest_route(map.start, map.end, true, 9).is_none());
+    }
+
+    #[test]
+    fn malformed() {
+        let error = |maze: &str| match Map::read(maze.as_bytes()) {
+            Err(Error(message)) => message,
+            Ok(_) => panic!("Maze should be invalid"),
+        };
+
+        assert_eq!(error(""), "Empty maze");
+        assert_eq!(error("  #.#\n  #.#\n   ZZ"), "No entrance AA");
+        assert_eq!(
+            error("   A \n   A \n  #.#\n  #.#\nBC..#\n  #.#\n   Z \n   Z "),
+            "Portal BC has 1 ends"
+        );
+        assert_eq!(
+            error("   A \n   A \n  #.#\n  #.X\n  #.#\n   ZZ"),
+            "Letter X at (4, 3) isn't part of a label"
+        );
+        assert!(Map::read(&b"  #.#\n  #\xff#\n"[..]).is_err());
+    }
+
+    #[test]
+    fn dot() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+
+        let mut output = Vec::new();
+        map.write_dot(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("graph portals {"));
+        assert!(output.contains("\"AA\" [shape=box, style=filled, fillcolor=orange];"));
+        assert!(output.contains("\"BC inner\" [label=\"BC\\ninner\"];"));
+        assert!(output.contains("\"AA\" -- \"BC inner\" [label=\"4\"];"));
+        assert!(output.contains("\"AA\" -- \"ZZ\" [label=\"26\"];"));
+        assert!(output.contains("\"BC inner\" -- \"BC outer\" [label=\"1\", style=dashed];"));
+    }
+
+    #[test]
+    fn matrix() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        let (points, distances) = map.distance_matrix();
+        for (from, row) in points.iter().zip(distances.iter()) {
+            for (to, distance) in points.iter().zip(row.iter()) {
+                let route = map.shortest_route((*from, 0), (*to, 0), false, 0);
+                assert_eq!(route.map(|route| route.distance), *distance);
+            }
+        }
+
+        let mut output = Vec::new();
+        map.write_matrix(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), points.len() + 1);
+        assert!(lines[0].trim_start().starts_with("AA  BC inner  BC outer"));
+        assert!(lines[1].starts_with("AA        "));
+        assert!(lines[1].ends_with("23"));
+        assert!(lines.last().unwrap().starts_with("ZZ"));
+    }
+}
@@ -0,0 +1,301 @@
+use std::io::{self, stdout};
+use std::fs;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+mod display;
+mod replay;
+mod simulator;
+mod springscript;
+mod synthesis;
+mod utils;
+
+use common::{CommonOpts, Flag};
+use intcode::ascii::{AsciiInput, AsciiOutput};
+use intcode::process::{Process, State};
+use intcode::program::Program;
+use intcode::transcript::{Playback, Recorder, RecordingInput, RecordingOutput, Transcript};
+use replay::Failure;
+use simulator::{failures, simulate, Outcome, MAX_EXHAUSTIVE_LENGTH};
+use springscript::{Mode, Script};
+use synthesis::extract_hulls;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+
+// Run the droid, returning everything it printed and a transcript of the
+// run.
+fn run(program: &Program, script: &Script) -> (String, Transcript) {
+    let mut output = vec![];
+    let script = script.to_string();
+    let recorder = Recorder::new();
+    let mut process = Process::new(
+        "springdroid",
+        program,
+        RecordingInput::new(AsciiInput::new(script.as_bytes(), io::sink(), ""), &recorder),
+        RecordingOutput::new(AsciiOutput::labelled(&mut output, "Damage"), &recorder),
+    );
+
+    let state = process.execute();
+    assert_eq!(state, State::Complete);
+    recorder.halt();
+    drop(process);
+
+    (String::from_utf8_lossy(&output).into_owned(), recorder.transcript())
+}
+
+// Let the springscript be typed in at the terminal, showing what the droid
+// prints as it goes.
+fn interactive(program: &Program) -> Result<(), Error> {
+    let input = AsciiInput::terminal("")?;
+    let mut process = Process::new(
+        "springdroid",
+        program,
+        input,
+        AsciiOutput::labelled(stdout(), "Damage"),
+    );
+    match process.execute() {
+        State::Complete => Ok(()),
+        _ => Err("The droid was still waiting for springscript".into()),
+    }
+}
+
+// The hull damage reported at the end of a successful run.
+fn damage(output: &str) -> Option<i64> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Damage: "))
+        .and_then(|damage| damage.parse().ok())
+}
+
+// Scripts known to get the droid across the hull.
+const WALK_SCRIPT: &str = "NOT A J\nNOT B T\nOR T J\nNOT C T\nOR T J\nAND D J\nWALK\n";
+const RUN_SCRIPT: &str = include_str!("../code");
+
+// Frames per second when replaying a failed run, unless given.
+const DEFAULT_SPEED: u64 = 5;
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// File containing the springscript to run, instead of the one for the part
+    #[structopt(parse(from_os_str))]
+    script: Option<PathBuf>,
+
+    /// Search for a script that survives the hulls in renderings of failed runs
+    #[structopt(long, parse(from_os_str))]
+    synthesize: Option<PathBuf>,
+
+    /// Simulate the script on these hulls rather than running the droid
+    #[structopt(long)]
+    simulate: Vec<String>,
+
+    /// Simulate the script on every hull of this length that can be crossed, from 1 to 32
+    #[structopt(long, parse(try_from_str = parse_hull_length))]
+    exhaustive: Option<usize>,
+
+    /// Write every value passed to and from the droid to a transcript
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+
+    /// Check the droid does exactly what a transcript says, instead of running it
+    #[structopt(long, parse(from_os_str), conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    /// Type the springscript in at the terminal instead of running a script
+    #[structopt(
+        long,
+        conflicts_with_all = &["script", "synthesize", "simulate", "exhaustive", "record", "replay"]
+    )]
+    interactive: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn parse_hull_length(text: &str) -> Result<usize, String> {
+    let length = text
+        .parse()
+        .map_err(|_| format!("{:?} isn't a length", text))?;
+    if (1..=MAX_EXHAUSTIVE_LENGTH).contains(&length) {
+        Ok(length)
+    } else {
+        Err(format!(
+            "Hulls can only be from 1 to {} tiles long",
+            MAX_EXHAUSTIVE_LENGTH
+        ))
+    }
+}
+
+fn read_program(common: &CommonOpts) -> Program {
+    match common.input() {
+        Ok(input) => Program::parse(input).unwrap(),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn read_script(path: &Path) -> Result<Script, Error> {
+    let code = fs::read_to_string(path)?;
+    match Script::parse(&code) {
+        Ok(script) => Ok(script),
+        Err(springscript::Error(message)) => {
+            Err(format!("Invalid springscript:\n{}", message).into())
+        }
+    }
+}
+
+fn synthesize(path: &Path, mode: Mode) -> Result<Script, Error> {
+    let hulls = extract_hulls(&fs::read_to_string(path)?);
+    if hulls.is_empty() {
+        return Err("No hulls found".into());
+    }
+
+    synthesis::synthesize(&hulls, mode).ok_or_else(|| "No script survives every hull".into())
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    if let Err(message) = opts.common.unsupported(&[Flag::Debug]) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+
+    // Part 1 walks and part 2 runs.
+    let part = opts.common.part(2);
+
+    if let Some(path) = opts.replay {
+        let program = read_program(&opts.common);
+        match Transcript::load(&path).and_then(|transcript| Playback::new(transcript).replay(&program)) {
+            Ok(count) => println!("Replayed {} values", count),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if opts.interactive {
+        if let Err(Error(message)) = interactive(&read_program(&opts.common)) {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = opts.synthesize {
+        let mode = if part == 1 { Mode::Walk } else { Mode::Run };
+        match synthesize(&path, mode) {
+            Ok(script) => print!("{}", script),
+            Err(Error(message)) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let script = match opts.script.as_deref() {
+        Some(path) => read_script(path),
+        None if part == 1 => Ok(Script::parse(WALK_SCRIPT).unwrap()),
+        None => Ok(Script::parse(RUN_SCRIPT).unwrap()),
+    };
+
+    let script = match script {
+        Ok(script) => script,
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    for hull in opts.simulate.iter() {
+        match simulate(&script, hull) {
+            Outcome::Survived => println!("{}: survived", hull),
+            Outcome::Fell(position) => println!("{}: fell at {}", hull, position),
+        }
+    }
+
+    if let Some(length) = opts.exhaustive {
+        let failures = failures(&script, length);
+        for hull in failures.iter() {
+            println!("{}", hull);
+        }
+        println!("Failed on {} hulls", failures.len());
+    }
+
+    if !opts.simulate.is_empty() || opts.exhaustive.is_some() {
+        return;
+    }
+
+    let program = read_program(&opts.common);
+    let (output, transcript) = run(&program, &script);
+
+    if let Some(path) = opts.record {
+        if let Err(message) = transcript.save(&path) {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+
+    if opts.common.answers_only() {
+        match damage(&output) {
+            Some(damage) => opts.common.print_answers(&[("damage", damage.into())]),
+            None => {
+                eprintln!("The droid didn't make it across");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    match replay::parse_failure(&output) {
+        Some(frames) => {
+            let mut screen = stdout();
+            let speed = opts.common.speed().unwrap_or(DEFAULT_SPEED);
+            replay::show(&frames, &mut screen, speed);
+            if let Some(failure) = Failure::new(&frames, script.mode.range()) {
+                println!("{}", failure);
+            }
+        }
+        None => print!("{}", output),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bundled_scripts() {
+        assert_eq!(Script::parse(WALK_SCRIPT).unwrap().mode, Mode::Walk);
+        assert_eq!(Script::parse(RUN_SCRIPT).unwrap().mode, Mode::Run);
+    }
+
+    #[test]
+    fn damages() {
+        assert_eq!(
+            damage("Input instructions:\n\nWalking...\n\nDamage: 19354173\n"),
+            Some(19354173)
+        );
+        assert_eq!(damage("Walking...\n\nDidn't make it across:\n"), None);
+    }
+
+    #[test]
+    fn hull_lengths() {
+        assert_eq!(parse_hull_length("1"), Ok(1));
+        assert_eq!(parse_hull_length("32"), Ok(32));
+        assert!(parse_hull_length("0").is_err());
+        assert!(parse_hull_length("33").is_err());
+        assert!(parse_hull_length("-1").is_err());
+    }
+}
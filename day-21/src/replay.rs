@@ -0,0 +1,216 @@
+use std::fmt;
+
+use termion::color;
+
+use crate::display::Screen;
+use crate::utils::Ticker;
+
+const FAILURE: &str = "Didn't make it across:";
+
+// How many of the final frames to show.
+const FRAMES_SHOWN: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Frame {
+    lines: Vec<String>,
+}
+
+impl Frame {
+    fn hull(&self) -> &str {
+        self.lines.last().unwrap()
+    }
+
+    // The column and row of the droid.
+    fn droid(&self) -> Option<(usize, usize)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .find_map(|(row, line)| line.find('@').map(|column| (column, row)))
+    }
+
+    fn is_airborne(&self) -> bool {
+        self.droid()
+            .map(|(_, row)| row + 2 < self.lines.len())
+            .unwrap_or(false)
+    }
+}
+
+// The frames of the replay the droid prints when it falls, if it fell.
+pub fn parse_failure(text: &str) -> Option<Vec<Frame>> {
+    let start = text.find(FAILURE)? + FAILURE.len();
+
+    let frames = text[start..]
+        .split("\n\n")
+        .map(|block| Frame {
+            lines: block
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+        })
+        .filter(|frame| !frame.lines.is_empty())
+        .collect();
+
+    Some(frames)
+}
+
+// Where the droid fell, where it made its last decision, and what its
+// sensors could see from there.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Failure {
+    pub hole: usize,
+    pub decided_at: usize,
+    pub jumped: bool,
+    pub sensors: String,
+}
+
+impl Failure {
+    pub fn new(frames: &[Frame], range: u8) -> Option<Self> {
+        let last = frames.last()?;
+        let (hole, _) = last.droid()?;
+
+        // A jump takes the droid up into the air for a few frames before it
+        // comes down.
+        let jumped = frames.iter().rev().take(4).any(Frame::is_airborne);
+        let decided_at = if jumped { hole.checked_sub(4)? } else { hole.checked_sub(1)? };
+
+        let hull = last.hull().as_bytes();
+        let sensors = (1..=range as usize)
+            .map(|distance| {
+                if hull.get(decided_at + distance).cloned().unwrap_or(b'#') == b'#' {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        Some(Failure {
+            hole,
+            decided_at,
+            jumped,
+            sensors,
+        })
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Fell into the hole at {} after {} from {}",
+            self.hole,
+            if self.jumped { "jumping" } else { "walking" },
+            self.decided_at
+        )?;
+        let names: String = (b'A'..).take(self.sensors.len()).map(char::from).collect();
+        writeln!(f, "Sensors: {}", names)?;
+        write!(f, "         {}", self.sensors)
+    }
+}
+
+struct Tile(char);
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            '@' => write!(f, "{}@{}", color::Fg(color::Red), color::Fg(color::Reset)),
+            c => write!(f, "{}", c),
+        }
+    }
+}
+
+impl fmt::Debug for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Play back the last few frames before the droid fell.
+pub fn show(frames: &[Frame], screen: &mut impl Screen, rate: u64) {
+    let mut ticker = Ticker::with_rate(rate);
+    let start = frames.len().saturating_sub(FRAMES_SHOWN);
+
+    for frame in frames[start..].iter() {
+        screen.clear();
+        for (y, line) in frame.lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                screen.set_tile([x as u16, y as u16], Tile(c));
+            }
+        }
+        ticker.wait();
+    }
+
+    let height = frames[start..].iter().map(|frame| frame.lines.len()).max().unwrap_or(0);
+    screen.goto([0, height as u16 + 1]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REPLAY: &str = "Walking...
+
+Didn't make it across:
+
+.................
+.................
+@................
+#####.#..########
+
+.................
+.................
+.@...............
+#####.#..########
+
+.................
+.................
+..@..............
+#####.#..########
+
+...@.............
+.................
+.................
+#####.#..########
+
+.................
+....@............
+.................
+#####.#..########
+
+.................
+.................
+.....@...........
+#####.#..########
+
+.................
+.................
+.................
+#####@#..########
+
+";
+
+    #[test]
+    fn parse() {
+        let frames = parse_failure(REPLAY).unwrap();
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames[3].droid(), Some((3, 0)));
+        assert_eq!(frames[6].hull(), "#####@#..########");
+        assert_eq!(parse_failure("Walking...\n\n19352638\n"), None);
+    }
+
+    #[test]
+    fn failure() {
+        let frames = parse_failure(REPLAY).unwrap();
+        assert_eq!(
+            Failure::new(&frames, 4),
+            Some(Failure {
+                hole: 5,
+                decided_at: 1,
+                jumped: true,
+                sensors: "###.".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::springscript::{Operation, Register, Script};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Survived,
+    // The droid fell through the hole at this position.
+    Fell(usize),
+}
+
+// What the sensors read with the droid at `position`, one bit per tile ahead.
+// Everything past the end of the hull is ground.
+pub fn reading(hull: &[u8], position: usize, range: u8) -> u16 {
+    (1..=range as usize)
+        .filter(|distance| hull.get(position + distance).cloned().unwrap_or(b'#') == b'#')
+        .fold(0, |reading, distance| reading | 1 << (distance - 1))
+}
+
+// Whether the script tells the droid to jump given what its sensors read.
+pub fn jumps(script: &Script, reading: u16) -> bool {
+    let mut temporary = false;
+    let mut jump = false;
+
+    for instruction in script.instructions.iter() {
+        let source = match instruction.source {
+            Register::Sensor(distance) => reading & 1 << (distance - 1) != 0,
+            Register::Temporary => temporary,
+            Register::Jump => jump,
+        };
+        let target = match instruction.target {
+            Register::Temporary => &mut temporary,
+            _ => &mut jump,
+        };
+
+        *target = match instruction.operation {
+            Operation::And => source && *target,
+            Operation::Or => source || *target,
+            Operation::Not => !source,
+        };
+    }
+
+    jump
+}
+
+// Walk the droid along the hull, jumping whenever `jump` says to.
+pub fn run(hull: &str, range: u8, jump: impl Fn(u16) -> bool) -> Outcome {
+    let hull = hull.as_bytes();
+    let mut position = 0;
+
+    while position < hull.len() {
+        if hull[position] != b'#' {
+            return Outcome::Fell(position);
+        }
+
+        position += if jump(reading(hull, position, range)) {
+            4
+        } else {
+            1
+        };
+    }
+
+    Outcome::Survived
+}
+
+pub fn simulate(script: &Script, hull: &str) -> Outcome {
+    run(hull, script.mode.range(), |reading| jumps(script, reading))
+}
+
+// Whether a droid that could see the whole hull would get across.
+pub fn survivable(hull: &str) -> bool {
+    let hull = hull.as_bytes();
+    let mut reachable = vec![false; hull.len() + 4];
+    reachable[0] = true;
+
+    for position in 0..hull.len() {
+        if reachable[position] && hull[position] == b'#' {
+            reachable[position + 1] = true;
+            reachable[position + 4] = true;
+        }
+    }
+
+    reachable[hull.len()..].iter().any(|reachable| *reachable)
+}
+
+// The longest hull `failures` can try every version of, as each tile after the
+// first is a bit of a u32.
+pub const MAX_EXHAUSTIVE_LENGTH: usize = 32;
+
+// Every hull of the given length which starts with ground and can be
+// crossed, on which the script falls.  The length must be from 1 to
+// `MAX_EXHAUSTIVE_LENGTH`.
+pub fn failures(script: &Script, length: usize) -> Vec<String> {
+    (0..1u32 << (length - 1))
+        .map(|holes| {
+            (0..length)
+                .map(|position| {
+                    if position > 0 && holes & 1 << (position - 1) != 0 {
+                        '.'
+                    } else {
+                        '#'
+                    }
+                })
+                .collect::<String>()
+        })
+        .filter(|hull| survivable(hull) && simulate(script, hull) != Outcome::Survived)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WALK: &str = "OR A T\nAND C T\nNOT T J\nAND D J\nWALK\n";
+
+    #[test]
+    fn walk() {
+        let script = Script::parse(WALK).unwrap();
+        assert_eq!(simulate(&script, "#####.###########"), Outcome::Survived);
+        assert_eq!(simulate(&script, "#####..#.########"), Outcome::Survived);
+        assert_eq!(simulate(&script, "#####.#.##..#.###"), Outcome::Fell(7));
+    }
+
+    #[test]
+    fn survivable_hulls() {
+        assert!(survivable("#...#"));
+        assert!(!survivable("#....#"));
+        assert!(survivable("#.#.#"));
+    }
+
+    #[test]
+    fn exhaustive() {
+        let script = Script::parse(WALK).unwrap();
+        let failures = failures(&script, 8);
+        assert_eq!(failures.len(), 4);
+        assert!(failures.contains(&"##.##.##".to_string()));
+        assert_eq!(simulate(&script, "##.##.##"), Outcome::Fell(2));
+    }
+}
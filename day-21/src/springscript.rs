@@ -0,0 +1,274 @@
+use std::fmt;
+
+const MAX_INSTRUCTIONS: usize = 15;
+
+#[derive(Debug)]
+pub struct Error(pub String);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    // How many tiles ahead the droid can see.
+    pub fn range(self) -> u8 {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    And,
+    Or,
+    Not,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::And => write!(f, "AND"),
+            Operation::Or => write!(f, "OR"),
+            Operation::Not => write!(f, "NOT"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    // Whether there's ground this many tiles ahead.
+    Sensor(u8),
+    Temporary,
+    Jump,
+}
+
+impl Register {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "T" => Some(Register::Temporary),
+            "J" => Some(Register::Jump),
+            _ if name.len() == 1 && ("A"..="I").contains(&name) => {
+                Some(Register::Sensor(name.as_bytes()[0] - b'A' + 1))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_writable(self) -> bool {
+        self == Register::Temporary || self == Register::Jump
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::Sensor(distance) => write!(f, "{}", (b'A' + distance - 1) as char),
+            Register::Temporary => write!(f, "T"),
+            Register::Jump => write!(f, "J"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub operation: Operation,
+    pub source: Register,
+    pub target: Register,
+}
+
+impl Instruction {
+    fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let operation = match words[0] {
+            "AND" => Operation::And,
+            "OR" => Operation::Or,
+            "NOT" => Operation::Not,
+            other => return Err(format!("unknown instruction \"{}\"", other)),
+        };
+
+        if words.len() != 3 {
+            return Err(format!(
+                "{} takes 2 registers, found {}",
+                operation,
+                words.len() - 1
+            ));
+        }
+
+        let source = Register::parse(words[1])
+            .ok_or_else(|| format!("unknown register \"{}\"", words[1]))?;
+        let target = Register::parse(words[2])
+            .ok_or_else(|| format!("unknown register \"{}\"", words[2]))?;
+
+        if !target.is_writable() {
+            return Err(format!("can't write to {}, only T and J", target));
+        }
+
+        Ok(Instruction {
+            operation,
+            source,
+            target,
+        })
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.operation, self.source, self.target)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+}
+
+impl Script {
+    // Check the script the same way the droid would, reporting every problem
+    // found along with the line it's on.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut errors = vec![];
+        let mut instructions = vec![];
+        let mut mode = None;
+
+        let lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        for (number, line) in lines {
+            if let Some(mode) = mode {
+                errors.push((number, format!("instructions after {}", mode)));
+                break;
+            }
+
+            match line {
+                "WALK" => mode = Some(Mode::Walk),
+                "RUN" => mode = Some(Mode::Run),
+                _ => match Instruction::parse(line) {
+                    Ok(instruction) => instructions.push((number, instruction)),
+                    Err(message) => errors.push((number, message)),
+                },
+            }
+        }
+
+        for (number, instruction) in instructions.iter() {
+            if let (Some(mode), Register::Sensor(distance)) = (mode, instruction.source) {
+                if distance > mode.range() {
+                    errors.push((
+                        *number,
+                        format!(
+                            "sensor {} is only available when running",
+                            instruction.source
+                        ),
+                    ));
+                }
+            }
+        }
+
+        errors.sort_by_key(|(number, _)| *number);
+        let mut messages: Vec<String> = errors
+            .into_iter()
+            .map(|(number, message)| format!("Line {}: {}", number, message))
+            .collect();
+
+        if mode.is_none() {
+            messages.push("Script must end with WALK or RUN".to_string());
+        }
+
+        if instructions.len() > MAX_INSTRUCTIONS {
+            messages.push(format!(
+                "{} instructions, limit is {}",
+                instructions.len(),
+                MAX_INSTRUCTIONS
+            ));
+        }
+
+        if !messages.is_empty() {
+            return Err(Error(messages.join("\n")));
+        }
+
+        Ok(Script {
+            instructions: instructions
+                .into_iter()
+                .map(|(_, instruction)| instruction)
+                .collect(),
+            mode: mode.unwrap(),
+        })
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in self.instructions.iter() {
+            writeln!(f, "{}", instruction)?;
+        }
+        writeln!(f, "{}", self.mode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn errors(text: &str) -> Vec<String> {
+        match Script::parse(text) {
+            Ok(_) => vec![],
+            Err(Error(message)) => message.lines().map(str::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let script = Script::parse("NOT A J\nOR  D T\n\nAND T J\nWALK\n").unwrap();
+        assert_eq!(script.mode, Mode::Walk);
+        assert_eq!(script.instructions.len(), 3);
+        assert_eq!(
+            script.instructions[1],
+            Instruction {
+                operation: Operation::Or,
+                source: Register::Sensor(4),
+                target: Register::Temporary,
+            }
+        );
+        assert_eq!(script.to_string(), "NOT A J\nOR D T\nAND T J\nWALK\n");
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            errors("NOR A J\nAND A\nOR X J\nNOT J A\nOR E J\nWALK\nNOT A J"),
+            vec![
+                "Line 1: unknown instruction \"NOR\"",
+                "Line 2: AND takes 2 registers, found 1",
+                "Line 3: unknown register \"X\"",
+                "Line 4: can't write to A, only T and J",
+                "Line 5: sensor E is only available when running",
+                "Line 7: instructions after WALK",
+            ]
+        );
+        assert_eq!(errors("NOT A J"), vec!["Script must end with WALK or RUN"]);
+        assert_eq!(
+            errors(&"NOT A J\n".repeat(16)),
+            vec![
+                "Script must end with WALK or RUN",
+                "16 instructions, limit is 15"
+            ]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::simulator::{self, reading, Outcome};
+use crate::springscript::{Instruction, Mode, Operation, Register, Script};
+
+const MAX_INSTRUCTIONS: usize = 15;
+
+// Give up once this many distinct register states have been seen.
+const MAX_STATES: usize = 2_000_000;
+
+// Pull the hulls out of the droid's renderings of failed runs: the lines
+// made up only of ground and holes, with at least some ground.
+pub fn extract_hulls(text: &str) -> Vec<String> {
+    let mut hulls = vec![];
+
+    for line in text.lines().map(str::trim) {
+        let is_hull = line.contains('#') && line.chars().all(|c| c == '#' || c == '.');
+        if is_hull && !hulls.iter().any(|hull| hull == line) {
+            hulls.push(line.to_string());
+        }
+    }
+
+    hulls
+}
+
+// The value of a register for every sensor reading that matters, one bit per
+// reading.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Table(Vec<u64>);
+
+impl Table {
+    fn new(size: usize, value: impl Fn(usize) -> bool) -> Self {
+        let mut words = vec![0; size.div_ceil(64)];
+        for index in (0..size).filter(|index| value(*index)) {
+            words[index / 64] |= 1 << (index % 64);
+        }
+        Table(words)
+    }
+
+    fn get(&self, index: usize) -> bool {
+        self.0[index / 64] & 1 << (index % 64) != 0
+    }
+
+    fn combine(&self, other: &Table, op: impl Fn(u64, u64) -> u64) -> Table {
+        Table(
+            self.0
+                .iter()
+                .zip(other.0.iter())
+                .map(|(a, b)| op(*a, *b))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Registers {
+    temporary: Table,
+    jump: Table,
+}
+
+struct Synthesizer {
+    range: u8,
+    size: usize,
+    indices: HashMap<u16, usize>,
+    sensors: Vec<Table>,
+    all: Table,
+}
+
+impl Synthesizer {
+    fn new(hulls: &[String], range: u8) -> Self {
+        let mut readings = vec![];
+        for hull in hulls {
+            let bytes = hull.as_bytes();
+            for position in (0..bytes.len()).filter(|position| bytes[*position] == b'#') {
+                let reading = reading(bytes, position, range);
+                if !readings.contains(&reading) {
+                    readings.push(reading);
+                }
+            }
+        }
+
+        let indices = readings
+            .iter()
+            .enumerate()
+            .map(|(index, reading)| (*reading, index))
+            .collect();
+
+        let sensors = (0..range)
+            .map(|bit| Table::new(readings.len(), |index| readings[index] & 1 << bit != 0))
+            .collect();
+
+        Synthesizer {
+            range,
+            size: readings.len(),
+            indices,
+            sensors,
+            all: Table::new(readings.len(), |_| true),
+        }
+    }
+
+    fn initial(&self) -> Registers {
+        Registers {
+            temporary: Table::new(self.size, |_| false),
+            jump: Table::new(self.size, |_| false),
+        }
+    }
+
+    fn instructions(&self) -> Vec<Instruction> {
+        let sources: Vec<Register> = (1..=self.range)
+            .map(Register::Sensor)
+            .chain(vec![Register::Temporary, Register::Jump])
+            .collect();
+
+        let mut instructions = vec![];
+        for &operation in [Operation::And, Operation::Or, Operation::Not].iter() {
+            for &source in sources.iter() {
+                for &target in [Register::Temporary, Register::Jump].iter() {
+                    instructions.push(Instruction {
+                        operation,
+                        source,
+                        target,
+                    });
+                }
+            }
+        }
+        instructions
+    }
+
+    fn execute(&self, registers: &Registers, instruction: &Instruction) -> Registers {
+        let source = match instruction.source {
+            Register::Sensor(distance) => &self.sensors[distance as usize - 1],
+            Register::Temporary => &registers.temporary,
+            Register::Jump => &registers.jump,
+        };
+        let target = match instruction.target {
+            Register::Temporary => &registers.temporary,
+            _ => &registers.jump,
+        };
+
+        let result = match instruction.operation {
+            Operation::And => source.combine(target, |a, b| a & b),
+            Operation::Or => source.combine(target, |a, b| a | b),
+            Operation::Not => source.combine(&self.all, |a, all| !a & all),
+        };
+
+        let mut registers = registers.clone();
+        match instruction.target {
+            Register::Temporary => registers.temporary = result,
+            _ => registers.jump = result,
+        }
+        registers
+    }
+
+    fn survives_all(&self, hulls: &[String], jump: &Table) -> bool {
+        hulls.iter().all(|hull| {
+            simulator::run(hull, self.range, |reading| jump.get(self.indices[&reading]))
+                == Outcome::Survived
+        })
+    }
+}
+
+// Breadth first search over programs, shortest first, treating two programs
+// as the same if they leave the registers the same for every reading the
+// droid could see on these hulls.
+pub fn synthesize(hulls: &[String], mode: Mode) -> Option<Script> {
+    let synthesizer = Synthesizer::new(hulls, mode.range());
+    let instructions = synthesizer.instructions();
+
+    let start = synthesizer.initial();
+
+    if synthesizer.survives_all(hulls, &start.jump) {
+        return Some(Script {
+            instructions: vec![],
+            mode,
+        });
+    }
+
+    let mut seen = HashSet::new();
+    seen.insert(start.clone());
+    let mut layer = vec![(start, vec![])];
+
+    for _ in 0..MAX_INSTRUCTIONS {
+        let mut next_layer = vec![];
+
+        for (registers, program) in layer {
+            for instruction in instructions.iter() {
+                let next = synthesizer.execute(&registers, instruction);
+                if seen.contains(&next) {
+                    continue;
+                }
+
+                let mut next_program: Vec<Instruction> = program.clone();
+                next_program.push(*instruction);
+
+                if synthesizer.survives_all(hulls, &next.jump) {
+                    return Some(Script {
+                        instructions: next_program,
+                        mode,
+                    });
+                }
+
+                if seen.len() >= MAX_STATES {
+                    return None;
+                }
+
+                seen.insert(next.clone());
+                next_layer.push((next, next_program));
+            }
+        }
+
+        layer = next_layer;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hulls_from_rendering() {
+        let rendering = "
+Didn't make it across:
+
+.................
+.................
+@................
+#####.#..########
+
+.................
+.................
+.@...............
+#####.#..########
+";
+        assert_eq!(extract_hulls(rendering), vec!["#####.#..########"]);
+    }
+
+    #[test]
+    fn walk() {
+        let hulls: Vec<String> = [
+            "#####.###########",
+            "#####..#.########",
+            "#####...#########",
+        ]
+        .iter()
+        .map(|hull| hull.to_string())
+        .collect();
+
+        let script = synthesize(&hulls, Mode::Walk).unwrap();
+        assert!(script.instructions.len() <= MAX_INSTRUCTIONS);
+        assert_eq!(script.mode, Mode::Walk);
+
+        let synthesizer = Synthesizer::new(&hulls, 4);
+        let mut registers = synthesizer.initial();
+        for instruction in script.instructions.iter() {
+            registers = synthesizer.execute(&registers, instruction);
+        }
+        assert!(synthesizer.survives_all(&hulls, &registers.jump));
+    }
+
+    #[test]
+    fn impossible() {
+        let hulls = vec!["#....#".to_string()];
+        assert_eq!(synthesize(&hulls, Mode::Walk), None);
+    }
+}
@@ -0,0 +1,150 @@
+// Shuffle a real deck of cards, one card at a time, to check the arithmetic.
+
+use std::io::{self, Write};
+
+use crate::Technique;
+use common::Checkpoint;
+
+// Give up rather than move more cards than this.
+pub const MAX_MOVES: u128 = 100_000_000;
+
+fn apply(deck: &mut Vec<u128>, technique: Technique) -> Result<(), String> {
+    let size = deck.len();
+
+    match technique {
+        Technique::NewStack => deck.reverse(),
+        Technique::Cut(depth) => {
+            let depth = depth.rem_euclid(size as i128) as usize;
+            deck.rotate_left(depth);
+        }
+        Technique::DealWithIncrement(increment) => {
+            let mut table: Vec<Option<u128>> = vec![None; size];
+            for (index, card) in deck.iter().enumerate() {
+                let position = (index as u128 * increment % size as u128) as usize;
+                if table[position].is_some() {
+                    return Err(format!(
+                        "Dealing with increment {} puts two cards at position {}",
+                        increment, position
+                    ));
+                }
+                table[position] = Some(*card);
+            }
+            *deck = table.into_iter().map(Option::unwrap).collect();
+        }
+    }
+
+    Ok(())
+}
+
+// A deck part way through being shuffled, after the first `applied`
+// techniques, counting each repetition of the shuffle separately.
+pub struct Partial {
+    pub applied: u128,
+    pub deck: Vec<u128>,
+}
+
+impl Partial {
+    pub fn new(deck_size: u128) -> Self {
+        Partial {
+            applied: 0,
+            deck: (0..deck_size).collect(),
+        }
+    }
+
+    // The number of techniques applied and the size of the deck on the first
+    // line, then the cards in order on the second.
+    pub fn write(&self, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "{} {}", self.applied, self.deck.len())?;
+        let cards: Vec<String> = self.deck.iter().map(u128::to_string).collect();
+        writeln!(output, "{}", cards.join(" "))
+    }
+
+    pub fn parse(text: &str, deck_size: u128) -> Result<Self, String> {
+        let error = || "The checkpoint isn't a partly shuffled deck".to_string();
+        let numbers = |line: Option<&str>| -> Result<Vec<u128>, String> {
+            line.ok_or_else(error)?
+                .split_whitespace()
+                .map(|number| number.parse().map_err(|_| error()))
+                .collect()
+        };
+
+        let mut lines = text.lines();
+        let (applied, size) = match numbers(lines.next())?[..] {
+            [applied, size] => (applied, size),
+            _ => return Err(error()),
+        };
+        if size != deck_size {
+            return Err(format!("The checkpoint is for a deck of {} cards", size));
+        }
+
+        let deck = numbers(lines.next())?;
+        let mut seen = vec![false; deck.len()];
+        for &card in deck.iter() {
+            match seen.get_mut(card as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Err(error()),
+            }
+        }
+        if deck.len() as u128 != deck_size {
+            return Err(error());
+        }
+
+        Ok(Partial { applied, deck })
+    }
+}
+
+// The deck after shuffling `repetitions` times, listing the card at each
+// position.
+pub fn shuffle(
+    techniques: &[Technique],
+    deck_size: u128,
+    repetitions: u128,
+) -> Result<Vec<u128>, String> {
+    check_moves(techniques, deck_size, repetitions)?;
+    resume(techniques, repetitions, Partial::new(deck_size), None)
+}
+
+// Whether the shuffle is small enough to simulate.
+pub fn check_moves(
+    techniques: &[Technique],
+    deck_size: u128,
+    repetitions: u128,
+) -> Result<(), String> {
+    let moves = deck_size
+        .checked_mul(repetitions)
+        .and_then(|moves| moves.checked_mul(techniques.len() as u128));
+    if moves.map(|moves| moves > MAX_MOVES).unwrap_or(true) {
+        return Err(format!(
+            "Shuffling {} cards {} times is too many to simulate",
+            deck_size, repetitions
+        ));
+    }
+    Ok(())
+}
+
+// Finish shuffling a partly shuffled deck, saving it to the checkpoint as it
+// goes.
+pub fn resume(
+    techniques: &[Technique],
+    repetitions: u128,
+    mut partial: Partial,
+    mut checkpoint: Option<&mut Checkpoint>,
+) -> Result<Vec<u128>, String> {
+    check_moves(techniques, partial.deck.len() as u128, repetitions)?;
+    let total = repetitions * techniques.len() as u128;
+    if partial.applied > total {
+        return Err("The checkpoint has shuffled the deck more times than asked".to_string());
+    }
+
+    while partial.applied < total {
+        let technique = techniques[(partial.applied % techniques.len() as u128) as usize];
+        apply(&mut partial.deck, technique)?;
+        partial.applied += 1;
+
+        if let Some(checkpoint) = checkpoint.as_mut() {
+            checkpoint.update(|output| partial.write(output));
+        }
+    }
+
+    Ok(partial.deck)
+}
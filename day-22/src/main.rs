@@ -0,0 +1,590 @@
+use std::str::FromStr;
+use std::fs;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use structopt::StructOpt;
+
+mod deck;
+mod term;
+
+use common::modmath::Affine;
+use common::{Checkpoint, CommonOpts, Flag};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Query {
+    // Which card ends up at this position.
+    Position(u128),
+    // Where this card ends up.
+    Card(u128),
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    // "position 2020" or "card 2019".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let query = match (words.next(), words.next().map(str::parse), words.next()) {
+            (Some("position"), Some(Ok(position)), None) => Query::Position(position),
+            (Some("card"), Some(Ok(card)), None) => Query::Card(card),
+            _ => return Err(format!("Unrecognised query {:?}", s).into()),
+        };
+        Ok(query)
+    }
+}
+
+impl Query {
+    // What the answer is called when there are several.
+    fn name(self) -> String {
+        match self {
+            Query::Position(position) => format!("card_at_{}", position),
+            Query::Card(card) => format!("position_of_{}", card),
+        }
+    }
+
+    fn describe(self, answer: u128) -> String {
+        match self {
+            Query::Position(position) => format!("Card at position {}: {}", position, answer),
+            Query::Card(card) => format!("Position of card {}: {}", card, answer),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Problem {
+    deck_size: u128,
+    repetitions: u128,
+    query: Query,
+}
+
+const PART_1: Problem = Problem {
+    deck_size: 10007,
+    repetitions: 1,
+    query: Query::Card(2019),
+};
+
+const PART_2: Problem = Problem {
+    deck_size: 119315717514047,
+    repetitions: 101741582076661,
+    query: Query::Position(2020),
+};
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Technique {
+    NewStack,
+    Cut(i128),
+    DealWithIncrement(u128),
+}
+
+impl FromStr for Technique {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "deal into new stack" {
+            Ok(Technique::NewStack)
+        } else if let Some(depth) = s.strip_prefix("cut ") {
+            Ok(Technique::Cut(depth.parse()?))
+        } else if let Some(increment) = s.strip_prefix("deal with increment ") {
+            Ok(Technique::DealWithIncrement(increment.parse()?))
+        } else {
+            Err(format!("Unrecognised technique {}", s).into())
+        }
+    }
+}
+
+impl Technique {
+    // Where each card moves to in a deck of the given size.
+    fn shuffle(self, deck_size: u128) -> Affine {
+        use Technique::*;
+        match self {
+            NewStack => Affine::new(deck_size - 1, deck_size - 1, deck_size),
+            Cut(depth) => {
+                let offset = (-depth).rem_euclid(deck_size as i128) as u128;
+                Affine::new(1, offset, deck_size)
+            }
+            DealWithIncrement(increment) => Affine::new(increment, 0, deck_size),
+        }
+    }
+}
+
+fn techniques<T: BufRead>(input: T) -> Result<Vec<Technique>, Error> {
+    input.lines().map(|line| line?.parse()).collect()
+}
+
+fn shuffle(techniques: &[Technique], deck_size: u128) -> Affine {
+    techniques
+        .iter()
+        .fold(Affine::identity(deck_size), |shuffle, technique| {
+            shuffle.then(technique.shuffle(deck_size))
+        })
+}
+
+// Where every card ends up after shuffling `repetitions` times, and the other
+// way round, worked out once so that any number of cards and positions can be
+// looked up cheaply.
+struct Shuffled {
+    forward: Affine,
+    backward: Option<Affine>,
+}
+
+impl Shuffled {
+    fn new(techniques: &[Technique], deck_size: u128, repetitions: u128) -> Self {
+        let once = shuffle(techniques, deck_size);
+        Shuffled {
+            forward: once.pow(repetitions),
+            backward: once.inverse().map(|unshuffle| unshuffle.pow(repetitions)),
+        }
+    }
+
+    // The card which ends up at `position`.
+    fn card_at(&self, position: u128) -> Option<u128> {
+        self.backward.map(|unshuffle| unshuffle.apply(position))
+    }
+
+    // Where `card` ends up.
+    fn position_of(&self, card: u128) -> u128 {
+        self.forward.apply(card)
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Number of cards in the deck, instead of the one for the part
+    #[structopt(long)]
+    deck_size: Option<u128>,
+
+    /// Number of times to shuffle the deck, instead of the one for the part
+    #[structopt(short, long)]
+    repetitions: Option<u128>,
+
+    /// Find which card ends up at each of these positions, as a comma separated list
+    #[structopt(long, use_delimiter = true)]
+    position: Vec<u128>,
+
+    /// Find where each of these cards ends up, as a comma separated list
+    #[structopt(long, use_delimiter = true)]
+    card: Vec<u128>,
+
+    /// Read positions and cards to look up from this file, one per line like "position 2020" or
+    /// "card 2019"
+    #[structopt(long, parse(from_os_str))]
+    queries: Option<PathBuf>,
+
+    /// Check the answer against the original symbolic solver
+    #[structopt(long)]
+    check: bool,
+
+    /// Shuffle a real deck and check every card against the arithmetic
+    #[structopt(long)]
+    simulate: bool,
+
+    /// Save the deck being shuffled to this file every so often
+    #[structopt(long, parse(from_os_str), requires = "simulate")]
+    checkpoint: Option<PathBuf>,
+
+    /// Seconds between saving the deck
+    #[structopt(long, default_value = "60")]
+    checkpoint_interval: u64,
+
+    /// Carry on shuffling the deck saved in the checkpoint instead of starting again
+    #[structopt(long, requires = "checkpoint")]
+    resume: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+impl Opts {
+    fn problem(&self) -> Result<Problem, Error> {
+        let problem = if self.common.part(2) == 1 { PART_1 } else { PART_2 };
+        if self.deck_size == Some(0) {
+            return Err("The deck needs at least one card".into());
+        }
+
+        Ok(Problem {
+            deck_size: self.deck_size.unwrap_or(problem.deck_size),
+            repetitions: self.repetitions.unwrap_or(problem.repetitions),
+            query: problem.query,
+        })
+    }
+
+    // Everything to look up, or just the part's question if nothing was
+    // asked for.
+    fn queries(&self) -> Result<Vec<Query>, Error> {
+        let mut queries: Vec<Query> = self.position.iter().cloned().map(Query::Position).collect();
+        queries.extend(self.card.iter().cloned().map(Query::Card));
+
+        if let Some(path) = &self.queries {
+            let text = fs::read_to_string(path)
+                .map_err(|error| format!("Can't read {}: {}", path.display(), error))?;
+            for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                queries.push(line.parse()?);
+            }
+        }
+
+        if queries.is_empty() {
+            queries.push(self.problem()?.query);
+        }
+        Ok(queries)
+    }
+}
+
+// Answer each of the queries about the problem's deck.  Working out the
+// shuffle is the expensive part, so it's only done once.
+fn solve_all(
+    problem: &Problem,
+    queries: &[Query],
+    techniques: &[Technique],
+    check: bool,
+) -> Result<Vec<u128>, Error> {
+    let Problem {
+        deck_size,
+        repetitions,
+        ..
+    } = *problem;
+    let shuffled = Shuffled::new(techniques, deck_size, repetitions);
+
+    queries
+        .iter()
+        .map(|&query| {
+            let (position, card) = match query {
+                Query::Position(position) | Query::Card(position) if position >= deck_size => {
+                    let message = format!("{} is outside a deck of {} cards", position, deck_size);
+                    return Err(message.into());
+                }
+                Query::Card(card) => (shuffled.position_of(card), card),
+                Query::Position(position) => {
+                    let card = shuffled.card_at(position).ok_or_else(|| {
+                        format!("The shuffle can't be undone with {} cards", deck_size)
+                    })?;
+                    (position, card)
+                }
+            };
+
+            if check {
+                let expected = term::card_at(techniques, deck_size, repetitions, position);
+                if expected != Some(card) {
+                    return Err(format!("Symbolic solver disagrees: {:?}", expected).into());
+                }
+            }
+
+            Ok(match query {
+                Query::Position(_) => card,
+                Query::Card(_) => position,
+            })
+        })
+        .collect()
+}
+
+// Shuffle a deck one card at a time and check that every card ends up where
+// the arithmetic says it should.
+fn simulate(problem: &Problem, techniques: &[Technique]) -> Result<u128, Error> {
+    let deck = deck::shuffle(techniques, problem.deck_size, problem.repetitions)?;
+    check_deck(problem, techniques, &deck)
+}
+
+// Like `simulate`, but saving the deck to the checkpoint as it's shuffled,
+// and starting from the deck saved there if `resume` is set.
+fn simulate_checkpointed(
+    problem: &Problem,
+    techniques: &[Technique],
+    path: &Path,
+    interval: Duration,
+    resume: bool,
+) -> Result<u128, Error> {
+    deck::check_moves(techniques, problem.deck_size, problem.repetitions)?;
+    let mut checkpoint = Checkpoint::new(path, interval);
+    let partial = if resume {
+        deck::Partial::parse(&checkpoint.load()?, problem.deck_size)?
+    } else {
+        deck::Partial::new(problem.deck_size)
+    };
+
+    let deck = deck::resume(techniques, problem.repetitions, partial, Some(&mut checkpoint))?;
+    check_deck(problem, techniques, &deck)
+}
+
+fn check_deck(problem: &Problem, techniques: &[Technique], deck: &[u128]) -> Result<u128, Error> {
+    // Ask for the card at every position along with the problem's own query,
+    // so the shuffle is only worked out once.
+    let mut queries: Vec<Query> = (0..deck.len() as u128).map(Query::Position).collect();
+    queries.push(problem.query);
+    let mut answers = solve_all(problem, &queries, techniques, false)?;
+    let answer = answers.pop().unwrap();
+
+    for (position, (&card, expected)) in deck.iter().zip(answers).enumerate() {
+        if expected != card {
+            return Err(format!(
+                "Simulation put card {} at position {}, expected card {}",
+                card, position, expected
+            )
+            .into());
+        }
+    }
+
+    Ok(answer)
+}
+
+fn main() {
+    let opts = Opts::from_args();
+
+    let problem = opts
+        .common
+        .unsupported(&[Flag::Speed, Flag::Debug])
+        .map_err(Error::from)
+        .and_then(|_| opts.problem());
+    let answers = problem.and_then(|problem| {
+        let queries = opts.queries()?;
+        let techniques = opts.common.input().map_err(Error::from).and_then(techniques)?;
+        let problem = Problem {
+            query: queries[0],
+            ..problem
+        };
+        if let Some(ref path) = opts.checkpoint {
+            let interval = Duration::from_secs(opts.checkpoint_interval);
+            simulate_checkpointed(&problem, &techniques, path, interval, opts.resume)?;
+        } else if opts.simulate {
+            simulate(&problem, &techniques)?;
+        }
+        let answers = solve_all(&problem, &queries, &techniques, opts.check)?;
+        Ok(queries.into_iter().zip(answers).collect::<Vec<_>>())
+    });
+
+    match answers {
+        Ok(answers) if answers.len() == 1 => {
+            opts.common.print_answers(&[("answer", answers[0].1.into())])
+        }
+        Ok(answers) if opts.common.answers_only() => {
+            let names: Vec<String> = answers.iter().map(|(query, _)| query.name()).collect();
+            let answers: Vec<_> = names
+                .iter()
+                .zip(answers.iter())
+                .map(|(name, (_, answer))| (name.as_str(), (*answer).into()))
+                .collect();
+            opts.common.print_answers(&answers);
+        }
+        Ok(answers) => {
+            for (query, answer) in answers {
+                println!("{}", query.describe(answer));
+            }
+        }
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DECK_SIZE: u128 = PART_2.deck_size;
+    const REPETITIONS: u128 = PART_2.repetitions;
+    const POSITION: u128 = 2020;
+
+    fn solve(problem: &Problem, techniques: &[Technique], check: bool) -> Result<u128, Error> {
+        Ok(solve_all(problem, &[problem.query], techniques, check)?[0])
+    }
+
+    fn parse(text: &str) -> Vec<Technique> {
+        techniques(text.as_bytes()).unwrap()
+    }
+
+    fn card_at(
+        techniques: &[Technique],
+        deck_size: u128,
+        repetitions: u128,
+        position: u128,
+    ) -> Option<u128> {
+        Shuffled::new(techniques, deck_size, repetitions).card_at(position)
+    }
+
+    fn position_of(
+        techniques: &[Technique],
+        deck_size: u128,
+        repetitions: u128,
+        card: u128,
+    ) -> u128 {
+        Shuffled::new(techniques, deck_size, repetitions).position_of(card)
+    }
+
+    fn deck(techniques: &[Technique]) -> Vec<u128> {
+        (0..10)
+            .map(|position| card_at(techniques, 10, 1, position).unwrap())
+            .collect()
+    }
+
+    const EXAMPLE: &str = "deal into new stack
+cut -2
+deal with increment 7
+cut 8
+cut -4
+deal with increment 7
+cut 3
+deal with increment 9
+deal with increment 3
+cut -1";
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            deck(&parse("deal with increment 7\ndeal into new stack\ndeal into new stack")),
+            vec![0, 3, 6, 9, 2, 5, 8, 1, 4, 7]
+        );
+        assert_eq!(
+            deck(&parse("cut 6\ndeal with increment 7\ndeal into new stack")),
+            vec![3, 0, 7, 4, 1, 8, 5, 2, 9, 6]
+        );
+        assert_eq!(
+            deck(&parse("deal with increment 7\ndeal with increment 9\ncut -2")),
+            vec![6, 3, 0, 7, 4, 1, 8, 5, 2, 9]
+        );
+        assert_eq!(deck(&parse(EXAMPLE)), vec![9, 2, 5, 8, 1, 4, 7, 0, 3, 6]);
+    }
+
+    #[test]
+    fn repeated() {
+        let techniques = parse(EXAMPLE);
+        for &repetitions in [0, 1, 2, 7, 1000, REPETITIONS].iter() {
+            assert_eq!(
+                card_at(&techniques, DECK_SIZE, repetitions, POSITION),
+                term::card_at(&techniques, DECK_SIZE, repetitions, POSITION)
+            );
+        }
+    }
+
+    #[test]
+    fn both_directions() {
+        let techniques = parse(EXAMPLE);
+        let card = card_at(&techniques, DECK_SIZE, REPETITIONS, POSITION).unwrap();
+        assert_eq!(position_of(&techniques, DECK_SIZE, REPETITIONS, card), POSITION);
+        assert_eq!(position_of(&techniques, 10, 1, 9), 0);
+        assert_eq!(position_of(&techniques, 10, 1, 6), 9);
+    }
+
+    #[test]
+    fn parts() {
+        let techniques = parse(EXAMPLE);
+        let card = solve(&PART_2, &techniques, true).unwrap();
+        assert_eq!(card, card_at(&techniques, DECK_SIZE, REPETITIONS, POSITION).unwrap());
+
+        let position = solve(&PART_1, &techniques, true).unwrap();
+        assert_eq!(card_at(&techniques, 10007, 1, position), Some(2019));
+
+        let small = Problem {
+            deck_size: 10,
+            repetitions: 1,
+            query: Query::Card(10),
+        };
+        assert!(solve(&small, &techniques, false).is_err());
+
+        let opts = Opts::from_iter(&["day-22", "--deck-size", "0"]);
+        assert!(opts.problem().is_err());
+        let opts = Opts::from_iter(&["day-22", "-p", "1", "--deck-size", "10"]);
+        assert_eq!(opts.problem().unwrap().deck_size, 10);
+    }
+
+    #[test]
+    fn batch() {
+        let techniques = parse(EXAMPLE);
+        let queries: Vec<Query> = "position 2020\ncard 2019\n position 0 "
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        assert_eq!(
+            queries,
+            vec![Query::Position(2020), Query::Card(2019), Query::Position(0)]
+        );
+
+        let answers = solve_all(&PART_2, &queries, &techniques, true).unwrap();
+        for (query, answer) in queries.iter().zip(answers) {
+            let problem = Problem {
+                query: *query,
+                ..PART_2
+            };
+            assert_eq!(solve(&problem, &techniques, false).unwrap(), answer);
+        }
+
+        let queries = [Query::Card(3), Query::Position(10)];
+        assert!(solve_all(&PART_1, &queries, &techniques, false).is_ok());
+        let small = Problem {
+            deck_size: 10,
+            ..PART_1
+        };
+        assert!(solve_all(&small, &queries, &techniques, false).is_err());
+
+        assert!("position".parse::<Query>().is_err());
+        assert!("card 1 2".parse::<Query>().is_err());
+        assert!("deal 1".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn simulated() {
+        let techniques = parse(EXAMPLE);
+        assert_eq!(
+            deck::shuffle(&techniques, 10, 1).unwrap(),
+            vec![9, 2, 5, 8, 1, 4, 7, 0, 3, 6]
+        );
+
+        for &deck_size in [11, 13, 10007].iter() {
+            for &repetitions in [1, 2, 5].iter() {
+                let problem = Problem {
+                    deck_size,
+                    repetitions,
+                    query: Query::Card(3),
+                };
+                assert_eq!(
+                    simulate(&problem, &techniques).unwrap(),
+                    solve(&problem, &techniques, false).unwrap()
+                );
+            }
+        }
+
+        assert!(deck::shuffle(&parse("deal with increment 2"), 10, 1).is_err());
+        assert!(deck::shuffle(&techniques, PART_2.deck_size, 1).is_err());
+    }
+
+    #[test]
+    fn checkpoint() {
+        let techniques = parse(EXAMPLE);
+        let once = techniques.len() as u128;
+
+        // Carry on after the first of three shuffles.
+        let partial = deck::Partial {
+            applied: once,
+            deck: deck::shuffle(&techniques, 11, 1).unwrap(),
+        };
+        let mut text = Vec::new();
+        partial.write(&mut text).unwrap();
+        let partial = deck::Partial::parse(&String::from_utf8(text).unwrap(), 11).unwrap();
+        let deck = deck::resume(&techniques, 3, partial, None).unwrap();
+        assert_eq!(deck, deck::shuffle(&techniques, 11, 3).unwrap());
+
+        // The last checkpoint saved is the finished deck.
+        let name = format!("advent-22-{}.checkpoint", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let mut checkpoint = Checkpoint::new(&path, Duration::from_secs(0));
+        let partial = deck::Partial::new(11);
+        deck::resume(&techniques, 2, partial, Some(&mut checkpoint)).unwrap();
+        let partial = deck::Partial::parse(&checkpoint.load().unwrap(), 11).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(partial.applied, 2 * once);
+        assert_eq!(partial.deck, deck::shuffle(&techniques, 11, 2).unwrap());
+        assert!(deck::resume(&techniques, 1, partial, None).is_err());
+
+        assert!(deck::Partial::parse("1 11\n0 1 2\n", 11).is_err());
+        assert!(deck::Partial::parse("1 3\n0 1 1\n", 3).is_err());
+        assert!(deck::Partial::parse("1 3\n2 0 1\n", 10).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+// The original symbolic solver, kept to cross-check the affine maps.
+
+use common::modmath::inverse;
+use crate::Technique;
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Invert,
+    Sub(u128),
+    Add(u128),
+    Mul(u128),
+}
+
+impl Operation {
+    fn to_term(self, input: Box<Term>, deck_size: u128) -> Box<Term> {
+        Box::new(match self {
+            Operation::Invert => Term::Mul(input, Box::new(Term::Value(deck_size - 1))),
+            Operation::Sub(x) => Term::Add(
+                input,
+                Box::new(Term::Mul(
+                    Box::new(Term::Value(x)),
+                    Box::new(Term::Value(deck_size - 1)),
+                )),
+            ),
+            Operation::Add(x) => Term::Add(input, Box::new(Term::Value(x))),
+            Operation::Mul(x) => Term::Mul(input, Box::new(Term::Value(x))),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Value(u128),
+    Variable(&'static str),
+    Add(Box<Term>, Box<Term>),
+    Mul(Box<Term>, Box<Term>),
+}
+
+impl Term {
+    fn normalize(self, deck_size: u128) -> Box<Self> {
+        use Term::*;
+        Box::new(match self {
+            Value(x) => Value(x),
+            Variable(x) => Variable(x),
+            Add(x, y) => {
+                let x = x.normalize(deck_size);
+                let y = y.normalize(deck_size);
+
+                if let (Value(a), Value(b)) = (&*x, &*y) {
+                    Value((a + b) % deck_size)
+                } else if let (Add(a, b), Value(c)) = (&*x, &*y) {
+                    if let Value(d) = &**b {
+                        Add(a.clone(), Box::new(Value((c + d) % deck_size)))
+                    } else {
+                        Add(x, y)
+                    }
+                } else {
+                    Add(x, y)
+                }
+            }
+            Mul(x, y) => {
+                let x = x.normalize(deck_size);
+                let y = y.normalize(deck_size);
+
+                if let Add(a, b) = *x {
+                    Add(
+                        Mul(a, y.clone()).normalize(deck_size),
+                        Mul(b, y).normalize(deck_size),
+                    )
+                } else if let (Value(a), Value(b)) = (&*x, &*y) {
+                    Value((a * b) % deck_size)
+                } else if let (Mul(a, b), Value(c)) = (&*x, &*y) {
+                    if let Value(d) = &**b {
+                        Mul(a.clone(), Box::new(Value((c * d) % deck_size)))
+                    } else {
+                        Mul(x, y)
+                    }
+                } else {
+                    Mul(x, y)
+                }
+            }
+        })
+    }
+
+    fn set(self, variable: &str, value: &Term, deck_size: u128) -> Box<Term> {
+        use Term::*;
+        match self {
+            Value(x) => Value(x),
+            Variable(x) if x == variable => value.clone(),
+            Variable(x) => Variable(x),
+            Add(x, y) => Add(
+                x.set(variable, value, deck_size),
+                y.set(variable, value, deck_size),
+            ),
+            Mul(x, y) => Mul(
+                x.set(variable, value, deck_size),
+                y.set(variable, value, deck_size),
+            ),
+        }
+        .normalize(deck_size)
+    }
+}
+
+// The operations which undo a technique.
+fn inverse_operations(technique: &Technique, deck_size: u128) -> Option<Vec<Operation>> {
+    Some(match *technique {
+        Technique::NewStack => vec![Operation::Invert, Operation::Sub(1)],
+        Technique::Cut(depth) => vec![Operation::Add(depth.rem_euclid(deck_size as i128) as u128)],
+        Technique::DealWithIncrement(increment) => {
+            vec![Operation::Mul(inverse(increment, deck_size)?)]
+        }
+    })
+}
+
+// The card which ends up at `position` after shuffling `repetitions` times.
+pub fn card_at(
+    techniques: &[Technique],
+    deck_size: u128,
+    repetitions: u128,
+    position: u128,
+) -> Option<u128> {
+    let mut term = Box::new(Term::Variable("x"));
+    for technique in techniques.iter().rev() {
+        for operation in inverse_operations(technique, deck_size)? {
+            term = operation.to_term(term, deck_size);
+        }
+    }
+
+    term = term.normalize(deck_size);
+
+    let mut num_iterations = repetitions;
+
+    let mut powers = vec![];
+
+    for index in 0.. {
+        powers.push(term.clone());
+
+        if 2u128.pow(index) > num_iterations {
+            break;
+        }
+
+        let term_2 = term.clone();
+        term = term.set("x", &term_2, deck_size);
+    }
+
+    let mut full_term = Box::new(Term::Variable("x"));
+
+    while num_iterations > 0 {
+        let mut exponent = 0;
+        while 2u128.pow(exponent + 1) < num_iterations {
+            exponent += 1;
+        }
+
+        full_term = powers[exponent as usize]
+            .clone()
+            .set("x", &full_term, deck_size);
+        num_iterations -= 2u128.pow(exponent);
+    }
+
+    match *full_term.set("x", &Term::Value(position), deck_size) {
+        Term::Value(card) => Some(card),
+        _ => None,
+    }
+}
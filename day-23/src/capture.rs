@@ -0,0 +1,46 @@
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Instant;
+
+// Record every packet sent, one JSON object per line.
+pub struct Capture {
+    output: Mutex<Box<dyn Write + Send>>,
+    start: Instant,
+    include_nat: bool,
+}
+
+impl Capture {
+    pub fn new(output: impl Write + Send + 'static, include_nat: bool) -> Self {
+        Capture {
+            output: Mutex::new(Box::new(output)),
+            start: Instant::now(),
+            include_nat,
+        }
+    }
+
+    fn write(&self, source: usize, destination: usize, x: i64, y: i64) {
+        let timestamp = self.start.elapsed().as_micros();
+        let mut output = self.output.lock().unwrap();
+        writeln!(
+            output,
+            "{{\"timestamp\": {}, \"source\": {}, \"destination\": {}, \"x\": {}, \"y\": {}}}",
+            timestamp, source, destination, x, y
+        )
+        .unwrap();
+    }
+
+    pub fn packet(&self, source: usize, destination: usize, x: i64, y: i64) {
+        self.write(source, destination, x, y);
+    }
+
+    // A packet the NAT sent to get an idle network going again.
+    pub fn nat(&self, source: usize, destination: usize, x: i64, y: i64) {
+        if self.include_nat {
+            self.write(source, destination, x, y);
+        }
+    }
+
+    pub fn flush(&self) {
+        self.output.lock().unwrap().flush().unwrap();
+    }
+}
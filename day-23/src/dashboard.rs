@@ -0,0 +1,118 @@
+use common::display::{Pane, Screen};
+
+// Computers shown in each column.
+const ROWS: usize = 25;
+const COLUMN_WIDTH: u16 = 44;
+
+pub struct NicStatus {
+    pub address: usize,
+    // Packets waiting to be read.
+    pub queued: usize,
+    pub idle: bool,
+    pub sent: usize,
+    pub received: usize,
+}
+
+pub struct NatStatus {
+    pub buffer: Option<(i64, i64)>,
+    pub last_sent: Option<(i64, i64)>,
+    pub sent: usize,
+}
+
+fn packet(packet: Option<(i64, i64)>) -> String {
+    match packet {
+        Some((x, y)) => format!("({}, {})", x, y),
+        None => "-".to_string(),
+    }
+}
+
+pub struct Dashboard<S> {
+    screen: S,
+    columns: Vec<Pane>,
+    nat: Pane,
+    status: Pane,
+}
+
+impl<S: Screen> Dashboard<S> {
+    pub fn new(mut screen: S, computers: usize) -> Self {
+        let columns: Vec<_> = (0..computers.max(1).div_ceil(ROWS))
+            .map(|column| Pane::new([column as u16 * COLUMN_WIDTH, 0], COLUMN_WIDTH))
+            .collect();
+        let width = columns.len() as u16 * COLUMN_WIDTH;
+        let height = computers.min(ROWS) as u16 + 1;
+
+        screen.clear();
+        Dashboard {
+            screen,
+            columns,
+            nat: Pane::new([0, height + 1], width),
+            status: Pane::new([0, height + 4], width),
+        }
+    }
+
+    pub fn draw(&mut self, nics: &[NicStatus], nat: &NatStatus) {
+        for (column, pane) in self.columns.iter().enumerate() {
+            pane.line(&mut self.screen, 0, "NIC  state  queued      sent  received");
+
+            let rows = nics.iter().skip(column * ROWS).take(ROWS);
+            for (row, nic) in rows.enumerate() {
+                let state = if nic.idle { "idle" } else { "busy" };
+                let text = format!(
+                    "{:>3}  {:<5}  {:>6}  {:>8}  {:>8}",
+                    nic.address, state, nic.queued, nic.sent, nic.received
+                );
+                pane.line(&mut self.screen, row as u16 + 1, &text);
+            }
+        }
+
+        self.nat.line(&mut self.screen, 0, "NAT");
+        let text = format!(
+            "buffer: {:<24} last sent: {:<24} packets sent: {}",
+            packet(nat.buffer),
+            packet(nat.last_sent),
+            nat.sent
+        );
+        self.nat.line(&mut self.screen, 1, &text);
+    }
+
+    pub fn status(&mut self, text: &str) {
+        self.status.line(&mut self.screen, 0, text);
+        let [_, y] = self.status.origin;
+        self.screen.goto([0, y + 1]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn draw() {
+        let mut output = vec![];
+        let mut dashboard = Dashboard::new(&mut output, 30);
+        let nics: Vec<_> = (0..30)
+            .map(|address| NicStatus {
+                address,
+                queued: address % 3,
+                idle: address == 27,
+                sent: address * 10,
+                received: 7,
+            })
+            .collect();
+        let nat = NatStatus {
+            buffer: Some((4, 5)),
+            last_sent: None,
+            sent: 0,
+        };
+        dashboard.draw(&nics, &nat);
+        dashboard.status("Running");
+        drop(dashboard);
+
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains(" 27  idle        0       270         7"));
+        assert!(text.contains("  3  busy        0        30         7"));
+        assert!(text.contains("buffer: (4, 5)"));
+        assert!(text.contains("last sent: -"));
+        assert!(text.contains("Running"));
+    }
+}
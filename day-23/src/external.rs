@@ -0,0 +1,426 @@
+// Computers run by an Intcode VM in another process, so that other
+// implementations can join the network and be checked against this one.
+//
+// The router starts the VM and talks to it a line at a time over its stdin and
+// stdout.  The first line the router sends is the program:
+//
+//   program 3,100,104,255,...
+//
+// After that it runs the program a slice at a time by sending
+//
+//   run N
+//
+// at which point the VM executes up to N instructions, sending
+//
+//   in           when the program wants a value, to which the router replies
+//                with "value V", or "none" if it has nothing to give, in which
+//                case the program waits at that instruction until the next run
+//   out V        when the program outputs V
+//
+// and then one of "yielded", "blocked" or "halted" to say why it stopped.  The
+// router closes the VM's stdin once the network has stopped.
+//
+// `serve` is the VM side of the protocol, for running this implementation as
+// an external VM.
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+
+use intcode::process::{Execute, Input, Output, Process, State};
+use intcode::program::Program;
+
+// Which computer to hand over to an external VM, and the command to run it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub address: usize,
+    pub command: String,
+}
+
+impl FromStr for Attachment {
+    type Err = String;
+
+    // Parse ADDRESS=COMMAND.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Expected ADDRESS=COMMAND, got {:?}", text);
+
+        let mut parts = text.splitn(2, '=');
+        let address = parts.next().unwrap();
+        let command = parts.next().filter(|command| !command.trim().is_empty());
+
+        Ok(Attachment {
+            address: address.trim().parse().map_err(|_| error())?,
+            command: command.ok_or_else(error)?.to_string(),
+        })
+    }
+}
+
+fn state_name(state: State) -> &'static str {
+    match state {
+        State::Yielded => "yielded",
+        State::Blocked => "blocked",
+        State::Complete => "halted",
+    }
+}
+
+pub struct External<I, O> {
+    name: String,
+    child: Option<Child>,
+    reader: Box<dyn BufRead>,
+    writer: Box<dyn Write>,
+    input: I,
+    output: O,
+    halted: bool,
+    failure: Option<String>,
+}
+
+impl<I: Input<i64>, O: Output<i64>> External<I, O> {
+    // Start `command` with the shell and load the program into it.
+    pub fn spawn<T: ToString>(
+        name: T,
+        command: &str,
+        program: &Program,
+        input: I,
+        output: O,
+    ) -> Result<Self, String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| format!("Can't start {:?}: {}", command, error))?;
+
+        let reader = BufReader::new(child.stdout.take().unwrap());
+        let writer = child.stdin.take().unwrap();
+        let mut external = Self::connect(name, reader, writer, program, input, output)?;
+        external.child = Some(child);
+        Ok(external)
+    }
+
+    pub fn connect<T: ToString>(
+        name: T,
+        reader: impl BufRead + 'static,
+        writer: impl Write + 'static,
+        program: &Program,
+        input: I,
+        output: O,
+    ) -> Result<Self, String> {
+        let mut external = External {
+            name: name.to_string(),
+            child: None,
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            input,
+            output,
+            halted: false,
+            failure: None,
+        };
+
+        let data: Vec<String> = program.data.iter().map(i64::to_string).collect();
+        external.send(&format!("program {}", data.join(",")))?;
+        Ok(external)
+    }
+
+    // Why the VM was given up on, if it was.
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.writer, "{}", line)
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| format!("{}: can't write to the VM: {}", self.name, error))
+    }
+
+    fn receive(&mut self) -> Result<String, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err(format!("{}: the VM exited", self.name)),
+            Ok(_) => Ok(line.trim().to_string()),
+            Err(error) => Err(format!("{}: can't read from the VM: {}", self.name, error)),
+        }
+    }
+
+    fn run(&mut self, max_instructions: usize) -> Result<State, String> {
+        self.send(&format!("run {}", max_instructions))?;
+
+        loop {
+            let line = self.receive()?;
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("in"), None, None) => {
+                    let reply = match self.input.get() {
+                        Some(value) => format!("value {}", value),
+                        None => "none".to_string(),
+                    };
+                    self.send(&reply)?;
+                }
+                (Some("out"), Some(value), None) => match value.parse() {
+                    Ok(value) => self.output.put(value),
+                    Err(_) => return Err(format!("{}: the VM output {:?}", self.name, value)),
+                },
+                (Some("yielded"), None, None) => return Ok(State::Yielded),
+                (Some("blocked"), None, None) => return Ok(State::Blocked),
+                (Some("halted"), None, None) => return Ok(State::Complete),
+                _ => return Err(format!("{}: the VM sent {:?}", self.name, line)),
+            }
+        }
+    }
+}
+
+impl<I: Input<i64>, O: Output<i64>> Execute for External<I, O> {
+    // A VM which breaks the protocol counts as having halted, see `failure`.
+    fn execute_for(&mut self, max_instructions: usize) -> State {
+        if self.halted || self.failure.is_some() {
+            return State::Complete;
+        }
+
+        match self.run(max_instructions) {
+            Ok(state) => {
+                self.halted = state == State::Complete;
+                state
+            }
+            Err(message) => {
+                self.failure = Some(message);
+                State::Complete
+            }
+        }
+    }
+}
+
+impl<I, O> Drop for External<I, O> {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+// The program's end of the protocol when it's running in `serve`.
+struct Link<R, W> {
+    reader: RefCell<R>,
+    writer: RefCell<W>,
+    failure: RefCell<Option<String>>,
+}
+
+impl<R: BufRead, W: Write> Link<R, W> {
+    fn send(&self, line: &str) -> Result<(), String> {
+        let mut writer = self.writer.borrow_mut();
+        writeln!(writer, "{}", line)
+            .and_then(|_| writer.flush())
+            .map_err(|error| format!("Can't write to the router: {}", error))
+    }
+
+    // The next line from the router, or None once it's closed the connection.
+    fn receive(&self) -> Result<Option<String>, String> {
+        let mut line = String::new();
+        match self.reader.borrow_mut().read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(line.trim().to_string())),
+            Err(error) => Err(format!("Can't read from the router: {}", error)),
+        }
+    }
+
+    fn value(&self) -> Result<Option<i64>, String> {
+        self.send("in")?;
+        let line = self
+            .receive()?
+            .ok_or("The router closed the connection while the program wanted a value")?;
+        if line == "none" {
+            return Ok(None);
+        }
+        match line.strip_prefix("value ").map(|value| value.trim().parse()) {
+            Some(Ok(value)) => Ok(Some(value)),
+            _ => Err(format!("The router sent {:?}", line)),
+        }
+    }
+
+    fn fail(&self, message: String) {
+        self.failure.borrow_mut().get_or_insert(message);
+    }
+}
+
+impl<R: BufRead, W: Write> Input<i64> for Link<R, W> {
+    fn get(&self) -> Option<i64> {
+        if self.failure.borrow().is_some() {
+            return None;
+        }
+
+        self.value().unwrap_or_else(|message| {
+            self.fail(message);
+            None
+        })
+    }
+}
+
+impl<R: BufRead, W: Write> Output<i64> for Link<R, W> {
+    fn put(&self, value: i64) {
+        if let Err(message) = self.send(&format!("out {}", value)) {
+            self.fail(message);
+        }
+    }
+}
+
+// Act as an external VM, reading the program and then running it as the
+// router asks until it closes the connection.
+pub fn serve(reader: impl BufRead, writer: impl Write) -> Result<(), String> {
+    let link = Link {
+        reader: RefCell::new(reader),
+        writer: RefCell::new(writer),
+        failure: RefCell::new(None),
+    };
+
+    let line = match link.receive()? {
+        Some(line) => line,
+        None => return Ok(()),
+    };
+    let program = match line.strip_prefix("program ") {
+        Some(data) => Program::parse(data.as_bytes())
+            .map_err(|error| format!("Can't parse the program: {:?}", error))?,
+        None => return Err(format!("Expected the program, got {:?}", line)),
+    };
+
+    let mut process = Process::new("Server", &program, &link, &link);
+    let mut halted = false;
+    while let Some(line) = link.receive()? {
+        let max_instructions = match line.strip_prefix("run ").map(|count| count.trim().parse()) {
+            Some(Ok(count)) => count,
+            _ => return Err(format!("Expected run N, got {:?}", line)),
+        };
+
+        let state = if halted {
+            State::Complete
+        } else {
+            process.execute_for(max_instructions)
+        };
+        if let Some(message) = link.failure.borrow_mut().take() {
+            return Err(message);
+        }
+        halted = state == State::Complete;
+        link.send(state_name(state))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use intcode::process::Channel;
+    use std::collections::VecDeque;
+    use std::io::{self, Cursor, Read};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::thread;
+
+    struct PipeReader {
+        receiver: Receiver<Vec<u8>>,
+        pending: VecDeque<u8>,
+    }
+
+    impl Read for PipeReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() {
+                match self.receiver.recv() {
+                    Ok(data) => self.pending.extend(data),
+                    Err(_) => return Ok(0),
+                }
+            }
+            let count = buf.len().min(self.pending.len());
+            for (byte, value) in buf.iter_mut().zip(self.pending.drain(..count)) {
+                *byte = value;
+            }
+            Ok(count)
+        }
+    }
+
+    struct PipeWriter(Sender<Vec<u8>>);
+
+    impl Write for PipeWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .send(buf.to_vec())
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "closed"))?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn pipe() -> (PipeWriter, BufReader<PipeReader>) {
+        let (sender, receiver) = channel();
+        let reader = PipeReader {
+            receiver,
+            pending: VecDeque::new(),
+        };
+        (PipeWriter(sender), BufReader::new(reader))
+    }
+
+    // Reads a value, then outputs it twice and its double, until it reads 0.
+    const ECHO: &str = "3,20,1006,20,19,4,20,4,20,102,2,20,21,4,21,1105,1,0,0,99";
+
+    #[test]
+    fn attachment() {
+        let attachment: Attachment = "12=intcode --serve".parse().unwrap();
+        assert_eq!(attachment.address, 12);
+        assert_eq!(attachment.command, "intcode --serve");
+        assert!("12".parse::<Attachment>().is_err());
+        assert!("12= ".parse::<Attachment>().is_err());
+        assert!("x=intcode".parse::<Attachment>().is_err());
+    }
+
+    #[test]
+    fn serve() {
+        let (to_vm, vm_input) = pipe();
+        let (vm_output, from_vm) = pipe();
+        let server = thread::spawn(move || super::serve(vm_input, vm_output));
+
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let input = Channel::new();
+        let output = Channel::new();
+        let mut vm = External::connect("VM", from_vm, to_vm, &program, &input, &output).unwrap();
+
+        input.put(3);
+        assert_eq!(vm.execute_for(100), State::Blocked);
+        input.put(-4);
+        assert_eq!(vm.execute_for(3), State::Yielded);
+        assert_eq!(vm.execute_for(100), State::Blocked);
+        input.put(0);
+        assert_eq!(vm.execute_for(100), State::Complete);
+        assert_eq!(vm.execute_for(100), State::Complete);
+        assert_eq!(vm.failure(), None);
+
+        drop(vm);
+        assert_eq!(Vec::from(output), vec![3, 3, 6, -4, -4, -8]);
+        assert_eq!(server.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn broken_protocol() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let input = Channel::new();
+        let output = Channel::new();
+
+        let replies = Cursor::new("out 5\nout five\n");
+        let mut vm = External::connect("VM", replies, io::sink(), &program, &input, &output)
+            .unwrap();
+        assert_eq!(vm.execute_for(100), State::Complete);
+        assert_eq!(vm.failure(), Some("VM: the VM output \"five\""));
+        drop(vm);
+        assert_eq!(output.get(), Some(5));
+        assert_eq!(output.get(), None);
+
+        let mut vm =
+            External::connect("VM", Cursor::new(""), io::sink(), &program, &input, &input)
+                .unwrap();
+        assert_eq!(vm.execute_for(100), State::Complete);
+        assert_eq!(vm.failure(), Some("VM: the VM exited"));
+
+        let router = Cursor::new("program 99\nstep\n");
+        assert!(super::serve(router, io::sink()).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+// Simulate an imperfect network, where packets can be held back for a number
+// of rounds on each link or dropped altogether.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Packet {
+    pub source: usize,
+    pub destination: usize,
+    pub x: i64,
+    pub y: i64,
+}
+
+// How many rounds packets from one address to another are held back for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkDelay {
+    pub source: usize,
+    pub destination: usize,
+    pub rounds: usize,
+}
+
+impl FromStr for LinkDelay {
+    type Err = String;
+
+    // Parse SOURCE:DESTINATION=ROUNDS.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Expected SOURCE:DESTINATION=ROUNDS, got {:?}", text);
+        let parse = |number: &str| number.trim().parse().map_err(|_| error());
+
+        let mut parts = text.splitn(2, '=');
+        let link = parts.next().unwrap();
+        let rounds = parts.next().ok_or_else(error)?;
+        let mut addresses = link.splitn(2, ':');
+        let source = addresses.next().unwrap();
+        let destination = addresses.next().ok_or_else(error)?;
+
+        Ok(LinkDelay {
+            source: parse(source)?,
+            destination: parse(destination)?,
+            rounds: parse(rounds)?,
+        })
+    }
+}
+
+// SplitMix64, which is good enough to decide which packets to drop and gives
+// the same drops for the same seed.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in [0, 1).
+    fn probability(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    pub sent: usize,
+    pub delivered: usize,
+    pub delayed: usize,
+    pub dropped: usize,
+    // Packets identical to one the same computer sent before.
+    pub retransmitted: usize,
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "sent: {}, delivered: {}, delayed: {}, dropped: {}, retransmitted: {}",
+            self.sent, self.delivered, self.delayed, self.dropped, self.retransmitted
+        )
+    }
+}
+
+pub struct Faults {
+    delay: usize,
+    links: HashMap<(usize, usize), usize>,
+    drop: f64,
+    random: Random,
+    round: usize,
+    // Delayed packets, with the round each is due to arrive in.
+    in_transit: Vec<(usize, Packet)>,
+    seen: HashSet<Packet>,
+    pub statistics: Statistics,
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Faults::new(0, &[], 0.0, 0).unwrap()
+    }
+}
+
+impl Faults {
+    // Delay every packet by `delay` rounds, or by the rounds given for its
+    // link, and drop each with probability `drop`.
+    pub fn new(delay: usize, links: &[LinkDelay], drop: f64, seed: u64) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&drop) {
+            return Err(format!("Drop probability {} isn't between 0 and 1", drop));
+        }
+
+        Ok(Faults {
+            delay,
+            links: links
+                .iter()
+                .map(|link| ((link.source, link.destination), link.rounds))
+                .collect(),
+            drop,
+            random: Random(seed),
+            round: 0,
+            in_transit: vec![],
+            seen: HashSet::new(),
+            statistics: Statistics::default(),
+        })
+    }
+
+    // Send a packet, returning it if it should be delivered straight away.
+    pub fn send(&mut self, packet: Packet) -> Option<Packet> {
+        self.statistics.sent += 1;
+        if !self.seen.insert(packet) {
+            self.statistics.retransmitted += 1;
+        }
+
+        if self.drop > 0.0 && self.random.probability() < self.drop {
+            self.statistics.dropped += 1;
+            return None;
+        }
+
+        let delay = self
+            .links
+            .get(&(packet.source, packet.destination))
+            .copied()
+            .unwrap_or(self.delay);
+        if delay > 0 {
+            self.statistics.delayed += 1;
+            self.in_transit.push((self.round + delay, packet));
+            None
+        } else {
+            self.statistics.delivered += 1;
+            Some(packet)
+        }
+    }
+
+    // Move on to the next round, returning the packets due to arrive in it in
+    // the order they were sent.
+    pub fn tick(&mut self) -> Vec<Packet> {
+        self.round += 1;
+        let round = self.round;
+        let (due, in_transit) = self
+            .in_transit
+            .drain(..)
+            .partition(|&(arrival, _)| arrival <= round);
+        self.in_transit = in_transit;
+
+        let due: Vec<_> = due.into_iter().map(|(_, packet)| packet).collect();
+        self.statistics.delivered += due.len();
+        due
+    }
+
+    pub fn in_transit(&self) -> usize {
+        self.in_transit.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn packet(source: usize, destination: usize, y: i64) -> Packet {
+        Packet {
+            source,
+            destination,
+            x: 0,
+            y,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "3:255=10".parse(),
+            Ok(LinkDelay {
+                source: 3,
+                destination: 255,
+                rounds: 10
+            })
+        );
+        assert!("3:255".parse::<LinkDelay>().is_err());
+        assert!("3=10".parse::<LinkDelay>().is_err());
+        assert!("a:1=2".parse::<LinkDelay>().is_err());
+    }
+
+    #[test]
+    fn delays() {
+        let link = LinkDelay {
+            source: 1,
+            destination: 2,
+            rounds: 3,
+        };
+        let mut faults = Faults::new(1, &[link], 0.0, 0).unwrap();
+
+        assert_eq!(faults.send(packet(1, 2, 10)), None);
+        assert_eq!(faults.send(packet(2, 1, 20)), None);
+        assert_eq!(faults.in_transit(), 2);
+        assert_eq!(faults.tick(), vec![packet(2, 1, 20)]);
+        assert_eq!(faults.tick(), vec![]);
+        assert_eq!(faults.tick(), vec![packet(1, 2, 10)]);
+        assert_eq!(faults.in_transit(), 0);
+
+        let mut faults = Faults::new(0, &[link], 0.0, 0).unwrap();
+        assert_eq!(faults.send(packet(2, 1, 20)), Some(packet(2, 1, 20)));
+    }
+
+    #[test]
+    fn drops() {
+        assert!(Faults::new(0, &[], 1.5, 0).is_err());
+
+        let mut faults = Faults::new(0, &[], 1.0, 0).unwrap();
+        assert_eq!(faults.send(packet(1, 2, 10)), None);
+        assert_eq!(faults.in_transit(), 0);
+
+        // The same seed drops the same packets.
+        let dropped = |seed| {
+            let mut faults = Faults::new(0, &[], 0.5, seed).unwrap();
+            (0..100)
+                .filter(|&y| faults.send(packet(1, 2, y)).is_none())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(dropped(7), dropped(7));
+        assert_ne!(dropped(7), dropped(8));
+        assert!((25..75).contains(&dropped(7).len()));
+    }
+
+    #[test]
+    fn statistics() {
+        let mut faults = Faults::new(0, &[], 0.0, 0).unwrap();
+        faults.send(packet(1, 2, 10));
+        faults.send(packet(1, 2, 10));
+        faults.send(packet(3, 2, 10));
+        assert_eq!(
+            faults.statistics,
+            Statistics {
+                sent: 3,
+                delivered: 3,
+                delayed: 0,
+                dropped: 0,
+                retransmitted: 1,
+            }
+        );
+    }
+}
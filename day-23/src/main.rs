@@ -0,0 +1,870 @@
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{stdin, stdout, BufWriter};
+use std::path::PathBuf;
+use std::sync::Arc;
+use structopt::StructOpt;
+use termion::cursor;
+use termion::raw::IntoRawMode;
+
+mod capture;
+mod dashboard;
+mod external;
+mod faults;
+mod threaded;
+mod utils;
+
+use capture::Capture;
+use common::display::{self, Screen};
+use common::{CommonOpts, Events, Flag};
+use dashboard::{Dashboard, NatStatus, NicStatus};
+use external::{Attachment, External};
+use faults::{Faults, LinkDelay, Packet, Statistics};
+use intcode::process::{Channel, Execute, Input, Output, Process, State};
+use intcode::program::Program;
+use intcode::scheduler::Scheduler;
+use std::rc::Rc;
+use utils::Ticker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Addresses {
+    // The computers take consecutive addresses starting from this one.
+    first: usize,
+    computers: usize,
+    nat: usize,
+}
+
+impl Default for Addresses {
+    fn default() -> Self {
+        Addresses {
+            first: 0,
+            computers: 50,
+            nat: 255,
+        }
+    }
+}
+
+impl Addresses {
+    fn new(first: usize, computers: usize, nat: usize) -> Result<Self, Error> {
+        let addresses = Addresses {
+            first,
+            computers,
+            nat,
+        };
+
+        if computers == 0 {
+            Err("The network needs at least one computer".into())
+        } else if addresses.computer(nat).is_some() {
+            Err(format!("NAT address {} is also a computer's address", nat).into())
+        } else {
+            Ok(addresses)
+        }
+    }
+
+    fn address(&self, index: usize) -> usize {
+        self.first + index
+    }
+
+    // Which computer has this address, if any.
+    fn computer(&self, address: usize) -> Option<usize> {
+        address
+            .checked_sub(self.first)
+            .filter(|&index| index < self.computers)
+    }
+}
+
+// Rounds the network can sit idle with nothing for the NAT to send before
+// giving up on it, which happens once packets start getting dropped.
+const STALL_ROUNDS: usize = 1000;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+struct Nat {
+    buffer: Cell<Option<(i64, i64)>>,
+    last_sent: Cell<Option<(i64, i64)>>,
+    first_received: Cell<Option<(i64, i64)>>,
+    repeated: Cell<Option<(i64, i64)>>,
+    sent: Cell<usize>,
+}
+
+impl Nat {
+    fn new() -> Self {
+        Nat {
+            buffer: Cell::new(None),
+            last_sent: Cell::new(None),
+            first_received: Cell::new(None),
+            repeated: Cell::new(None),
+            sent: Cell::new(0),
+        }
+    }
+
+    fn receive(&self, x: i64, y: i64) {
+        if self.first_received.get().is_none() {
+            self.first_received.set(Some((x, y)));
+        }
+        self.buffer.set(Some((x, y)));
+    }
+
+    fn poll(&self, router: &Router) {
+        if router.network_idle() {
+            if let Some((x, y)) = self.buffer.get() {
+                router.send(router.addresses.nat, router.addresses.first, x, y);
+
+                if self.last_sent.get() == Some((x, y)) && self.repeated.get().is_none() {
+                    self.repeated.set(Some((x, y)));
+                }
+                self.last_sent.set(Some((x, y)));
+                self.sent.set(self.sent.get() + 1);
+            }
+        }
+    }
+}
+
+struct Router {
+    interfaces: RefCell<Vec<Rc<Nic>>>,
+    nat: Nat,
+    addresses: Addresses,
+    capture: Option<Arc<Capture>>,
+    events: Option<RefCell<Events>>,
+    faults: RefCell<Faults>,
+    idle_rounds: Cell<usize>,
+}
+
+impl Router {
+    fn new(
+        addresses: Addresses,
+        capture: Option<Arc<Capture>>,
+        events: Option<Events>,
+        faults: Faults,
+    ) -> Rc<Self> {
+        Rc::new(Router {
+            interfaces: RefCell::new(vec![]),
+            nat: Nat::new(),
+            addresses,
+            capture,
+            events: events.map(RefCell::new),
+            faults: RefCell::new(faults),
+            idle_rounds: Cell::new(0),
+        })
+    }
+
+    fn new_interface(self: Rc<Self>) -> Rc<Nic> {
+        let mut interfaces = self.interfaces.borrow_mut();
+        let address = self.addresses.address(interfaces.len());
+        let interface = Rc::new(Nic::new(self.clone(), address as i64));
+        interfaces.push(interface.clone());
+        interface
+    }
+
+    fn send(&self, source: usize, destination: usize, x: i64, y: i64) {
+        match &self.capture {
+            Some(capture) if source == self.addresses.nat => capture.nat(source, destination, x, y),
+            Some(capture) => capture.packet(source, destination, x, y),
+            None => {}
+        }
+
+        if let Some(events) = &self.events {
+            let fields = [
+                ("source", source.into()),
+                ("destination", destination.into()),
+                ("x", x.into()),
+                ("y", y.into()),
+            ];
+            let _ = events.borrow_mut().write("packet", &fields);
+        }
+
+        let packet = Packet {
+            source,
+            destination,
+            x,
+            y,
+        };
+        let delivered = self.faults.borrow_mut().send(packet);
+        if let Some(packet) = delivered {
+            self.deliver(packet);
+        }
+    }
+
+    fn deliver(&self, packet: Packet) {
+        let interfaces = self.interfaces.borrow();
+        if packet.destination == self.addresses.nat {
+            self.nat.receive(packet.x, packet.y);
+        } else if let Some(index) = self.addresses.computer(packet.destination) {
+            interfaces[index].receive(packet.x, packet.y);
+        }
+    }
+
+    fn network_idle(&self) -> bool {
+        self.faults.borrow().in_transit() == 0
+            && self
+                .interfaces
+                .borrow()
+                .iter()
+                .all(|interface| interface.is_idle())
+    }
+
+    fn poll(&self) {
+        let arrived = self.faults.borrow_mut().tick();
+        for packet in arrived {
+            self.deliver(packet);
+        }
+
+        self.nat.poll(self);
+
+        if self.network_idle() && self.nat.buffer.get().is_none() {
+            self.idle_rounds.set(self.idle_rounds.get() + 1);
+        } else {
+            self.idle_rounds.set(0);
+        }
+    }
+
+    // Whether the network has gone quiet without anything reaching the NAT.
+    fn stalled(&self) -> bool {
+        self.idle_rounds.get() >= STALL_ROUNDS
+    }
+
+    // The router is never dropped, as the interfaces point back to it, so
+    // the events have to be written out by hand.
+    fn flush_events(&self) {
+        if let Some(events) = &self.events {
+            let _ = events.borrow_mut().flush();
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.faults.borrow().statistics.clone()
+    }
+
+    // The Y value of the packet which answers the given part, once it's been
+    // sent.
+    fn answer(&self, part: u8) -> Option<i64> {
+        let packet = if part == 1 {
+            self.nat.first_received.get()
+        } else {
+            self.nat.repeated.get()
+        };
+        packet.map(|(_, y)| y)
+    }
+
+    fn nic_statuses(&self) -> Vec<NicStatus> {
+        self.interfaces
+            .borrow()
+            .iter()
+            .map(|interface| NicStatus {
+                address: interface.index as usize,
+                queued: interface.input_buffer.len() / 2,
+                idle: interface.is_idle(),
+                sent: interface.sent.get(),
+                received: interface.received.get(),
+            })
+            .collect()
+    }
+
+    fn nat_status(&self) -> NatStatus {
+        NatStatus {
+            buffer: self.nat.buffer.get(),
+            last_sent: self.nat.last_sent.get(),
+            sent: self.nat.sent.get(),
+        }
+    }
+}
+
+struct Nic {
+    index: i64,
+    got_index: Cell<bool>,
+    got_input: Cell<bool>,
+    router: Rc<Router>,
+    input_buffer: Channel<i64>,
+    output_buffer: Channel<i64>,
+    sent: Cell<usize>,
+    received: Cell<usize>,
+}
+
+impl Nic {
+    fn new(router: Rc<Router>, index: i64) -> Self {
+        Nic {
+            index,
+            got_index: Cell::new(false),
+            got_input: Cell::new(true),
+            router,
+            input_buffer: Channel::new(),
+            output_buffer: Channel::new(),
+            sent: Cell::new(0),
+            received: Cell::new(0),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.input_buffer.is_empty() && self.output_buffer.is_empty() && !self.got_input.get()
+    }
+
+    fn receive(&self, x: i64, y: i64) {
+        self.input_buffer.put(x);
+        self.input_buffer.put(y);
+        self.received.set(self.received.get() + 1);
+    }
+}
+
+impl Input<i64> for Nic {
+    fn get(&self) -> Option<i64> {
+        if !self.got_index.get() {
+            self.got_index.set(true);
+            Some(self.index)
+        } else if let Some(value) = self.input_buffer.get() {
+            self.got_input.set(true);
+            Some(value)
+        } else {
+            self.got_input.set(false);
+            Some(-1)
+        }
+    }
+}
+
+impl Output<i64> for Nic {
+    fn put(&self, value: i64) {
+        self.output_buffer.put(value);
+
+        if self.output_buffer.len() == 3 {
+            let packet = self.output_buffer.drain();
+            self.router.send(
+                self.index as usize,
+                packet[0] as usize,
+                packet[1],
+                packet[2],
+            );
+            self.sent.set(self.sent.get() + 1);
+        }
+    }
+}
+
+// A computer on the network, run here or by an external VM.
+enum Computer {
+    Local(Process<Rc<Nic>, Rc<Nic>>),
+    External(External<Rc<Nic>, Rc<Nic>>),
+}
+
+impl Computer {
+    fn start(program: &Program, nic: Rc<Nic>, external: &[Attachment]) -> Result<Self, Error> {
+        let name = format!("Computer {}", nic.index);
+        match external
+            .iter()
+            .find(|attachment| attachment.address as i64 == nic.index)
+        {
+            Some(attachment) => {
+                let vm = External::spawn(name, &attachment.command, program, nic.clone(), nic)?;
+                Ok(Computer::External(vm))
+            }
+            None => Ok(Computer::Local(Process::new(
+                name,
+                program,
+                nic.clone(),
+                nic,
+            ))),
+        }
+    }
+
+    fn failure(&self) -> Option<&str> {
+        match self {
+            Computer::Local(_) => None,
+            Computer::External(vm) => vm.failure(),
+        }
+    }
+}
+
+impl Execute for Computer {
+    fn execute_for(&mut self, max_instructions: usize) -> State {
+        match self {
+            Computer::Local(process) => process.execute_for(max_instructions),
+            Computer::External(vm) => vm.execute_for(max_instructions),
+        }
+    }
+}
+
+// Run the network until the answer to the given part has been sent, calling
+// `progress` after each round, which can stop the network early by returning
+// true.  Also returns statistics about the packets sent.
+#[allow(clippy::too_many_arguments)]
+fn run_network(
+    program: &Program,
+    part: u8,
+    addresses: Addresses,
+    external: &[Attachment],
+    capture: Option<Arc<Capture>>,
+    events: Option<Events>,
+    faults: Faults,
+    mut progress: impl FnMut(&Router) -> bool,
+) -> (Result<i64, Error>, Statistics) {
+    let router = Router::new(addresses, capture, events, faults);
+    let computers: Result<Vec<_>, _> = (0..addresses.computers)
+        .map(|_| Computer::start(program, router.clone().new_interface(), external))
+        .collect();
+    let mut computers = match computers {
+        Ok(computers) => computers,
+        Err(error) => return (Err(error), router.statistics()),
+    };
+
+    let mut scheduler = Scheduler::new().time_slice(100).on_round(|| {
+        router.poll();
+        progress(&router) || router.answer(part).is_some() || router.stalled()
+    });
+    for computer in computers.iter_mut() {
+        scheduler.add(computer);
+    }
+    scheduler.run();
+    router.flush_events();
+
+    if let Some(message) = computers.iter().find_map(Computer::failure) {
+        return (Err(message.into()), router.statistics());
+    }
+
+    let answer = match router.answer(part) {
+        Some(y) => Ok(y),
+        None if router.stalled() => {
+            Err("The network went idle before anything reached the NAT".into())
+        }
+        None => Err("Every computer stopped before the answer was sent".into()),
+    };
+    (answer, router.statistics())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn show_network(
+    program: &Program,
+    part: u8,
+    addresses: Addresses,
+    external: &[Attachment],
+    capture: Option<Arc<Capture>>,
+    events: Option<Events>,
+    faults: Faults,
+    screen: impl Screen,
+    speed: Option<u64>,
+) -> (Result<i64, Error>, Statistics) {
+    let keys = display::read_keys();
+    let mut dashboard = Dashboard::new(screen, addresses.computers);
+    let mut ticker = speed.map(Ticker::with_rate);
+    let mut quit = false;
+
+    let (answer, statistics) = run_network(
+        program,
+        part,
+        addresses,
+        external,
+        capture,
+        events,
+        faults,
+        |router| {
+            dashboard.draw(&router.nic_statuses(), &router.nat_status());
+            dashboard.status("Running, q to quit");
+
+            if let Some(ticker) = ticker.as_mut() {
+                ticker.wait();
+            }
+
+            quit = keys.try_iter().any(|key| display::is_quit(&key));
+            quit
+        },
+    );
+
+    if quit {
+        return (Err("Stopped before the answer was sent".into()), statistics);
+    }
+
+    match &answer {
+        Ok(y) => dashboard.status(&format!("Answer: {}, q to quit", y)),
+        Err(Error(message)) => dashboard.status(&format!("{}, q to quit", message)),
+    }
+
+    for key in keys.iter() {
+        if display::is_quit(&key) {
+            break;
+        }
+    }
+
+    (answer, statistics)
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Number of computers on the network
+    #[structopt(short = "n", long, default_value = "50")]
+    computers: usize,
+
+    /// Address of the first computer, the others follow on from it
+    #[structopt(long, default_value = "0")]
+    first_address: usize,
+
+    /// Address of the NAT
+    #[structopt(long, default_value = "255")]
+    nat_address: usize,
+
+    /// Run each computer on its own thread
+    #[structopt(short, long)]
+    threads: bool,
+
+    /// Write every packet sent to this file, one JSON object per line
+    #[structopt(short, long, parse(from_os_str))]
+    capture: Option<PathBuf>,
+
+    /// Include the packets the NAT sends in the capture
+    #[structopt(long)]
+    capture_nat: bool,
+
+    /// Write every packet sent, including the NAT's, to this file as JSON
+    /// lines
+    #[structopt(long, parse(from_os_str), conflicts_with = "threads")]
+    events: Option<PathBuf>,
+
+    /// Show the state of each computer and the NAT as the network runs, at
+    /// --speed rounds per second
+    #[structopt(long, conflicts_with_all = &["threads", "quiet", "json"])]
+    dashboard: bool,
+
+    /// Rounds to hold back every packet for before delivering it
+    #[structopt(long, default_value = "0", conflicts_with = "threads")]
+    delay: usize,
+
+    /// Rounds to hold back packets on one link for, as SOURCE:DESTINATION=ROUNDS
+    #[structopt(long, number_of_values = 1, conflicts_with = "threads")]
+    link_delay: Vec<LinkDelay>,
+
+    /// Probability of dropping each packet
+    #[structopt(long, default_value = "0", conflicts_with = "threads")]
+    drop: f64,
+
+    /// Seed for choosing which packets to drop
+    #[structopt(long, default_value = "0")]
+    seed: u64,
+
+    /// Print statistics about the packets sent once the network stops
+    #[structopt(long, conflicts_with = "threads")]
+    stats: bool,
+
+    /// Run one computer with an external Intcode VM, as ADDRESS=COMMAND
+    #[structopt(long, number_of_values = 1, conflicts_with = "threads")]
+    external: Vec<Attachment>,
+
+    /// Act as an external VM for another network, on stdin and stdout
+    #[structopt(long)]
+    serve: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    if let Err(message) = opts.common.unsupported(&[Flag::Debug]) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+
+    // The router sends the program, so don't read it from stdin.
+    if opts.serve {
+        let stdin = stdin();
+        if let Err(message) = external::serve(stdin.lock(), stdout().lock()) {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let program = match opts.common.input() {
+        Ok(input) => Program::parse(input).unwrap(),
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    let part = opts.common.part(2);
+
+    let addresses = match Addresses::new(opts.first_address, opts.computers, opts.nat_address) {
+        Ok(addresses) => addresses,
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(attachment) = opts
+        .external
+        .iter()
+        .find(|attachment| addresses.computer(attachment.address).is_none())
+    {
+        eprintln!("{} isn't the address of a computer", attachment.address);
+        std::process::exit(1);
+    }
+
+    let capture = match opts.capture.as_ref().map(File::create) {
+        Some(Ok(file)) => Some(Arc::new(Capture::new(
+            BufWriter::new(file),
+            opts.capture_nat,
+        ))),
+        Some(Err(error)) => {
+            eprintln!("Can't create capture file: {}", error);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    let events = match opts.events.as_ref().map(|path| Events::create(path)) {
+        Some(Ok(events)) => Some(events),
+        Some(Err(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    let faults = match Faults::new(opts.delay, &opts.link_delay, opts.drop, opts.seed) {
+        Ok(faults) => faults,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    let (answer, statistics) = if opts.dashboard {
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        show_network(
+            &program,
+            part,
+            addresses,
+            &opts.external,
+            capture.clone(),
+            events,
+            faults,
+            screen,
+            opts.common.speed(),
+        )
+    } else if opts.threads {
+        let answer = threaded::run(&program, part, addresses, capture.clone())
+            .ok_or_else(|| "Every computer stopped before the answer was sent".into());
+        (answer, Statistics::default())
+    } else {
+        run_network(
+            &program,
+            part,
+            addresses,
+            &opts.external,
+            capture.clone(),
+            events,
+            faults,
+            |_| false,
+        )
+    };
+
+    if let Some(capture) = capture {
+        capture.flush();
+    }
+
+    if opts.stats {
+        eprintln!("{}", statistics);
+    }
+
+    match answer {
+        Ok(y) => opts.common.print_answers(&[("y", y.into())]),
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(
+        program: &Program,
+        part: u8,
+        addresses: Addresses,
+        capture: Option<Arc<Capture>>,
+    ) -> Result<i64, Error> {
+        run_network(
+            program,
+            part,
+            addresses,
+            &[],
+            capture,
+            None,
+            Faults::default(),
+            |_| false,
+        )
+        .0
+    }
+
+    // Each computer sends (address, 100 + address) to the NAT and then sits
+    // waiting for packets.
+    const NIC: &str = "3,100,104,255,4,100,1001,100,100,102,4,102,3,101,1105,1,12";
+
+    #[test]
+    fn parts() {
+        let program = Program::parse(NIC.as_bytes()).unwrap();
+        assert_eq!(run(&program, 1, Addresses::default(), None).unwrap(), 100);
+        assert_eq!(run(&program, 2, Addresses::default(), None).unwrap(), 149);
+    }
+
+    // The first computer starts a packet which each computer passes on to the
+    // next, adding one to Y, until the last passes it to the NAT.  The first
+    // computer ignores anything sent to it.
+    const CHAIN: &str = "3,100,1008,100,0,103,1006,103,20,104,1,104,0,104,7,3,101,1105,1,15,\
+        3,101,1008,101,-1,103,1005,103,20,3,102,1001,100,1,104,1008,104,50,103,1006,103,46,\
+        1101,255,0,104,4,104,4,101,1001,102,1,105,4,105,1105,1,20";
+
+    fn chain(addresses: Addresses) -> Program {
+        let mut program = Program::parse(CHAIN.as_bytes()).unwrap();
+        program.data[4] = addresses.first as i64;
+        program.data[10] = addresses.first as i64 + 1;
+        program.data[37] = (addresses.first + addresses.computers) as i64;
+        program.data[43] = addresses.nat as i64;
+        program
+    }
+
+    #[test]
+    fn threads() {
+        let program = Program::parse(CHAIN.as_bytes()).unwrap();
+        for &part in [1, 2].iter() {
+            assert_eq!(run(&program, part, Addresses::default(), None).unwrap(), 56);
+            assert_eq!(
+                threaded::run(&program, part, Addresses::default(), None),
+                Some(56)
+            );
+        }
+
+        let program = Program::parse(NIC.as_bytes()).unwrap();
+        let y = threaded::run(&program, 2, Addresses::default(), None).unwrap();
+        assert!((100..150).contains(&y));
+        let program = Program::parse("3,100,99".as_bytes()).unwrap();
+        assert_eq!(threaded::run(&program, 2, Addresses::default(), None), None);
+    }
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn capture() {
+        let program = Program::parse(CHAIN.as_bytes()).unwrap();
+        for &include_nat in [false, true].iter() {
+            let output = Shared::default();
+            let capture = Arc::new(Capture::new(output.clone(), include_nat));
+            run(&program, 2, Addresses::default(), Some(capture)).unwrap();
+
+            let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+            let lines: Vec<&str> = text.lines().collect();
+            assert!(lines[0].starts_with("{\"timestamp\": "));
+            assert!(lines[0].ends_with("\"source\": 0, \"destination\": 1, \"x\": 0, \"y\": 7}"));
+            assert!(lines[49].ends_with("\"destination\": 255, \"x\": 0, \"y\": 56}"));
+
+            let from_nat = lines
+                .iter()
+                .filter(|line| line.contains("\"source\": 255"))
+                .count();
+            assert_eq!(from_nat, if include_nat { 2 } else { 0 });
+            assert_eq!(lines.len(), 50 + from_nat);
+        }
+    }
+
+    #[test]
+    fn events() {
+        let program = Program::parse(CHAIN.as_bytes()).unwrap();
+        let path = std::env::temp_dir().join(format!("advent-23-{}.events", std::process::id()));
+        let events = Events::create(&path).unwrap();
+        run_network(
+            &program,
+            2,
+            Addresses::default(),
+            &[],
+            None,
+            Some(events),
+            Faults::default(),
+            |_| false,
+        )
+        .0
+        .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("{\"timestamp\": "));
+        assert!(lines[0].ends_with(
+            "\"event\": \"packet\", \"source\": 0, \"destination\": 1, \"x\": 0, \"y\": 7}"
+        ));
+        let from_nat = lines
+            .iter()
+            .filter(|line| line.contains("\"source\": 255"))
+            .count();
+        assert_eq!(from_nat, 2);
+        assert_eq!(lines.len(), 52);
+    }
+
+    #[test]
+    fn addresses() {
+        let addresses = Addresses::new(10, 5, 3).unwrap();
+        assert_eq!(addresses.computer(10), Some(0));
+        assert_eq!(addresses.computer(14), Some(4));
+        assert_eq!(addresses.computer(15), None);
+        assert_eq!(addresses.computer(3), None);
+        assert!(Addresses::new(10, 5, 12).is_err());
+        assert!(Addresses::new(0, 0, 255).is_err());
+
+        let program = chain(addresses);
+        for &part in [1, 2].iter() {
+            assert_eq!(run(&program, part, addresses, None).unwrap(), 11);
+            assert_eq!(threaded::run(&program, part, addresses, None), Some(11));
+        }
+        let program = Program::parse(CHAIN.as_bytes()).unwrap();
+        assert_eq!(chain(Addresses::default()).data, program.data);
+    }
+
+    #[test]
+    fn faults() {
+        let program = Program::parse(CHAIN.as_bytes()).unwrap();
+        let run_faults = |part, faults| {
+            run_network(
+                &program,
+                part,
+                Addresses::default(),
+                &[],
+                None,
+                None,
+                faults,
+                |_| false,
+            )
+        };
+
+        let link = "10:11=20".parse().unwrap();
+        let faults = Faults::new(3, &[link], 0.0, 0).unwrap();
+        let (answer, statistics) = run_faults(2, faults);
+        assert_eq!(answer.unwrap(), 56);
+        assert_eq!(statistics.delayed, 52);
+        assert_eq!(statistics.retransmitted, 1);
+
+        let (answer, statistics) = run_faults(1, Faults::new(0, &[], 1.0, 0).unwrap());
+        assert!(answer.is_err());
+        assert_eq!(statistics.dropped, 1);
+        assert_eq!(statistics.delivered, 0);
+    }
+
+    #[test]
+    fn stops() {
+        let program = Program::parse("3,100,99".as_bytes()).unwrap();
+        assert!(run(&program, 1, Addresses::default(), None).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+// Run each computer on its own thread, passing packets over channels.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use intcode::process::{Execute, Input, Output, Process, State};
+use intcode::program::Program;
+
+use crate::capture::Capture;
+use crate::Addresses;
+
+// How many times in a row a computer has to find no packets waiting before it
+// counts as idle.
+const IDLE_READS: usize = 2;
+
+#[derive(Default)]
+struct Nat {
+    buffer: Option<(i64, i64)>,
+    last_sent: Option<(i64, i64)>,
+    first_received: Option<(i64, i64)>,
+    repeated: Option<(i64, i64)>,
+}
+
+// Everything the NAT needs to decide whether the network is idle, kept behind
+// one lock so it sees a consistent picture.
+struct Status {
+    // Empty reads by each computer since it last sent or received anything.
+    idle_reads: Vec<usize>,
+    // Packets sent but not yet read in full.
+    in_flight: usize,
+    finished: Vec<bool>,
+    running: usize,
+    stopped: bool,
+    nat: Nat,
+}
+
+impl Status {
+    fn is_idle(&self) -> bool {
+        self.in_flight == 0 && self.idle_reads.iter().all(|&reads| reads >= IDLE_READS)
+    }
+
+    fn answer(&self, part: u8) -> Option<i64> {
+        let packet = if part == 1 {
+            self.nat.first_received
+        } else {
+            self.nat.repeated
+        };
+        packet.map(|(_, y)| y)
+    }
+}
+
+struct Network {
+    addresses: Addresses,
+    senders: Vec<Sender<(i64, i64)>>,
+    status: Mutex<Status>,
+    changed: Condvar,
+    capture: Option<Arc<Capture>>,
+}
+
+impl Network {
+    fn send(&self, status: &mut Status, source: usize, destination: usize, x: i64, y: i64) {
+        match &self.capture {
+            Some(capture) if source == self.addresses.nat => {
+                capture.nat(source, destination, x, y)
+            }
+            Some(capture) => capture.packet(source, destination, x, y),
+            None => {}
+        }
+
+        let computer = self.addresses.computer(destination);
+        if destination == self.addresses.nat {
+            if status.nat.first_received.is_none() {
+                status.nat.first_received = Some((x, y));
+            }
+            status.nat.buffer = Some((x, y));
+            self.changed.notify_all();
+        } else if let Some(index) = computer.filter(|&index| !status.finished[index]) {
+            self.senders[index].send((x, y)).unwrap();
+            status.in_flight += 1;
+        }
+    }
+
+    // Pass the NAT's packet on to computer 0 if nothing else is happening.
+    fn poll_nat(&self, status: &mut Status) {
+        if !status.is_idle() {
+            return;
+        }
+
+        if let Some((x, y)) = status.nat.buffer {
+            self.send(status, self.addresses.nat, self.addresses.first, x, y);
+
+            if status.nat.last_sent == Some((x, y)) && status.nat.repeated.is_none() {
+                status.nat.repeated = Some((x, y));
+            }
+            status.nat.last_sent = Some((x, y));
+        }
+    }
+}
+
+#[derive(Default)]
+struct Buffers {
+    got_address: bool,
+    // The second half of a packet which has been partly read.
+    y: Option<i64>,
+    output: Vec<i64>,
+}
+
+struct Nic {
+    index: usize,
+    address: usize,
+    network: Arc<Network>,
+    packets: Receiver<(i64, i64)>,
+    buffers: RefCell<Buffers>,
+}
+
+impl Input<i64> for Nic {
+    fn get(&self) -> Option<i64> {
+        let mut buffers = self.buffers.borrow_mut();
+        let mut status = self.network.status.lock().unwrap();
+
+        if status.stopped {
+            None
+        } else if !buffers.got_address {
+            buffers.got_address = true;
+            Some(self.address as i64)
+        } else if let Some(y) = buffers.y.take() {
+            status.in_flight -= 1;
+            Some(y)
+        } else if let Ok((x, y)) = self.packets.try_recv() {
+            status.idle_reads[self.index] = 0;
+            buffers.y = Some(y);
+            Some(x)
+        } else {
+            status.idle_reads[self.index] += 1;
+            if status.idle_reads[self.index] == IDLE_READS {
+                self.network.changed.notify_all();
+            }
+            drop(status);
+            thread::yield_now();
+            Some(-1)
+        }
+    }
+}
+
+impl Output<i64> for Nic {
+    fn put(&self, value: i64) {
+        let mut buffers = self.buffers.borrow_mut();
+        let mut status = self.network.status.lock().unwrap();
+
+        status.idle_reads[self.index] = 0;
+        buffers.output.push(value);
+
+        if buffers.output.len() == 3 {
+            let (destination, x, y) = (buffers.output[0], buffers.output[1], buffers.output[2]);
+            self.network.send(&mut status, self.address, destination as usize, x, y);
+            buffers.output.clear();
+        }
+    }
+}
+
+// Run each computer on its own thread, with the NAT on this one, until the
+// answer to the given part has been sent.
+pub fn run(
+    program: &Program,
+    part: u8,
+    addresses: Addresses,
+    capture: Option<Arc<Capture>>,
+) -> Option<i64> {
+    let computers = addresses.computers;
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..computers).map(|_| channel()).unzip();
+
+    let network = Arc::new(Network {
+        addresses,
+        senders,
+        status: Mutex::new(Status {
+            idle_reads: vec![0; computers],
+            in_flight: 0,
+            finished: vec![false; computers],
+            running: computers,
+            stopped: false,
+            nat: Nat::default(),
+        }),
+        changed: Condvar::new(),
+        capture,
+    });
+
+    let program = Arc::new(Program {
+        data: program.data.clone(),
+    });
+
+    let threads: Vec<_> = receivers
+        .into_iter()
+        .enumerate()
+        .map(|(index, packets)| {
+            let network = network.clone();
+            let program = program.clone();
+            thread::spawn(move || {
+                let address = addresses.address(index);
+                let nic = Rc::new(Nic {
+                    index,
+                    address,
+                    network: network.clone(),
+                    packets,
+                    buffers: RefCell::new(Buffers::default()),
+                });
+                let name = format!("Computer {}", address);
+                let mut process = Process::new(name, &program, nic.clone(), nic.clone());
+                while process.execute_for(1000) == State::Yielded {}
+
+                // A computer which has stopped won't send or read anything
+                // else.
+                let mut status = network.status.lock().unwrap();
+                let unread = nic.packets.try_iter().count() + nic.buffers.borrow().y.iter().count();
+                status.in_flight -= unread;
+                status.finished[index] = true;
+                status.running -= 1;
+                status.idle_reads[index] = IDLE_READS;
+                network.changed.notify_all();
+            })
+        })
+        .collect();
+
+    let mut status = network.status.lock().unwrap();
+    let answer = loop {
+        if let Some(y) = status.answer(part) {
+            break Some(y);
+        }
+        if status.running == 0 {
+            break None;
+        }
+
+        network.poll_nat(&mut status);
+
+        status = network
+            .changed
+            .wait_timeout(status, Duration::from_millis(10))
+            .unwrap()
+            .0;
+    };
+    status.stopped = true;
+    drop(status);
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    answer
+}
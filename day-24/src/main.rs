@@ -0,0 +1,703 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Write, stdout};
+use std::path::{Path, PathBuf};
+use std::iter::once;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use itertools::Either;
+use std::fmt;
+use structopt::StructOpt;
+use termion::cursor;
+use termion::event::Key;
+use termion::raw::IntoRawMode;
+
+mod utils;
+mod viewer;
+
+use common::display::{self, Screen};
+use common::{CommonOpts, Flag};
+use utils::Ticker;
+use viewer::Viewer;
+
+// The width and height of each grid, which has to be odd so there's a middle
+// tile to hold the next level down, and small enough for a level to fit in a
+// u32.
+const SIZE: isize = 5;
+const MIDDLE: isize = SIZE / 2;
+const LAST: isize = SIZE - 1;
+const TILES: usize = (SIZE * SIZE) as usize;
+const _: () = assert!(SIZE % 2 == 1 && TILES <= 32);
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Tile {
+    Bug,
+    Empty
+}
+
+impl TryFrom<char> for Tile {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        use Tile::*;
+        match c {
+            '#' => Ok(Bug),
+            '.' | '?' => Ok(Empty),
+            _ => Err(format!("Unknown tile {}", c).into())
+        }
+    }
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Tile::*;
+        match *self {
+            Bug => write!(f, "#"),
+            Empty => write!(f, "."),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Layout {
+    // A single grid, for part 1.
+    Flat,
+    // Each grid has another inside its middle tile, for part 2.
+    Recursive,
+}
+
+// Which numbers of adjacent bugs cause a bug to be born on an empty tile, and
+// which let a bug survive, as bitmasks of the counts.  Written like "B12/S1",
+// which is the puzzle's rule.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Rules {
+    birth: u16,
+    survival: u16,
+}
+
+// No tile has more than this many neighbours, which is the most for a tile
+// next to the middle in the recursive layout.
+const MAX_NEIGHBOURS: u32 = 3 + SIZE as u32;
+
+impl Rules {
+    fn alive(self, bug: bool, neighbours: u32) -> bool {
+        let counts = if bug { self.survival } else { self.birth };
+        counts & (1 << neighbours) != 0
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            birth: 0b110,
+            survival: 0b10,
+        }
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut birth = None;
+        let mut survival = None;
+        for part in s.split('/') {
+            let mut chars = part.chars();
+            let counts = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => &mut birth,
+                Some('S') => &mut survival,
+                _ => return Err(format!("Rule {:?} doesn't start with B or S", part)),
+            };
+            if counts.is_some() {
+                return Err(format!("There's more than one {} rule", &part[..1]));
+            }
+
+            let mut mask = 0;
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(count) if count <= MAX_NEIGHBOURS => mask |= 1 << count,
+                    _ => {
+                        return Err(format!(
+                            "{:?} in rule {:?} isn't a number of neighbours from 0 to {}",
+                            c, part, MAX_NEIGHBOURS
+                        ))
+                    }
+                }
+            }
+            *counts = Some(mask);
+        }
+
+        Ok(Rules {
+            birth: birth.unwrap_or(0),
+            survival: survival.unwrap_or(0),
+        })
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |mask: u16| -> String {
+            (0..=MAX_NEIGHBOURS)
+                .filter(|count| mask & (1 << count) != 0)
+                .map(|count| count.to_string())
+                .collect()
+        };
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+struct Position {
+    x: isize,
+    y: isize,
+    level : isize
+}
+
+impl Position {
+    fn adjacent(self, layout: Layout) -> impl Iterator<Item=Position> {
+        match layout {
+            Layout::Flat => Either::Left(self.flat_adjacent()),
+            Layout::Recursive => Either::Right(self.recursive_adjacent()),
+        }
+    }
+
+    fn neighbours(self) -> impl Iterator<Item=Position> {
+        [(0, 1), (-1, 0), (0, -1), (1, 0)].iter().map(move |&(x, y)| Position {
+            x: self.x + x,
+            y: self.y + y,
+            level: self.level,
+        })
+    }
+
+    fn flat_adjacent(self) -> impl Iterator<Item=Position> {
+        self.neighbours()
+            .filter(|&Position { x, y, .. }| (0..SIZE).contains(&x) && (0..SIZE).contains(&y))
+    }
+
+    fn recursive_adjacent(self) -> impl Iterator<Item=Position> {
+        self.neighbours().flat_map(move |Position { x, y, level }| {
+            if x < 0 {
+                Either::Left(once(Position { x: MIDDLE - 1, y: MIDDLE, level: level - 1 }))
+            } else if x > LAST {
+                Either::Left(once(Position { x: MIDDLE + 1, y: MIDDLE, level: level - 1 }))
+            } else if y < 0 {
+                Either::Left(once(Position { x: MIDDLE, y: MIDDLE - 1, level: level - 1 }))
+            } else if y > LAST {
+                Either::Left(once(Position { x: MIDDLE, y: MIDDLE + 1, level: level - 1 }))
+            } else if (x, y) == (MIDDLE, MIDDLE) {
+                Either::Right((0..SIZE).map(move |index| 
+                    if self.x < MIDDLE {
+                        Position { x: 0, y: index, level: level + 1 }
+                    } else if self.x > MIDDLE {
+                        Position { x: LAST, y: index, level: level + 1 }
+                    } else if self.y < MIDDLE {
+                        Position { x: index, y: 0, level: level + 1 }
+                    } else if self.y > MIDDLE {
+                        Position { x: index, y: LAST, level: level + 1 }
+                    } else {
+                        unreachable!();
+                    }
+                ))
+            } else {
+                Either::Left(once(Position { x, y, level }))
+            }
+
+        })
+    }
+
+    // Which bit of a level's mask this tile is.
+    fn index(self) -> usize {
+        (self.x + SIZE * self.y) as usize
+    }
+
+    fn is_middle(self) -> bool {
+        (self.x, self.y) == (MIDDLE, MIDDLE)
+    }
+}
+
+// The tiles next to each tile of a level, as masks of the tiles on the level
+// above, on the same level and on the level below.
+struct Neighbours {
+    outer: Vec<u32>,
+    same: Vec<u32>,
+    inner: Vec<u32>,
+    // The tiles which can hold bugs.
+    tiles: u32,
+}
+
+impl Neighbours {
+    fn new(layout: Layout) -> Self {
+        let mut neighbours = Neighbours {
+            outer: vec![0; TILES],
+            same: vec![0; TILES],
+            inner: vec![0; TILES],
+            tiles: 0,
+        };
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let position = Position { x, y, level: 0 };
+                if layout == Layout::Recursive && position.is_middle() {
+                    continue;
+                }
+
+                let index = position.index();
+                neighbours.tiles |= 1 << index;
+                for p in position.adjacent(layout) {
+                    let mask = match p.level {
+                        -1 => &mut neighbours.outer[index],
+                        0 => &mut neighbours.same[index],
+                        _ => &mut neighbours.inner[index],
+                    };
+                    *mask |= 1 << p.index();
+                }
+            }
+        }
+
+        neighbours
+    }
+
+    // The next generation of a level, given it and the levels either side.
+    fn next(&self, rules: Rules, outer: u32, level: u32, inner: u32) -> u32 {
+        (0..TILES)
+            .filter(|&index| self.tiles & (1 << index) != 0)
+            .filter(|&index| {
+                let bugs = (outer & self.outer[index]).count_ones()
+                    + (level & self.same[index]).count_ones()
+                    + (inner & self.inner[index]).count_ones();
+                rules.alive(level & (1 << index) != 0, bugs)
+            })
+            .fold(0, |mask, index| mask | 1 << index)
+    }
+}
+
+struct Map {
+    // The bugs on each level as a bitmask, starting from level `first`, with
+    // no empty levels at either end.
+    levels: Vec<u32>,
+    first: isize,
+}
+
+impl Map {
+    fn new(first: isize, mut levels: Vec<u32>) -> Self {
+        let start = levels.iter().take_while(|&&level| level == 0).count();
+        let end = levels.iter().rposition(|&level| level != 0).map_or(start, |end| end + 1);
+        levels.truncate(end);
+        levels.drain(..start);
+
+        Map {
+            levels,
+            first: first + start as isize,
+        }
+    }
+
+    fn read(input: impl BufRead) -> Result<Self, Error> {
+        let mut level = 0;
+        let mut rows = 0;
+        for (y, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.chars().count() != SIZE as usize {
+                return Err(format!("Row {} isn't {} tiles wide", y + 1, SIZE).into());
+            }
+            for (x, c) in line.chars().enumerate() {
+                let position = Position { x: x as isize, y: y as isize, level: 0 };
+                if Tile::try_from(c)? == Tile::Bug {
+                    level |= 1 << position.index();
+                }
+            }
+            rows += 1;
+        }
+
+        if rows != SIZE {
+            return Err(format!("The grid has {} rows rather than {}", rows, SIZE).into());
+        }
+
+        Ok(Map::new(0, vec![level]))
+    }
+
+    // The bugs on a level.
+    fn level(&self, level: isize) -> u32 {
+        usize::try_from(level - self.first)
+            .ok()
+            .and_then(|index| self.levels.get(index))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    // The bits of level 0 are in the same order as the powers of two in the
+    // rating.
+    fn rating(&self) -> usize {
+        self.level(0) as usize
+    }
+
+    fn tile(&self, position: Position) -> Tile {
+        if self.level(position.level) & (1 << position.index()) != 0 {
+            Tile::Bug
+        } else {
+            Tile::Empty
+        }
+    }
+
+    // The levels which have bugs on, along with level 0.
+    fn levels(&self) -> RangeInclusive<isize> {
+        let last = self.first + self.levels.len() as isize - 1;
+        if self.levels.is_empty() {
+            0..=0
+        } else {
+            self.first.min(0)..=last.max(0)
+        }
+    }
+
+    fn num_bugs(&self) -> usize {
+        self.levels.iter().map(|level| level.count_ones() as usize).sum()
+    }
+
+    // One row of a level, with the middle tile shown as the level below.
+    fn row(&self, y: isize, level: isize) -> String {
+        (0..SIZE)
+            .map(|x| {
+                let position = Position { x, y, level };
+                if position.is_middle() {
+                    "?".to_string()
+                } else {
+                    self.tile(position).to_string()
+                }
+            })
+            .collect()
+    }
+
+    // Only rules where a bug needs a neighbour to be born can be used on the
+    // recursive grids, as otherwise every one of the infinitely many empty
+    // levels would fill with bugs.
+    fn next(&self, layout: Layout, rules: Rules) -> Self {
+        let neighbours = Neighbours::new(layout);
+
+        // Bugs can spread one level further in each direction.
+        let first = match layout {
+            Layout::Flat => 0,
+            Layout::Recursive => self.first - 1,
+        };
+        let last = match layout {
+            Layout::Flat => 0,
+            Layout::Recursive => self.first + self.levels.len() as isize,
+        };
+
+        let levels = (first..=last)
+            .map(|level| {
+                let (outer, inner) = (self.level(level - 1), self.level(level + 1));
+                neighbours.next(rules, outer, self.level(level), inner)
+            })
+            .collect();
+
+        Map::new(first, levels)
+    }
+
+    // The first layout of a single grid to appear twice.
+    fn first_repeated(&self, rules: Rules) -> Self {
+        let mut map = Map::new(0, vec![self.level(0)]);
+        let mut seen = HashSet::new();
+
+        // The rating identifies the layout of a single grid.
+        while seen.insert(map.rating()) {
+            map = map.next(Layout::Flat, rules);
+        }
+
+        map
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let levels = self.levels();
+        for level in levels.clone() {
+            if level != *levels.start() {
+                writeln!(f)?;
+            }
+            writeln!(f, "Depth {}:", level)?;
+            for y in 0..SIZE {
+                writeln!(f, "{}", self.row(y, level))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Run the recursive grids for a number of generations, returning the last map
+// along with the number of bugs after each generation, starting from the
+// first.
+fn run_generations(mut map: Map, generations: usize, rules: Rules) -> (Map, Vec<usize>) {
+    let mut counts = vec![map.num_bugs()];
+    for _ in 0..generations {
+        map = map.next(Layout::Recursive, rules);
+        counts.push(map.num_bugs());
+    }
+    (map, counts)
+}
+
+// Write the number of bugs after each generation, then the final state.
+fn export(path: &Path, map: &Map, counts: &[usize]) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "Bugs after each generation:")?;
+    for (generation, count) in counts.iter().enumerate() {
+        writeln!(file, "{}: {}", generation, count)?;
+    }
+    writeln!(file)?;
+    write!(file, "{}", map)?;
+    Ok(())
+}
+
+// Show the recursive grids as they change, returning the map after the last
+// generation and the counts of bugs, or None if the viewer was closed before
+// then.
+fn show_generations(
+    mut map: Map,
+    generations: usize,
+    screen: impl Screen,
+    speed: Option<u64>,
+    columns: usize,
+    rules: Rules,
+) -> Option<(Map, Vec<usize>)> {
+    let keys = display::read_keys();
+    let mut viewer = Viewer::new(screen, columns);
+    let mut ticker = speed.map(Ticker::with_rate);
+    let mut generation = 0;
+    let mut paused = false;
+    let mut counts = vec![map.num_bugs()];
+
+    loop {
+        let (first, last) = viewer.shown();
+        viewer.draw(&map);
+        viewer.status(&format!(
+            "Generation {} of {}, {} bugs, depths {} to {}{}  \
+             left/right: page, space: pause, q: quit",
+            generation,
+            generations,
+            map.num_bugs(),
+            first,
+            last,
+            if paused { " (paused)" } else { "" }
+        ));
+
+        // Block on the next key press if there's nothing to animate.
+        let pressed: Vec<Key> = if paused || generation == generations {
+            match keys.recv() {
+                Ok(key) => vec![key],
+                Err(_) => return Some((map, counts)),
+            }
+        } else {
+            keys.try_iter().collect()
+        };
+
+        for key in pressed {
+            if display::is_quit(&key) {
+                return if generation == generations { Some((map, counts)) } else { None };
+            }
+            match key {
+                Key::Left | Key::Char('h') => viewer.page(-1),
+                Key::Right | Key::Char('l') => viewer.page(1),
+                Key::Char(' ') => paused = !paused,
+                _ => {}
+            }
+        }
+
+        if !paused && generation < generations {
+            map = map.next(Layout::Recursive, rules);
+            counts.push(map.num_bugs());
+            generation += 1;
+
+            if let Some(ticker) = ticker.as_mut() {
+                ticker.wait();
+            }
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// Number of generations to run the recursive grids for
+    #[structopt(short, long, default_value = "200")]
+    generations: usize,
+
+    /// Watch the recursive grids change, a page of levels at a time, at
+    /// --speed generations per second
+    #[structopt(short, long, conflicts_with_all = &["quiet", "json"])]
+    view: bool,
+
+    /// Write the bugs after each generation and the final state to this file
+    #[structopt(short, long, parse(from_os_str))]
+    export: Option<PathBuf>,
+
+    /// How bugs live and die, as the numbers of adjacent bugs which cause one to be born on an
+    /// empty tile and let one survive
+    #[structopt(long, default_value = "B12/S1")]
+    rules: Rules,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+
+    let map = opts
+        .common
+        .unsupported(&[Flag::Debug])
+        .and_then(|_| opts.common.input())
+        .map_err(Error::from)
+        .and_then(Map::read);
+    let map = match map {
+        Ok(map) => map,
+        Err(Error(message)) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if opts.common.part(2) == 1 {
+        let rating = map.first_repeated(opts.rules).rating();
+        opts.common.print_answers(&[("rating", rating.into())]);
+        return;
+    }
+
+    if opts.rules.alive(false, 0) {
+        eprintln!(
+            "The recursive grids can't be run with {}, as bugs would be born on every level",
+            opts.rules
+        );
+        std::process::exit(1);
+    }
+
+    let (map, counts) = if opts.view {
+        let (width, _) = termion::terminal_size().unwrap_or((80, 24));
+        let columns = (width / viewer::COLUMN_WIDTH) as usize;
+        let screen = cursor::HideCursor::from(stdout().into_raw_mode().unwrap());
+        let speed = opts.common.speed();
+        match show_generations(map, opts.generations, screen, speed, columns, opts.rules) {
+            Some(result) => result,
+            None => {
+                eprintln!("Stopped before the last generation");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        let (map, counts) = run_generations(map, opts.generations, opts.rules);
+        if !opts.common.answers_only() {
+            print!("{}", map);
+        }
+        (map, counts)
+    };
+
+    if let Some(path) = opts.export {
+        if let Err(Error(message)) = export(&path, &map, &counts) {
+            eprintln!("Can't export to {}: {}", path.display(), message);
+            std::process::exit(1);
+        }
+    }
+
+    opts.common.print_answers(&[("bugs", map.num_bugs().into())]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "....#\n#..#.\n#..##\n..#..\n#....";
+
+    #[test]
+    fn flat() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        let map = map.next(Layout::Flat, Rules::default());
+        let expected = Map::read("#..#.\n####.\n###.#\n##.##\n.##..".as_bytes()).unwrap();
+        assert_eq!(map.rating(), expected.rating());
+
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(map.first_repeated(Rules::default()).rating(), 2129920);
+    }
+
+    #[test]
+    fn read() {
+        assert!(Map::read("....#\n#..#.".as_bytes()).is_err());
+        assert!(Map::read("....\n#..#.\n#..##\n..#..\n#....".as_bytes()).is_err());
+        assert!(Map::read("....x\n#..#.\n#..##\n..#..\n#....".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn adjacent() {
+        let count = |x, y| Position { x, y, level: 0 }.adjacent(Layout::Recursive).count();
+        assert_eq!(count(0, 0), 4);
+        assert_eq!(count(MIDDLE, MIDDLE - 1), 3 + SIZE as usize);
+        assert_eq!(count(LAST, MIDDLE), 4);
+        assert_eq!(Position { x: 0, y: 0, level: 0 }.adjacent(Layout::Flat).count(), 2);
+    }
+
+    #[test]
+    fn neighbours() {
+        let neighbours = Neighbours::new(Layout::Recursive);
+        let above_middle = Position { x: MIDDLE, y: MIDDLE - 1, level: 0 }.index();
+        assert_eq!(neighbours.same[above_middle].count_ones(), 3);
+        assert_eq!(neighbours.inner[above_middle].count_ones(), SIZE as u32);
+        assert_eq!(neighbours.outer[0].count_ones(), 2);
+        assert_eq!(neighbours.tiles.count_ones(), TILES as u32 - 1);
+
+        let neighbours = Neighbours::new(Layout::Flat);
+        assert_eq!(neighbours.outer[0] | neighbours.inner[0], 0);
+        assert_eq!(neighbours.tiles.count_ones(), TILES as u32);
+
+        let map = Map::new(-2, vec![0, 0, 1, 0, 2, 0]);
+        assert_eq!((map.first, map.levels.clone()), (0, vec![1, 0, 2]));
+        assert_eq!(map.levels(), 0..=2);
+        assert_eq!(Map::new(3, vec![0, 0]).levels(), 0..=0);
+    }
+
+    #[test]
+    fn recursive() {
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        let (map, counts) = run_generations(map, 10, Rules::default());
+        assert_eq!(map.num_bugs(), 99);
+        assert_eq!(counts.len(), 11);
+        assert_eq!(counts[0], 8);
+        assert_eq!(counts[10], 99);
+        assert_eq!(map.levels(), -5..=5);
+
+        let text = map.to_string();
+        assert!(text.starts_with("Depth -5:\n..#..\n.#.#.\n..?.#\n.#.#.\n..#..\n\nDepth -4:\n"));
+        assert!(text.ends_with("Depth 5:\n####.\n#..#.\n#.?#.\n####.\n.....\n"));
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!("B12/S1".parse(), Ok(Rules::default()));
+        assert_eq!("s1/b21".parse(), Ok(Rules::default()));
+        assert_eq!(Rules::default().to_string(), "B12/S1");
+        assert_eq!("B3/S23".parse::<Rules>().unwrap().to_string(), "B3/S23");
+        assert_eq!("B/S".parse::<Rules>().unwrap().to_string(), "B/S");
+        assert_eq!("S8".parse::<Rules>().unwrap().to_string(), "B/S8");
+        assert!("B9/S1".parse::<Rules>().is_err());
+        assert!("B1/B2".parse::<Rules>().is_err());
+        assert!("X1".parse::<Rules>().is_err());
+        assert!("B1a/S1".parse::<Rules>().is_err());
+
+        // Nothing is ever born and everything survives, so nothing changes.
+        let rules: Rules = "B/S012345678".parse().unwrap();
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(map.next(Layout::Flat, rules).rating(), map.rating());
+        let (map, counts) = run_generations(map, 3, rules);
+        assert_eq!(counts, [8, 8, 8, 8]);
+        assert_eq!(map.levels(), 0..=0);
+
+        // Nothing survives and nothing is born, so everything dies.
+        let rules: Rules = "B/S".parse().unwrap();
+        let map = Map::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(map.next(Layout::Recursive, rules).num_bugs(), 0);
+    }
+}
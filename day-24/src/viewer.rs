@@ -0,0 +1,92 @@
+use common::display::{Pane, Screen};
+use crate::{Map, SIZE};
+
+// Room for "Depth -123" and a gap before the next level.
+pub const COLUMN_WIDTH: u16 = 12;
+
+// Shows a page of levels side by side, with a status line underneath.
+pub struct Viewer<S> {
+    screen: S,
+    columns: Vec<Pane>,
+    // The level in the leftmost column.
+    first: isize,
+    status: Pane,
+}
+
+impl<S: Screen> Viewer<S> {
+    pub fn new(mut screen: S, columns: usize) -> Self {
+        let columns: Vec<_> = (0..columns.max(1))
+            .map(|column| Pane::new([column as u16 * COLUMN_WIDTH, 0], COLUMN_WIDTH))
+            .collect();
+        let width = columns.len() as u16 * COLUMN_WIDTH;
+        let first = -(columns.len() as isize / 2);
+
+        screen.clear();
+        Viewer {
+            screen,
+            columns,
+            first,
+            status: Pane::new([0, SIZE as u16 + 2], width),
+        }
+    }
+
+    // Move a number of pages deeper, or shallower if negative.
+    pub fn page(&mut self, pages: isize) {
+        self.first += pages * self.columns.len() as isize;
+    }
+
+    pub fn draw(&mut self, map: &Map) {
+        let levels = map.levels();
+
+        for (column, pane) in self.columns.iter().enumerate() {
+            let level = self.first + column as isize;
+            if !levels.contains(&level) {
+                for row in 0..=SIZE as u16 {
+                    pane.line(&mut self.screen, row, "");
+                }
+                continue;
+            }
+
+            pane.line(&mut self.screen, 0, &format!("Depth {}", level));
+            for y in 0..SIZE {
+                pane.line(&mut self.screen, y as u16 + 1, &map.row(y, level));
+            }
+        }
+    }
+
+    pub fn status(&mut self, text: &str) {
+        self.status.line(&mut self.screen, 0, text);
+        let [_, y] = self.status.origin;
+        self.screen.goto([0, y + 1]);
+    }
+
+    // The range of levels on the current page.
+    pub fn shown(&self) -> (isize, isize) {
+        (self.first, self.first + self.columns.len() as isize - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn draw() {
+        let map = Map::read("....#\n#..#.\n#..##\n..#..\n#....".as_bytes()).unwrap();
+        let mut output = vec![];
+        let mut viewer = Viewer::new(&mut output, 3);
+        assert_eq!(viewer.shown(), (-1, 1));
+        viewer.draw(&map);
+        viewer.page(1);
+        assert_eq!(viewer.shown(), (2, 4));
+        viewer.status("Generation 0");
+        drop(viewer);
+
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("Depth 0"));
+        assert!(!text.contains("Depth -1"));
+        assert!(text.contains("#..#."));
+        assert!(text.contains("#.?##"));
+        assert!(text.contains("Generation 0"));
+    }
+}
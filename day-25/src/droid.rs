@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use intcode::process::{Input, Output, Process, State};
+use intcode::program::Program;
+
+// The droid's end of the ASCII link: commands waiting to be read, and
+// everything printed since it was last collected.
+#[derive(Default)]
+struct Terminal {
+    input: RefCell<VecDeque<i64>>,
+    output: RefCell<String>,
+}
+
+impl Input<i64> for Terminal {
+    fn get(&self) -> Option<i64> {
+        self.input.borrow_mut().pop_front()
+    }
+}
+
+impl Output<i64> for Terminal {
+    fn put(&self, value: i64) {
+        let mut output = self.output.borrow_mut();
+        match value {
+            0..=127 => output.push(value as u8 as char),
+            _ => output.push_str(&value.to_string()),
+        }
+    }
+}
+
+pub struct Droid {
+    terminal: Rc<Terminal>,
+    process: Process<Rc<Terminal>, Rc<Terminal>>,
+    finished: bool,
+}
+
+impl Droid {
+    pub fn new(program: &Program) -> Self {
+        let terminal = Rc::new(Terminal::default());
+        let process = Process::new("droid", program, terminal.clone(), terminal.clone());
+        Droid {
+            terminal,
+            process,
+            finished: false,
+        }
+    }
+
+    // Run until the droid wants another command or stops, returning what it
+    // printed.
+    pub fn run(&mut self) -> String {
+        if !self.finished {
+            self.finished = self.process.execute() == State::Complete;
+        }
+        self.terminal.output.replace(String::new())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn send(&mut self, command: &str) {
+        let mut input = self.terminal.input.borrow_mut();
+        input.extend(command.bytes().map(i64::from));
+        input.push_back(i64::from(b'\n'));
+    }
+
+    // Send a command and return the droid's reply.
+    pub fn command(&mut self, command: &str) -> String {
+        self.send(command);
+        self.run()
+    }
+}
+
+// A snapshot of the droid, which carries on independently of the original.
+impl Clone for Droid {
+    fn clone(&self) -> Self {
+        let terminal = Rc::new(Terminal {
+            input: self.terminal.input.clone(),
+            output: self.terminal.output.clone(),
+        });
+        Droid {
+            process: self.process.fork(terminal.clone(), terminal.clone()),
+            terminal,
+            finished: self.finished,
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs::{self, File};
+use std::io::{self, stdout, BufWriter, Write};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+mod droid;
+mod prompt;
+mod room;
+mod script;
+mod ship;
+mod solver;
+
+use common::{CommonOpts, Flag};
+use droid::Droid;
+use intcode::program::Program;
+use prompt::Prompt;
+use ship::Ship;
+
+#[derive(Debug)]
+struct Error(String);
+
+impl<T: ToString> From<T> for Error {
+    fn from(error: T) -> Self {
+        Error(error.to_string())
+    }
+}
+
+// Run the droid, passing it commands until it stops or there are none left,
+// and record both sides of the conversation in the transcript.  The map of the
+// ship is kept up to date along the way, and shown after every move if
+// `live_map` is set, or when asked for with the `map` command.
+fn play(
+    droid: &mut Droid,
+    ship: &mut Ship,
+    live_map: bool,
+    mut next_command: impl FnMut() -> Result<Option<String>, Error>,
+    output: &mut dyn Write,
+    transcript: &mut dyn Write,
+) -> Result<(), Error> {
+    let mut command = None;
+
+    loop {
+        let text = droid.run();
+        ship.update(command.as_deref(), &text);
+        output.write_all(text.as_bytes())?;
+        transcript.write_all(text.as_bytes())?;
+        if live_map && room::parse(&text).is_some() {
+            output.write_all(ship.draw().as_bytes())?;
+        }
+        output.flush()?;
+
+        if droid.is_finished() {
+            return Ok(());
+        }
+
+        let next = loop {
+            match next_command()? {
+                Some(command) if command.trim() == "map" => {
+                    output.write_all(ship.render().as_bytes())?;
+                    output.flush()?;
+                }
+                Some(command) => break command,
+                None => return Ok(()),
+            }
+        };
+
+        writeln!(transcript, "{}", next)?;
+        droid.send(&next);
+        command = Some(next);
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Opts {
+    /// File of commands to send before asking for any, with # starting a comment
+    #[structopt(parse(from_os_str))]
+    script: Option<PathBuf>,
+
+    /// Stop when the script ends rather than carrying on from the prompt
+    #[structopt(short, long)]
+    batch: bool,
+
+    /// Draw the map of the ship after every move
+    #[structopt(short, long)]
+    map: bool,
+
+    /// Write everything the droid prints, and every command, to this file
+    #[structopt(short, long, parse(from_os_str))]
+    transcript: Option<PathBuf>,
+
+    /// Explore the ship and get past the checkpoint without any help
+    #[structopt(long)]
+    solve: bool,
+
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() {
+    let opts = Opts::from_args();
+    let program = opts
+        .common
+        .unsupported(&[Flag::Part, Flag::Speed, Flag::Debug])
+        .and_then(|_| {
+            if opts.common.answers_only() && !opts.solve {
+                Err("There's only an answer to print with --solve".to_string())
+            } else {
+                opts.common.input()
+            }
+        })
+        .map(|input| Program::parse(input).unwrap());
+    let program = match program {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if opts.solve {
+        match solver::solve(&program) {
+            Ok(password) if opts.common.answers_only() => {
+                opts.common.print_answers(&[("password", password.into())])
+            }
+            Ok(password) => println!("{}", password),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut transcript: Box<dyn Write> = match opts.transcript.as_ref().map(File::create) {
+        Some(Ok(file)) => Box::new(BufWriter::new(file)),
+        Some(Err(error)) => {
+            eprintln!("Can't create transcript: {}", error);
+            std::process::exit(1);
+        }
+        None => Box::new(io::sink()),
+    };
+
+    let script = match opts.script.as_ref().map(fs::read_to_string) {
+        Some(Ok(text)) => script::parse(&text),
+        Some(Err(error)) => {
+            eprintln!("Can't read script: {}", error);
+            std::process::exit(1);
+        }
+        None => vec![],
+    };
+
+    let mut droid = Droid::new(&program);
+    let mut prompt = Prompt::default();
+    let mut script = script.into_iter();
+    let next_command = || match script.next() {
+        Some(command) => {
+            // Show the command as if it had been typed at the prompt.
+            println!("> {}", command);
+            prompt.remember(&command);
+            Ok(Some(command))
+        }
+        None if opts.batch => Ok(None),
+        None => Ok(prompt.read_line("> ")?),
+    };
+
+    let mut ship = Ship::default();
+    let result = play(
+        &mut droid,
+        &mut ship,
+        opts.map,
+        next_command,
+        &mut stdout(),
+        &mut transcript,
+    );
+    let result = result.and_then(|_| Ok(transcript.flush()?));
+
+    if let Err(Error(message)) = result {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Echoes each character it reads until it reads a 'q'.
+    const ECHO: &str = "3,100,1008,100,113,101,1005,101,14,4,100,1105,1,0,99";
+
+    #[test]
+    fn transcript() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let mut droid = Droid::new(&program);
+        let mut commands = vec!["north", "take mug", "q"].into_iter();
+        let mut output = vec![];
+        let mut transcript = vec![];
+
+        play(
+            &mut droid,
+            &mut Ship::default(),
+            false,
+            || Ok(commands.next().map(str::to_string)),
+            &mut output,
+            &mut transcript,
+        )
+        .unwrap();
+
+        assert!(droid.is_finished());
+        assert_eq!(String::from_utf8(output).unwrap(), "north\ntake mug\n");
+        assert_eq!(
+            String::from_utf8(transcript).unwrap(),
+            "north\nnorth\ntake mug\ntake mug\nq\n"
+        );
+    }
+
+    #[test]
+    fn map_command() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let mut droid = Droid::new(&program);
+        let mut commands = vec!["== Hull Breach ==", "map", "q"].into_iter();
+        let mut output = vec![];
+        let mut transcript = vec![];
+
+        play(
+            &mut droid,
+            &mut Ship::default(),
+            true,
+            || Ok(commands.next().map(str::to_string)),
+            &mut output,
+            &mut transcript,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("== Hull Breach ==\n\n @\n\n"));
+        assert!(output.contains("@ you, * items, ? unexplored\n\nHull Breach (here)\n"));
+        assert!(!String::from_utf8(transcript).unwrap().contains("map"));
+    }
+
+    #[test]
+    fn snapshot() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let mut droid = Droid::new(&program);
+        assert_eq!(droid.command("north"), "north\n");
+
+        let mut snapshot = droid.clone();
+        assert_eq!(droid.command("q"), "");
+        assert!(droid.is_finished());
+        assert!(!snapshot.is_finished());
+        assert_eq!(snapshot.command("east"), "east\n");
+    }
+
+    #[test]
+    fn out_of_commands() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let mut droid = Droid::new(&program);
+        let mut output = vec![];
+        let mut ship = Ship::default();
+        play(
+            &mut droid,
+            &mut ship,
+            false,
+            || Ok(None),
+            &mut output,
+            &mut io::sink(),
+        )
+        .unwrap();
+        assert!(!droid.is_finished());
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+// Read commands from the terminal with some basic line editing and a history
+// of earlier commands.  The terminal is used directly since the program comes
+// in on stdin.
+
+use std::io::{self, Write};
+
+use termion::event::Key;
+use termion::get_tty;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::{clear, cursor};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    Edit,
+    Submit(String),
+    Quit,
+}
+
+#[derive(Default)]
+pub struct Editor {
+    line: Vec<char>,
+    // Where the next character typed goes.
+    cursor: usize,
+    history: Vec<String>,
+    // Which history entry is being shown, along with the line that was being
+    // typed before moving into the history.
+    browsing: Option<(usize, Vec<char>)>,
+}
+
+impl Editor {
+    pub fn key(&mut self, key: Key) -> Action {
+        match key {
+            Key::Char('\n') | Key::Char('\r') => {
+                let line: String = self.line.drain(..).collect();
+                self.cursor = 0;
+                self.browsing = None;
+                self.remember(&line);
+                return Action::Submit(line);
+            }
+            Key::Ctrl('c') => return Action::Quit,
+            Key::Ctrl('d') if self.line.is_empty() => return Action::Quit,
+            Key::Char(c) => {
+                self.line.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.line.remove(self.cursor);
+            }
+            Key::Delete | Key::Ctrl('d') if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            Key::Left if self.cursor > 0 => self.cursor -= 1,
+            Key::Right if self.cursor < self.line.len() => self.cursor += 1,
+            Key::Home | Key::Ctrl('a') => self.cursor = 0,
+            Key::End | Key::Ctrl('e') => self.cursor = self.line.len(),
+            Key::Ctrl('u') => {
+                self.line.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Key::Up => self.browse_back(),
+            Key::Down => self.browse_forward(),
+            _ => {}
+        }
+        Action::Edit
+    }
+
+    // Add a line to the history, unless it's blank or the same as the last.
+    pub fn remember(&mut self, line: &str) {
+        if !line.trim().is_empty() && self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+    }
+
+    fn browse_back(&mut self) {
+        let index = match self.browsing.take() {
+            Some((index, draft)) => {
+                self.browsing = Some((index.saturating_sub(1), draft));
+                index.saturating_sub(1)
+            }
+            None if !self.history.is_empty() => {
+                let draft = self.line.clone();
+                self.browsing = Some((self.history.len() - 1, draft));
+                self.history.len() - 1
+            }
+            None => return,
+        };
+        self.show(self.history[index].chars().collect());
+    }
+
+    fn browse_forward(&mut self) {
+        match self.browsing.take() {
+            Some((index, draft)) if index + 1 == self.history.len() => self.show(draft),
+            Some((index, draft)) => {
+                self.browsing = Some((index + 1, draft));
+                self.show(self.history[index + 1].chars().collect());
+            }
+            None => {}
+        }
+    }
+
+    fn show(&mut self, line: Vec<char>) {
+        self.cursor = line.len();
+        self.line = line;
+    }
+
+    pub fn line(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    // Redraw the line being edited, leaving the cursor in the right place.
+    fn draw(&self, prompt: &str, output: &mut impl Write) -> io::Result<()> {
+        write!(output, "\r{}{}{}", clear::CurrentLine, prompt, self.line())?;
+        let back = self.line.len() - self.cursor;
+        if back > 0 {
+            write!(output, "{}", cursor::Left(back as u16))?;
+        }
+        output.flush()
+    }
+}
+
+#[derive(Default)]
+pub struct Prompt {
+    editor: Editor,
+}
+
+impl Prompt {
+    // Make a command sent some other way available from the history.
+    pub fn remember(&mut self, line: &str) {
+        self.editor.remember(line);
+    }
+
+    // Read a line from the terminal, or None if the user asked to stop.
+    pub fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        let mut output = get_tty()?.into_raw_mode()?;
+        self.editor.draw(prompt, &mut output)?;
+
+        for key in get_tty()?.keys() {
+            match self.editor.key(key?) {
+                Action::Edit => self.editor.draw(prompt, &mut output)?,
+                Action::Submit(line) => {
+                    write!(output, "\r\n")?;
+                    return Ok(Some(line));
+                }
+                Action::Quit => break,
+            }
+        }
+
+        write!(output, "\r\n")?;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn type_line(editor: &mut Editor, text: &str) -> Action {
+        for c in text.chars() {
+            editor.key(Key::Char(c));
+        }
+        editor.key(Key::Char('\n'))
+    }
+
+    #[test]
+    fn editing() {
+        let mut editor = Editor::default();
+        for c in "nrth".chars() {
+            editor.key(Key::Char(c));
+        }
+        editor.key(Key::Left);
+        editor.key(Key::Left);
+        editor.key(Key::Left);
+        editor.key(Key::Char('o'));
+        assert_eq!(editor.line(), "north");
+
+        editor.key(Key::End);
+        editor.key(Key::Backspace);
+        editor.key(Key::Home);
+        editor.key(Key::Delete);
+        assert_eq!(editor.line(), "ort");
+        editor.key(Key::End);
+        editor.key(Key::Ctrl('u'));
+        assert_eq!(editor.line(), "");
+
+        assert_eq!(editor.key(Key::Ctrl('d')), Action::Quit);
+        assert_eq!(editor.key(Key::Ctrl('c')), Action::Quit);
+    }
+
+    #[test]
+    fn history() {
+        let mut editor = Editor::default();
+        assert_eq!(
+            type_line(&mut editor, "north"),
+            Action::Submit("north".to_string())
+        );
+        type_line(&mut editor, "take mug");
+        type_line(&mut editor, "take mug");
+        type_line(&mut editor, "");
+        assert_eq!(editor.history, ["north", "take mug"]);
+
+        editor.key(Key::Char('s'));
+        editor.key(Key::Up);
+        assert_eq!(editor.line(), "take mug");
+        editor.key(Key::Up);
+        editor.key(Key::Up);
+        assert_eq!(editor.line(), "north");
+        editor.key(Key::Down);
+        assert_eq!(editor.line(), "take mug");
+        editor.key(Key::Down);
+        assert_eq!(editor.line(), "s");
+        editor.key(Key::Down);
+        assert_eq!(editor.line(), "s");
+    }
+}
@@ -0,0 +1,109 @@
+// Pick out the description of a room from what the droid prints.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+enum List {
+    Doors,
+    Items,
+}
+
+// Every room described in the output, in order.  There's more than one when
+// the droid gets thrown out of a room as soon as it goes in.
+pub fn parse_all(output: &str) -> Vec<Room> {
+    let mut rooms: Vec<Room> = vec![];
+    let mut list = None;
+    let mut lines = output.lines();
+
+    while let Some(line) = lines.next() {
+        let name = line
+            .strip_prefix("== ")
+            .and_then(|line| line.strip_suffix(" =="));
+        if let Some(name) = name {
+            rooms.push(Room {
+                name: name.to_string(),
+                description: lines.next().unwrap_or("").to_string(),
+                doors: vec![],
+                items: vec![],
+            });
+            list = None;
+            continue;
+        }
+
+        let room = match rooms.last_mut() {
+            Some(room) => room,
+            None => continue,
+        };
+        match (line, line.strip_prefix("- "), &list) {
+            ("Doors here lead:", _, _) => list = Some(List::Doors),
+            ("Items here:", _, _) => list = Some(List::Items),
+            (_, Some(entry), Some(List::Doors)) => room.doors.push(entry.to_string()),
+            (_, Some(entry), Some(List::Items)) => room.items.push(entry.to_string()),
+            _ => list = None,
+        }
+    }
+
+    rooms
+}
+
+// The last room described in the output, which is where the droid ended up.
+pub fn parse(output: &str) -> Option<Room> {
+    parse_all(output).pop()
+}
+
+pub fn opposite(direction: &str) -> Option<&'static str> {
+    match direction {
+        "north" => Some("south"),
+        "south" => Some("north"),
+        "east" => Some("west"),
+        "west" => Some("east"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rooms() {
+        let output = "\n\n\n== Hull Breach ==\nYou got in through a hole in the floor here.\n\n\
+            Doors here lead:\n- north\n- east\n\nCommand?\n\n\n\n== Kitchen ==\n\
+            Everything's freeze-dried.\n\nDoors here lead:\n- south\n- west\n\n\
+            Items here:\n- mug\n- space heater\n\nCommand?\n";
+
+        assert_eq!(
+            parse(output),
+            Some(Room {
+                name: "Kitchen".to_string(),
+                description: "Everything's freeze-dried.".to_string(),
+                doors: vec!["south".to_string(), "west".to_string()],
+                items: vec!["mug".to_string(), "space heater".to_string()],
+            })
+        );
+
+        let first = parse("== Hull Breach ==\nA hole.\n\nDoors here lead:\n- north\n").unwrap();
+        assert_eq!(first.name, "Hull Breach");
+        assert_eq!(first.doors, ["north"]);
+        assert!(first.items.is_empty());
+
+        assert_eq!(parse("\nYou take the mug.\n\nCommand?\n"), None);
+
+        let ejected =
+            "== Pressure-Sensitive Floor ==\nAnalyzing...\n\nDoors here lead:\n- south\n\n\
+            A loud, robotic voice says \"Alert!\"\n\n\n\n== Security Checkpoint ==\n\
+            In the next room, a pressure-sensitive floor will verify your identity.\n\n\
+            Doors here lead:\n- north\n- south\n\nCommand?\n";
+        let rooms = parse_all(ejected);
+        assert_eq!(rooms.len(), 2);
+        assert_eq!(rooms[0].name, "Pressure-Sensitive Floor");
+        assert_eq!(rooms[0].doors, ["south"]);
+        assert_eq!(rooms[1].name, "Security Checkpoint");
+        assert_eq!(rooms[1].doors, ["north", "south"]);
+    }
+}
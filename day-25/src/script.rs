@@ -0,0 +1,22 @@
+// Scripts of commands to send the droid, one per line.  Anything after a `#`
+// is a comment, and blank lines are skipped.
+
+pub fn parse(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn comments() {
+        let text = "# The way to the checkpoint\nnorth\n\n  take mug   # it's safe\n#west\nwest\n";
+        assert_eq!(parse(text), ["north", "take mug", "west"]);
+        assert!(parse("# nothing\n\n").is_empty());
+    }
+}
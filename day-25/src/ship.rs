@@ -0,0 +1,218 @@
+// Keep track of the rooms the droid has seen and which doors lead where, and
+// draw them as a map.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+use crate::room::{self, Room};
+
+#[derive(Default)]
+pub struct Ship {
+    rooms: HashMap<String, Room>,
+    // The order the rooms were first seen in.
+    order: Vec<String>,
+    // Where each door leads, once someone has been through it.
+    links: HashMap<(String, String), String>,
+    current: Option<String>,
+}
+
+fn offset(direction: &str) -> Option<(i32, i32)> {
+    match direction {
+        "north" => Some((0, -1)),
+        "south" => Some((0, 1)),
+        "east" => Some((1, 0)),
+        "west" => Some((-1, 0)),
+        _ => None,
+    }
+}
+
+impl Ship {
+    // Take in what the droid said in response to a command.
+    pub fn update(&mut self, command: Option<&str>, output: &str) {
+        let described = room::parse_all(output);
+        let command = command.unwrap_or("").trim();
+
+        if let (Some(from), Some(to)) = (self.current.clone(), described.first()) {
+            if let Some(back) = room::opposite(command) {
+                self.link(&from, command, &to.name);
+                if to.doors.iter().any(|door| door == back) {
+                    self.link(&to.name, back, &from);
+                }
+            }
+        }
+
+        let rooms = &mut self.rooms;
+        if let Some(current) = self.current.as_ref().and_then(|name| rooms.get_mut(name)) {
+            if let Some(item) = command.strip_prefix("take ") {
+                if output.contains("You take the") {
+                    current.items.retain(|other| other != item);
+                }
+            } else if let Some(item) = command.strip_prefix("drop ") {
+                if output.contains("You drop the") {
+                    current.items.push(item.to_string());
+                }
+            }
+        }
+
+        for room in described {
+            if !self.rooms.contains_key(&room.name) {
+                self.order.push(room.name.clone());
+            }
+            self.current = Some(room.name.clone());
+            self.rooms.insert(room.name.clone(), room);
+        }
+    }
+
+    fn link(&mut self, from: &str, door: &str, to: &str) {
+        self.links
+            .insert((from.to_string(), door.to_string()), to.to_string());
+    }
+
+    // Where each room goes on the map, working out from the first one seen.
+    // Rooms which would land on top of another are left off.
+    fn layout(&self) -> HashMap<&str, (i32, i32)> {
+        let mut positions = HashMap::new();
+        let mut taken = HashMap::new();
+        let mut queue: VecDeque<_> = self
+            .order
+            .first()
+            .map(|name| (name, (0, 0)))
+            .into_iter()
+            .collect();
+
+        while let Some((name, position)) = queue.pop_front() {
+            if positions.contains_key(name.as_str()) || taken.contains_key(&position) {
+                continue;
+            }
+            positions.insert(name.as_str(), position);
+            taken.insert(position, name);
+
+            for door in self.rooms[name].doors.iter() {
+                let next = self.links.get(&(name.clone(), door.clone()));
+                if let (Some(next), Some((dx, dy))) = (next, offset(door)) {
+                    queue.push_back((next, (position.0 + dx, position.1 + dy)));
+                }
+            }
+        }
+
+        positions
+    }
+
+    // Just the map, without the list of rooms.
+    pub fn draw(&self) -> String {
+        let positions = self.layout();
+        if positions.is_empty() {
+            return String::new();
+        }
+
+        let min_x = positions.values().map(|&(x, _)| x).min().unwrap();
+        let max_x = positions.values().map(|&(x, _)| x).max().unwrap();
+        let min_y = positions.values().map(|&(_, y)| y).min().unwrap();
+        let max_y = positions.values().map(|&(_, y)| y).max().unwrap();
+
+        // Rooms go on every other row and column, with the doors between
+        // them, and a margin for doors leading off the edge.
+        let width = ((max_x - min_x) * 2 + 3) as usize;
+        let height = ((max_y - min_y) * 2 + 3) as usize;
+        let mut grid = vec![vec![' '; width]; height];
+
+        for (name, &(x, y)) in positions.iter() {
+            let column = ((x - min_x) * 2 + 1) as usize;
+            let row = ((y - min_y) * 2 + 1) as usize;
+            let room = &self.rooms[*name];
+
+            grid[row][column] = if self.current.as_deref() == Some(name) {
+                '@'
+            } else if room.items.is_empty() {
+                '#'
+            } else {
+                '*'
+            };
+
+            for door in room.doors.iter() {
+                if let Some((dx, dy)) = offset(door) {
+                    let explored = self.links.contains_key(&(name.to_string(), door.clone()));
+                    let cell = &mut grid[(row as i32 + dy) as usize][(column as i32 + dx) as usize];
+                    *cell = match (explored, dx) {
+                        (false, _) => '?',
+                        (true, 0) => '|',
+                        (true, _) => '-',
+                    };
+                }
+            }
+        }
+
+        grid.into_iter()
+            .map(|row| row.into_iter().collect::<String>().trim_end().to_string() + "\n")
+            .collect()
+    }
+
+    // The map, followed by a list of the rooms seen and where their doors
+    // lead.
+    pub fn render(&self) -> String {
+        let mut text = self.draw();
+        text.push_str("\n@ you, * items, ? unexplored\n\n");
+
+        for name in self.order.iter() {
+            let room = &self.rooms[name];
+            let here = if self.current.as_ref() == Some(name) {
+                " (here)"
+            } else {
+                ""
+            };
+            writeln!(text, "{}{}", name, here).unwrap();
+
+            for door in room.doors.iter() {
+                let to = self.links.get(&(name.clone(), door.clone()));
+                writeln!(text, "  {} -> {}", door, to.map_or("?", String::as_str)).unwrap();
+            }
+            if !room.items.is_empty() {
+                writeln!(text, "  items: {}", room.items.join(", ")).unwrap();
+            }
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn describe(name: &str, doors: &[&str], items: &[&str]) -> String {
+        let mut text = format!("\n\n\n== {} ==\nA room.\n\nDoors here lead:\n", name);
+        for door in doors {
+            text += &format!("- {}\n", door);
+        }
+        if !items.is_empty() {
+            text += "\nItems here:\n";
+            for item in items {
+                text += &format!("- {}\n", item);
+            }
+        }
+        text + "\nCommand?\n"
+    }
+
+    #[test]
+    fn mapping() {
+        let mut ship = Ship::default();
+        ship.update(None, &describe("Hull Breach", &["north", "east"], &[]));
+        ship.update(Some("north"), &describe("Kitchen", &["south"], &["mug"]));
+        ship.update(
+            Some("south"),
+            &describe("Hull Breach", &["north", "east"], &[]),
+        );
+
+        let text = ship.render();
+        let map: Vec<&str> = text.lines().take(5).collect();
+        assert_eq!(map, ["", " *", " |", " @?", ""]);
+        assert!(text.contains("Hull Breach (here)\n  north -> Kitchen\n  east -> ?\n"));
+        assert!(text.contains("Kitchen\n  south -> Hull Breach\n  items: mug\n"));
+
+        ship.update(Some("north"), &describe("Kitchen", &["south"], &["mug"]));
+        ship.update(Some("take mug"), "\nYou take the mug.\n\nCommand?\n");
+        let text = ship.render();
+        assert!(text.ends_with("Kitchen (here)\n  south -> Hull Breach\n"));
+        assert_eq!(text.lines().nth(1), Some(" @"));
+    }
+}
@@ -0,0 +1,249 @@
+// Find the airlock password without any help: explore the ship picking up
+// everything safe, then try carrying different sets of items onto the
+// pressure-sensitive floor until one weighs the same as a droid should.
+
+use std::collections::HashSet;
+
+use crate::droid::Droid;
+use intcode::program::Program;
+use crate::room::{self, Room};
+
+// Items which end the game, or leave the droid stuck, when picked up.
+const TRAPS: [&str; 5] = [
+    "escape pod",
+    "giant electromagnet",
+    "infinite loop",
+    "molten lava",
+    "photons",
+];
+
+const CHECKPOINT: &str = "Security Checkpoint";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weight {
+    TooLight,
+    TooHeavy,
+    Right,
+}
+
+// What the floor made of the droid.
+fn weigh(output: &str) -> Weight {
+    if output.contains("heavier than the detected value") {
+        Weight::TooLight
+    } else if output.contains("lighter than the detected value") {
+        Weight::TooHeavy
+    } else {
+        Weight::Right
+    }
+}
+
+fn find_password(output: &str) -> Option<String> {
+    let start = output.find("typing ")? + "typing ".len();
+    let digits: String = output[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    if digits.is_empty() {
+        None
+    } else {
+        Some(digits)
+    }
+}
+
+// Try sets of `count` items, as bitmasks, until one is the right weight.
+// Sets which must be too light or too heavy, given the ones tried already, are
+// skipped.
+pub fn search(count: usize, mut weigh: impl FnMut(u32) -> Weight) -> Option<u32> {
+    let mut too_light: Vec<u32> = vec![];
+    let mut too_heavy: Vec<u32> = vec![];
+
+    for items in 0..1 << count {
+        let lighter = too_light.iter().any(|&light| items & !light == 0);
+        let heavier = too_heavy.iter().any(|&heavy| heavy & !items == 0);
+        if lighter || heavier {
+            continue;
+        }
+
+        match weigh(items) {
+            Weight::TooLight => too_light.push(items),
+            Weight::TooHeavy => too_heavy.push(items),
+            Weight::Right => return Some(items),
+        }
+    }
+
+    None
+}
+
+struct Explorer {
+    droid: Droid,
+    visited: HashSet<String>,
+    // The doors taken from the start to the current room.
+    path: Vec<String>,
+    items: Vec<String>,
+    // The way to the checkpoint, and the door from it onto the floor.
+    checkpoint: Option<(Vec<String>, String)>,
+}
+
+impl Explorer {
+    // Pick up everything in this room and the ones beyond it, ending up back
+    // here.
+    fn visit(&mut self, room: Room) -> Result<(), String> {
+        self.visited.insert(room.name.clone());
+
+        for item in room.items.iter() {
+            if !TRAPS.contains(&item.as_str()) {
+                self.take(item);
+            }
+        }
+
+        let back = self.path.last().and_then(|door| room::opposite(door));
+
+        if room.name == CHECKPOINT {
+            // Going through the other door puts the droid on the floor, which
+            // throws it back here unless it's carrying the right items.
+            let floor = room
+                .doors
+                .iter()
+                .find(|&door| Some(door.as_str()) != back)
+                .ok_or("The checkpoint doesn't lead anywhere")?;
+            self.checkpoint = Some((self.path.clone(), floor.clone()));
+            return Ok(());
+        }
+
+        for door in room.doors.iter() {
+            if Some(door.as_str()) == back {
+                continue;
+            }
+            let return_door =
+                room::opposite(door).ok_or_else(|| format!("Unknown direction {}", door))?;
+
+            let output = self.droid.command(door);
+            let next = room::parse(&output)
+                .ok_or_else(|| format!("No room through the {} door of {}", door, room.name))?;
+
+            if !self.visited.contains(&next.name) {
+                self.path.push(door.clone());
+                self.visit(next)?;
+                self.path.pop();
+            }
+
+            self.droid.command(return_door);
+        }
+
+        Ok(())
+    }
+
+    fn take(&mut self, item: &str) {
+        // Try it out on a snapshot first, in case it's a trap no one knew
+        // about.
+        let mut trial = self.droid.clone();
+        let output = trial.command(&format!("take {}", item));
+        if !trial.is_finished() && output.contains("You take the") {
+            self.droid = trial;
+            self.items.push(item.to_string());
+        }
+    }
+}
+
+pub fn solve(program: &Program) -> Result<String, String> {
+    let mut droid = Droid::new(program);
+    let start = room::parse(&droid.run()).ok_or("The droid didn't say where it is")?;
+
+    let mut explorer = Explorer {
+        droid,
+        visited: HashSet::new(),
+        path: vec![],
+        items: vec![],
+        checkpoint: None,
+    };
+    explorer.visit(start)?;
+
+    let (path, floor) = explorer
+        .checkpoint
+        .ok_or("Didn't find the security checkpoint")?;
+    let items = explorer.items;
+    let mut droid = explorer.droid;
+    for door in path.iter() {
+        droid.command(door);
+    }
+
+    if items.len() > 31 {
+        return Err(format!("Too many items to try: {}", items.len()));
+    }
+
+    // Start from a snapshot at the checkpoint for each set, rather than
+    // exploring the ship all over again.
+    let mut password = None;
+    search(items.len(), |carried| {
+        let mut trial = droid.clone();
+        for (index, item) in items.iter().enumerate() {
+            if carried & (1 << index) == 0 {
+                trial.command(&format!("drop {}", item));
+            }
+        }
+
+        let output = trial.command(&floor);
+        let weight = weigh(&output);
+        if weight == Weight::Right {
+            password = find_password(&output);
+        }
+        weight
+    })
+    .ok_or("No set of items is the right weight")?;
+
+    password.ok_or_else(|| "Got past the checkpoint but didn't see the password".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn floor() {
+        let heavier = "A loud, robotic voice says \"Alert! Droids on this ship are heavier \
+            than the detected value!\" and you are ejected back to the checkpoint.";
+        assert_eq!(weigh(heavier), Weight::TooLight);
+        assert_eq!(
+            weigh(&heavier.replace("heavier", "lighter")),
+            Weight::TooHeavy
+        );
+
+        let passed = "\"Oh, hello! You should be able to get in by typing 2424308736 on the \
+            keypad at the main airlock.\"\n";
+        assert_eq!(weigh(passed), Weight::Right);
+        assert_eq!(find_password(passed), Some("2424308736".to_string()));
+        assert_eq!(find_password(heavier), None);
+    }
+
+    #[test]
+    fn subsets() {
+        let weights = [1, 2, 4, 8, 16, 32];
+        for target in 0..64 {
+            let mut tried = 0;
+            let found = search(weights.len(), |items| {
+                tried += 1;
+                let weight: u32 = (0..weights.len())
+                    .filter(|&index| items & (1 << index) != 0)
+                    .map(|index| weights[index])
+                    .sum();
+                if weight < target {
+                    Weight::TooLight
+                } else if weight > target {
+                    Weight::TooHeavy
+                } else {
+                    Weight::Right
+                }
+            });
+            assert_eq!(found, Some(target));
+            assert!(tried <= target + 1);
+        }
+
+        assert_eq!(search(3, |_| Weight::TooLight), None);
+        let mut tried = 0;
+        search(8, |_| {
+            tried += 1;
+            Weight::TooHeavy
+        });
+        assert_eq!(tried, 1);
+    }
+}
@@ -0,0 +1,158 @@
+// Talking to programs which read and write ASCII text, a line at a time.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+
+use super::process::{Input, Output};
+
+// Asks for a line whenever the program wants input and there's none left,
+// and hands it over a character at a time with a newline on the end.
+pub struct AsciiInput<R, W> {
+    reader: RefCell<R>,
+    prompt_to: RefCell<W>,
+    prompt: String,
+    pending: RefCell<VecDeque<i64>>,
+}
+
+impl AsciiInput<BufReader<File>, File> {
+    // Prompt on and read from the terminal, which works even when the
+    // program came in on stdin.
+    pub fn terminal(prompt: &str) -> Result<Self, String> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map_err(|error| format!("Can't open the terminal: {}", error))?;
+        let reader = tty
+            .try_clone()
+            .map_err(|error| format!("Can't open the terminal: {}", error))?;
+        Ok(AsciiInput::new(BufReader::new(reader), tty, prompt))
+    }
+}
+
+impl<R: BufRead, W: Write> AsciiInput<R, W> {
+    pub fn new(reader: R, prompt_to: W, prompt: &str) -> Self {
+        AsciiInput {
+            reader: RefCell::new(reader),
+            prompt_to: RefCell::new(prompt_to),
+            prompt: prompt.to_string(),
+            pending: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    // The next line, or None at the end of the input.
+    fn read_line(&self) -> Option<String> {
+        let mut prompt_to = self.prompt_to.borrow_mut();
+        let _ = write!(prompt_to, "{}", self.prompt).and_then(|_| prompt_to.flush());
+
+        let mut line = String::new();
+        match self.reader.borrow_mut().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()),
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Input<i64> for AsciiInput<R, W> {
+    fn get(&self) -> Option<i64> {
+        if self.pending.borrow().is_empty() {
+            let line = self.read_line()?;
+            let mut pending = self.pending.borrow_mut();
+            pending.extend(line.bytes().map(i64::from));
+            pending.push_back(i64::from(b'\n'));
+        }
+        self.pending.borrow_mut().pop_front()
+    }
+}
+
+// Writes characters as the program outputs them.  Anything which isn't ASCII
+// is written as a number on its own line, after a label if there is one, and
+// kept to be looked at afterwards.
+pub struct AsciiOutput<W> {
+    writer: RefCell<W>,
+    label: Option<String>,
+    values: RefCell<Vec<i64>>,
+}
+
+impl<W: Write> AsciiOutput<W> {
+    pub fn new(writer: W) -> Self {
+        AsciiOutput {
+            writer: RefCell::new(writer),
+            label: None,
+            values: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn labelled(writer: W, label: &str) -> Self {
+        AsciiOutput {
+            label: Some(label.to_string()),
+            ..AsciiOutput::new(writer)
+        }
+    }
+
+    // The values which weren't ASCII characters.
+    pub fn values(&self) -> Vec<i64> {
+        self.values.borrow().clone()
+    }
+}
+
+impl<W: Write> Output<i64> for AsciiOutput<W> {
+    // Output to the terminal isn't worth stopping the program over if it
+    // fails, so errors are ignored.
+    fn put(&self, value: i64) {
+        let mut writer = self.writer.borrow_mut();
+        let _ = match (value, &self.label) {
+            (0..=127, _) => write!(writer, "{}", value as u8 as char),
+            (_, Some(label)) => writeln!(writer, "{}: {}", label, value),
+            (_, None) => writeln!(writer, "{}", value),
+        };
+        if !(0..=127).contains(&value) {
+            self.values.borrow_mut().push(value);
+        }
+        let _ = writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::{Process, State};
+    use crate::program::Program;
+
+    // Echoes each character it reads until it reads a 'q', then outputs 1000.
+    const ECHO: &str = "3,100,1008,100,113,101,1005,101,14,4,100,1105,1,0,104,1000,99";
+
+    #[test]
+    fn console() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let mut prompts = vec![];
+        let mut printed = vec![];
+        let input = AsciiInput::new("hi\r\nthere\nq\n".as_bytes(), &mut prompts, "> ");
+        let output = AsciiOutput::labelled(&mut printed, "Answer");
+
+        let mut process = Process::new("TEST", &program, input, &output);
+        assert_eq!(process.execute(), State::Complete);
+        drop(process);
+        assert_eq!(output.values(), [1000]);
+        drop(output);
+
+        assert_eq!(prompts, b"> > > ");
+        assert_eq!(printed, b"hi\nthere\nAnswer: 1000\n");
+    }
+
+    #[test]
+    fn end_of_input() {
+        let program = Program::parse(ECHO.as_bytes()).unwrap();
+        let mut printed = vec![];
+        let input = AsciiInput::new("ok".as_bytes(), std::io::sink(), "");
+        let output = AsciiOutput::new(&mut printed);
+
+        let mut process = Process::new("TEST", &program, input, output);
+        assert_eq!(process.execute(), State::Blocked);
+        drop(process);
+
+        assert_eq!(printed, b"ok\n");
+    }
+}
@@ -0,0 +1,170 @@
+// Host functions for opcodes the Intcode computer doesn't define itself, so
+// that programs can call out to whatever is running them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// The most parameters an extension can take, including the one its result is
+// written through, which is the most any other instruction takes.
+pub const MAX_PARAMETERS: usize = 3;
+
+type Handler = Box<dyn FnMut(&[i64], &[i64]) -> i64>;
+
+pub struct Extension {
+    pub(crate) name: String,
+    pub(crate) inputs: usize,
+    pub(crate) writes: bool,
+    handler: RefCell<Handler>,
+}
+
+impl Extension {
+    // The handler is called with the values of `inputs` parameters and the
+    // process's memory.  The name is what the instruction is called when
+    // it's disassembled.
+    pub fn new(
+        name: &str,
+        inputs: usize,
+        mut handler: impl FnMut(&[i64], &[i64]) + 'static,
+    ) -> Self {
+        Extension {
+            name: name.to_string(),
+            inputs,
+            writes: false,
+            handler: RefCell::new(Box::new(move |values, memory| {
+                handler(values, memory);
+                0
+            })),
+        }
+    }
+
+    // Like `new`, but whatever the handler returns is written through one
+    // more parameter after the inputs.
+    pub fn returning(
+        name: &str,
+        inputs: usize,
+        handler: impl FnMut(&[i64], &[i64]) -> i64 + 'static,
+    ) -> Self {
+        Extension {
+            name: name.to_string(),
+            inputs,
+            writes: true,
+            handler: RefCell::new(Box::new(handler)),
+        }
+    }
+
+    pub(crate) fn parameters(&self) -> usize {
+        self.inputs + self.writes as usize
+    }
+
+    pub(crate) fn call(&self, values: &[i64], memory: &[i64]) -> i64 {
+        (self.handler.borrow_mut())(values, memory)
+    }
+}
+
+// Which extension each opcode runs, to be shared between any processes which
+// use them.
+#[derive(Default)]
+pub struct Extensions {
+    extensions: HashMap<i64, Extension>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Extensions::default()
+    }
+
+    // Only the opcodes from 1 to 98 which the computer doesn't already use
+    // can be extended.
+    pub fn register(&mut self, opcode: i64, extension: Extension) -> Result<(), String> {
+        if !(1..=98).contains(&opcode) {
+            return Err(format!("Opcode {} can't be extended", opcode));
+        }
+        if opcode <= 9 {
+            return Err(format!("Opcode {} is already an instruction", opcode));
+        }
+        if self.extensions.contains_key(&opcode) {
+            return Err(format!("Opcode {} is already an extension", opcode));
+        }
+        if extension.parameters() > MAX_PARAMETERS {
+            return Err(format!(
+                "{} takes {} parameters, but extensions can only take {}",
+                extension.name,
+                extension.parameters(),
+                MAX_PARAMETERS
+            ));
+        }
+
+        self.extensions.insert(opcode, extension);
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, opcode: i64) -> Option<&Extension> {
+        self.extensions.get(&opcode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::{Channel, Process, State};
+    use crate::program::Program;
+    use std::rc::Rc;
+
+    #[test]
+    fn register() {
+        let mut extensions = Extensions::new();
+        let nothing = || Extension::new("nothing", 0, |_, _| {});
+        assert!(extensions.register(42, nothing()).is_ok());
+        assert_eq!(
+            extensions.register(42, nothing()),
+            Err("Opcode 42 is already an extension".to_string())
+        );
+        assert_eq!(
+            extensions.register(7, nothing()),
+            Err("Opcode 7 is already an instruction".to_string())
+        );
+        assert_eq!(
+            extensions.register(99, nothing()),
+            Err("Opcode 99 can't be extended".to_string())
+        );
+        assert_eq!(
+            extensions.register(43, Extension::returning("big", 3, |_, _| 0)),
+            Err("big takes 4 parameters, but extensions can only take 3".to_string())
+        );
+    }
+
+    #[test]
+    fn host_functions() {
+        // Prints the string at address 13, then outputs the sum of two
+        // "random" numbers.
+        let program = "150,13,51,100,51,101,1,100,101,102,4,102,99,104,105,0";
+        let program = Program::parse(program.as_bytes()).unwrap();
+
+        let printed = Rc::new(RefCell::new(String::new()));
+        let mut extensions = Extensions::new();
+        let print = printed.clone();
+        let print = Extension::new("print", 1, move |values, memory| {
+            let start = values[0] as usize;
+            let text = memory[start..].iter().take_while(|&&c| c != 0);
+            print.borrow_mut().extend(text.map(|&c| c as u8 as char));
+        });
+        extensions.register(50, print).unwrap();
+        let mut next = 0;
+        let random = Extension::returning("random", 0, move |_, _| {
+            next += 10;
+            next
+        });
+        extensions.register(51, random).unwrap();
+
+        let output = Channel::new();
+        let mut process = Process::new("TEST", &program, Channel::new(), &output);
+        process.set_extensions(Rc::new(extensions));
+        assert_eq!(process.disassemble(0), "print 13");
+        assert_eq!(process.disassemble(2), "random -> [100]");
+        assert_eq!(process.execute(), State::Complete);
+        drop(process);
+
+        assert_eq!(printed.borrow().as_str(), "hi");
+        assert_eq!(Vec::from(output), vec![30]);
+    }
+}
@@ -0,0 +1,176 @@
+// Input read from a file and output appended to one, so that a program can be
+// driven entirely from the filesystem.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use super::process::{Input, Output};
+
+// The values in a file, one per line or separated by commas, handed to the
+// program in order.
+pub struct FileInput {
+    values: RefCell<VecDeque<i64>>,
+}
+
+impl FileInput {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Can't read {}: {}", path.display(), error))?;
+        FileInput::parse(&text).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let values = text
+            .split(&[',', '\n'][..])
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("{:?} isn't a number", value))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(FileInput {
+            values: RefCell::new(values),
+        })
+    }
+
+    // How many values are left.
+    pub fn remaining(&self) -> usize {
+        self.values.borrow().len()
+    }
+}
+
+impl Input<i64> for FileInput {
+    fn get(&self) -> Option<i64> {
+        self.values.borrow_mut().pop_front()
+    }
+}
+
+// Writes each value the program outputs on its own line.  Output can't fail,
+// so the first error is kept to be picked up by `finish`.
+pub struct FileOutput<W = File> {
+    writer: RefCell<W>,
+    error: RefCell<Option<String>>,
+}
+
+impl FileOutput {
+    // Add to the end of the file, creating it if it doesn't exist.
+    pub fn append(path: &Path) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| format!("Can't open {}: {}", path.display(), error))?;
+        Ok(FileOutput::new(file))
+    }
+}
+
+impl<W: Write> FileOutput<W> {
+    pub fn new(writer: W) -> Self {
+        FileOutput {
+            writer: RefCell::new(writer),
+            error: RefCell::new(None),
+        }
+    }
+
+    // The writer, or the first thing which went wrong writing to it.
+    pub fn finish(self) -> Result<W, String> {
+        let mut writer = self.writer.into_inner();
+        if let Some(error) = self.error.into_inner() {
+            return Err(error);
+        }
+        writer
+            .flush()
+            .map_err(|error| format!("Can't write output: {}", error))?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write> Output<i64> for FileOutput<W> {
+    fn put(&self, value: i64) {
+        if self.error.borrow().is_some() {
+            return;
+        }
+
+        if let Err(error) = writeln!(self.writer.borrow_mut(), "{}", value) {
+            *self.error.borrow_mut() = Some(format!("Can't write output: {}", error));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::{Process, State};
+    use crate::program::Program;
+    use std::io;
+
+    // Fails every write.
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn inputs() {
+        let input = FileInput::parse("1,2\n-3\n\n 4 , 5,\n").unwrap();
+        assert_eq!(input.remaining(), 5);
+        let values: Vec<_> = std::iter::from_fn(|| input.get()).collect();
+        assert_eq!(values, [1, 2, -3, 4, 5]);
+
+        assert_eq!(
+            FileInput::parse("1,two").err(),
+            Some("\"two\" isn't a number".to_string())
+        );
+    }
+
+    #[test]
+    fn outputs() {
+        let output = FileOutput::new(vec![]);
+        output.put(12);
+        output.put(-1);
+        assert_eq!(output.finish().unwrap(), b"12\n-1\n");
+
+        let output = FileOutput::new(Broken);
+        output.put(1);
+        assert_eq!(
+            output.finish().err(),
+            Some("Can't write output: broken".to_string())
+        );
+    }
+
+    #[test]
+    fn files() {
+        // Adds up pairs of numbers.
+        let program = Program::parse("3,13,3,14,1,13,14,15,4,15,1105,1,0".as_bytes()).unwrap();
+        let name = format!("advent-intcode-{}", std::process::id());
+        let inputs = std::env::temp_dir().join(format!("{}.in", name));
+        let outputs = std::env::temp_dir().join(format!("{}.out", name));
+        fs::write(&inputs, "1,2\n3,4\n").unwrap();
+        fs::write(&outputs, "0\n").unwrap();
+
+        let input = FileInput::open(&inputs).unwrap();
+        let output = FileOutput::append(&outputs).unwrap();
+        let mut process = Process::new("TEST", &program, &input, &output);
+        assert_eq!(process.execute(), State::Blocked);
+        drop(process);
+        output.finish().unwrap();
+
+        assert_eq!(fs::read_to_string(&outputs).unwrap(), "0\n3\n7\n");
+        fs::remove_file(inputs).unwrap();
+        fs::remove_file(outputs).unwrap();
+
+        assert!(FileInput::open(Path::new("/nonexistent/values")).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+// The Intcode computer, for the days which run Intcode programs.
+
+pub mod ascii;
+pub mod extension;
+pub mod file;
+pub mod process;
+pub mod program;
+pub mod scheduler;
+pub mod trace;
+pub mod transcript;
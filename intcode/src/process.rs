@@ -0,0 +1,1491 @@
+use super::extension::Extensions;
+use super::program::Program;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Complete,
+    Blocked,
+    // Stopped after running as many instructions as it was allowed to.
+    Yielded,
+}
+
+// Something a program did which it shouldn't have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultKind {
+    UnknownOpcode(i64),
+    UnknownMode(i64),
+    AddressOutOfRange(i64),
+    // An access at or beyond the process's memory limit.
+    MemoryLimit { address: i64, limit: usize },
+    // Only a fault in strict mode, otherwise the parameter is used as an
+    // address as if it were in position mode.
+    ImmediateWrite,
+    // Only a fault in strict mode, otherwise it only matters if the relative
+    // base is used while it's negative.
+    NegativeRelativeBase(i64),
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FaultKind::UnknownOpcode(opcode) => write!(f, "Unknown opcode {}", opcode),
+            FaultKind::UnknownMode(mode) => write!(f, "Unknown parameter mode {}", mode),
+            FaultKind::AddressOutOfRange(address) => {
+                write!(f, "Address {} is out of range", address)
+            }
+            FaultKind::MemoryLimit { address, limit } => write!(
+                f,
+                "Address {} is past the memory limit of {} cells",
+                address, limit
+            ),
+            FaultKind::ImmediateWrite => write!(f, "Write through an immediate mode parameter"),
+            FaultKind::NegativeRelativeBase(base) => {
+                write!(f, "Relative base moved to {}", base)
+            }
+        }
+    }
+}
+
+// A fault along with the address of the instruction which caused it, and
+// the instruction itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub kind: FaultKind,
+    pub address: usize,
+    pub instruction: Vec<i64>,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let words: Vec<String> = self.instruction.iter().map(i64::to_string).collect();
+        write!(
+            f,
+            "{} at address {} ({})",
+            self.kind,
+            self.address,
+            words.join(",")
+        )
+    }
+}
+
+#[derive(Debug)]
+enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+struct Modes(i64);
+
+impl Modes {
+    fn mode(&self, index: usize) -> Result<Mode, FaultKind> {
+        let mode = (self.0 % 10_i64.pow(index as u32 + 1)) / 10_i64.pow(index as u32);
+        match mode {
+            0 => Ok(Mode::Position),
+            1 => Ok(Mode::Immediate),
+            2 => Ok(Mode::Relative),
+            _ => Err(FaultKind::UnknownMode(mode)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Parameter {
+    mode: Mode,
+    value: i64,
+}
+
+// Immediate parameters are written as they are, addresses in brackets.
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "{}", self.value),
+            Mode::Relative if self.value < 0 => write!(f, "[rb{}]", self.value),
+            Mode::Relative => write!(f, "[rb+{}]", self.value),
+        }
+    }
+}
+
+struct Parameters<'a> {
+    data: &'a [i64],
+    modes: Modes,
+}
+
+impl<'a> Parameters<'a> {
+    fn new(data: &'a [i64], modes: i64) -> Self {
+        Parameters {
+            data,
+            modes: Modes(modes),
+        }
+    }
+
+    fn get(&self, index: usize) -> Result<Parameter, FaultKind> {
+        Ok(Parameter {
+            mode: self.modes.mode(index)?,
+            value: self.data[index],
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Instruction {
+    Add {
+        x: Parameter,
+        y: Parameter,
+        output: Parameter,
+    },
+    Mul {
+        x: Parameter,
+        y: Parameter,
+        output: Parameter,
+    },
+    Input {
+        output: Parameter,
+    },
+    Output {
+        input: Parameter,
+    },
+    JumpIfTrue {
+        input: Parameter,
+        address: Parameter,
+    },
+    JumpIfFalse {
+        input: Parameter,
+        address: Parameter,
+    },
+    LessThan {
+        x: Parameter,
+        y: Parameter,
+        output: Parameter,
+    },
+    Equals {
+        x: Parameter,
+        y: Parameter,
+        output: Parameter,
+    },
+    RelativeBaseOffset {
+        offset: Parameter,
+    },
+    Exit,
+    Extension {
+        name: String,
+        opcode: i64,
+        inputs: Vec<Parameter>,
+        output: Option<Parameter>,
+    },
+}
+
+// The most words any instruction takes up.
+const MAX_INSTRUCTION_SIZE: usize = 4;
+
+impl Instruction {
+    fn parse(data: &[i64], extensions: &Extensions) -> Result<Self, FaultKind> {
+        use Instruction::*;
+        let opcode = data[0] % 100;
+        let parameters = Parameters::new(&data[1..], data[0] / 100);
+        match opcode {
+            1 => Ok(Add {
+                x: parameters.get(0)?,
+                y: parameters.get(1)?,
+                output: parameters.get(2)?,
+            }),
+            2 => Ok(Mul {
+                x: parameters.get(0)?,
+                y: parameters.get(1)?,
+                output: parameters.get(2)?,
+            }),
+            3 => Ok(Input {
+                output: parameters.get(0)?,
+            }),
+            4 => Ok(Output {
+                input: parameters.get(0)?,
+            }),
+            5 => Ok(JumpIfTrue {
+                input: parameters.get(0)?,
+                address: parameters.get(1)?,
+            }),
+            6 => Ok(JumpIfFalse {
+                input: parameters.get(0)?,
+                address: parameters.get(1)?,
+            }),
+            7 => Ok(LessThan {
+                x: parameters.get(0)?,
+                y: parameters.get(1)?,
+                output: parameters.get(2)?,
+            }),
+            8 => Ok(Equals {
+                x: parameters.get(0)?,
+                y: parameters.get(1)?,
+                output: parameters.get(2)?,
+            }),
+            9 => Ok(RelativeBaseOffset {
+                offset: parameters.get(0)?,
+            }),
+            99 => Ok(Exit),
+            _ => match extensions.get(opcode) {
+                Some(extension) => Ok(Extension {
+                    name: extension.name.clone(),
+                    opcode,
+                    inputs: (0..extension.inputs)
+                        .map(|index| parameters.get(index))
+                        .collect::<Result<_, _>>()?,
+                    output: match extension.writes {
+                        true => Some(parameters.get(extension.inputs)?),
+                        false => None,
+                    },
+                }),
+                None => Err(FaultKind::UnknownOpcode(data[0])),
+            },
+        }
+    }
+
+    fn size(&self) -> usize {
+        use Instruction::*;
+        match self {
+            Add { .. } | Mul { .. } | LessThan { .. } | Equals { .. } => 4,
+            JumpIfTrue { .. } | JumpIfFalse { .. } => 3,
+            Input { .. } | Output { .. } | RelativeBaseOffset { .. } => 2,
+            Exit => 1,
+            Extension { inputs, output, .. } => 1 + inputs.len() + output.iter().count(),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Instruction::*;
+        match self {
+            Add { x, y, output } => write!(f, "add {}, {} -> {}", x, y, output),
+            Mul { x, y, output } => write!(f, "mul {}, {} -> {}", x, y, output),
+            Input { output } => write!(f, "in -> {}", output),
+            Output { input } => write!(f, "out {}", input),
+            JumpIfTrue { input, address } => write!(f, "jt {}, {}", input, address),
+            JumpIfFalse { input, address } => write!(f, "jf {}, {}", input, address),
+            LessThan { x, y, output } => write!(f, "lt {}, {} -> {}", x, y, output),
+            Equals { x, y, output } => write!(f, "eq {}, {} -> {}", x, y, output),
+            RelativeBaseOffset { offset } => write!(f, "arb {}", offset),
+            Exit => write!(f, "hlt"),
+            Extension {
+                name,
+                inputs,
+                output,
+                ..
+            } => {
+                write!(f, "{}", name)?;
+                for (index, input) in inputs.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    write!(f, "{}{}", separator, input)?;
+                }
+                match output {
+                    Some(output) => write!(f, " -> {}", output),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+// The instruction starting with these words written out in assembly, or
+// what's wrong with it if it isn't one.
+fn disassemble(words: &[i64], extensions: &Extensions) -> String {
+    let mut data = [0; MAX_INSTRUCTION_SIZE];
+    for (word, value) in data.iter_mut().zip(words) {
+        *word = *value;
+    }
+    match Instruction::parse(&data, extensions) {
+        Ok(instruction) => instruction.to_string(),
+        Err(kind) => format!("?? ({})", kind),
+    }
+}
+
+// The number of words in the instruction with this opcode, or 1 if there's
+// no such instruction.
+fn instruction_size(opcode: i64, extensions: &Extensions) -> usize {
+    match opcode % 100 {
+        1 | 2 | 7 | 8 => 4,
+        5 | 6 => 3,
+        3 | 4 | 9 => 2,
+        opcode => extensions
+            .get(opcode)
+            .map_or(1, |extension| 1 + extension.parameters()),
+    }
+}
+
+pub trait Input<T> {
+    fn get(&self) -> Option<T>;
+}
+
+pub trait Output<T> {
+    fn put(&self, value: T);
+}
+
+// Something which runs a program a slice at a time.
+pub trait Execute {
+    fn execute_for(&mut self, max_instructions: usize) -> State;
+
+    // How many instructions have been run altogether, so that it's possible
+    // to tell whether a blocked program made any progress first, or None if
+    // that isn't known.
+    fn instructions(&self) -> Option<usize> {
+        None
+    }
+}
+
+pub struct Channel<T> {
+    buffer: RefCell<VecDeque<T>>,
+}
+
+impl<T> Channel<T> {
+    pub fn new() -> Self {
+        Channel {
+            buffer: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    // How many values are waiting to be read.
+    pub fn len(&self) -> usize {
+        self.buffer.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.borrow().is_empty()
+    }
+
+    // The next value to be read, without reading it.
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.buffer.borrow().front().cloned()
+    }
+
+    // Read every value waiting at once.
+    pub fn drain(&self) -> Vec<T> {
+        self.buffer.borrow_mut().drain(..).collect()
+    }
+}
+
+impl<T> Default for Channel<T> {
+    fn default() -> Self {
+        Channel::new()
+    }
+}
+
+impl<T> From<Channel<T>> for Vec<T> {
+    fn from(channel: Channel<T>) -> Self {
+        channel.buffer.into_inner().into()
+    }
+}
+
+impl<T> IntoIterator for Channel<T> {
+    type Item = T;
+    type IntoIter = <VecDeque<T> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.buffer.into_inner().into_iter()
+    }
+}
+
+impl<T, I: Input<T> + ?Sized> Input<T> for &I {
+    fn get(&self) -> Option<T> {
+        (**self).get()
+    }
+}
+
+impl<T, O: Output<T> + ?Sized> Output<T> for &O {
+    fn put(&self, value: T) {
+        (**self).put(value)
+    }
+}
+
+impl<T, I: Input<T> + ?Sized> Input<T> for Rc<I> {
+    fn get(&self) -> Option<T> {
+        (**self).get()
+    }
+}
+
+impl<T, O: Output<T> + ?Sized> Output<T> for Rc<O> {
+    fn put(&self, value: T) {
+        (**self).put(value)
+    }
+}
+
+impl<T, I: Input<T> + ?Sized> Input<T> for Box<I> {
+    fn get(&self) -> Option<T> {
+        (**self).get()
+    }
+}
+
+impl<T, O: Output<T> + ?Sized> Output<T> for Box<O> {
+    fn put(&self, value: T) {
+        (**self).put(value)
+    }
+}
+
+// Input and output which can be picked at runtime, so that a process has the
+// same type whatever it's plugged into.
+pub type DynInput<'a> = Box<dyn Input<i64> + 'a>;
+pub type DynOutput<'a> = Box<dyn Output<i64> + 'a>;
+pub type DynProcess<'a> = Process<DynInput<'a>, DynOutput<'a>>;
+
+impl<T> Input<T> for Channel<T> {
+    fn get(&self) -> Option<T> {
+        self.buffer.borrow_mut().pop_front()
+    }
+}
+
+impl<T> Output<T> for Channel<T> {
+    fn put(&self, value: T) {
+        self.buffer.borrow_mut().push_back(value)
+    }
+}
+
+// Passes everything output to both of two outputs, first one then the other.
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Tee<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Tee { first, second }
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<T: Clone, A: Output<T>, B: Output<T>> Output<T> for Tee<A, B> {
+    fn put(&self, value: T) {
+        self.first.put(value.clone());
+        self.second.put(value)
+    }
+}
+
+// How many cells of memory a process can use unless it's given some other
+// limit.  Memory is only allocated as it's written to.
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 20;
+
+// What a hook is shown of the process at an instruction, before or after it
+// runs.
+pub struct Context<'a> {
+    pub address: usize,
+    pub instruction: &'a [i64],
+    pub memory: &'a [i64],
+    pub relative_base: i64,
+    // How many instructions have been run so far.
+    pub instructions: usize,
+    extensions: &'a Extensions,
+}
+
+impl<'a> Context<'a> {
+    pub fn opcode(&self) -> i64 {
+        self.instruction[0] % 100
+    }
+
+    pub fn disassemble(&self) -> String {
+        disassemble(self.instruction, self.extensions)
+    }
+}
+
+// Something which watches a process run an instruction at a time, like a
+// tracer or a profiler.  Every instruction is seen before it runs, but only
+// ones which don't block or fault are seen afterwards.
+pub trait Hook {
+    fn before(&mut self, _context: &Context) {}
+
+    fn after(&mut self, _context: &Context) {}
+}
+
+// So that whatever added a hook can still get at it.
+impl<H: Hook + ?Sized> Hook for Rc<RefCell<H>> {
+    fn before(&mut self, context: &Context) {
+        self.borrow_mut().before(context)
+    }
+
+    fn after(&mut self, context: &Context) {
+        self.borrow_mut().after(context)
+    }
+}
+
+// An instruction which has been run, with enough of the state from before it
+// ran to undo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub address: usize,
+    pub relative_base: i64,
+    // The address written to, if any, and what was there before.
+    pub overwrote: Option<(usize, i64)>,
+}
+
+pub struct Process<I, O> {
+    name: String,
+    memory: Vec<i64>,
+    memory_limit: usize,
+    instruction_pointer: usize,
+    instructions: usize,
+    relative_base: i64,
+    strict: bool,
+    history: VecDeque<Step>,
+    history_limit: usize,
+    overwrote: Option<(usize, i64)>,
+    hooks: Vec<Box<dyn Hook>>,
+    extensions: Rc<Extensions>,
+    input: I,
+    output: O,
+}
+
+impl<I, O> Process<I, O> {
+    pub fn fork<J, P>(&self, input: J, output: P) -> Process<J, P> {
+        Process {
+            name: self.name.clone(),
+            memory: self.memory.clone(),
+            memory_limit: self.memory_limit,
+            instruction_pointer: self.instruction_pointer,
+            instructions: self.instructions,
+            relative_base: self.relative_base,
+            strict: self.strict,
+            history: self.history.clone(),
+            history_limit: self.history_limit,
+            overwrote: None,
+            hooks: vec![],
+            extensions: self.extensions.clone(),
+            input,
+            output,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // In strict mode things which are probably mistakes in the program are
+    // faults too, rather than being carried on past.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // Plug the process into something else, returning what it was plugged
+    // into before.
+    pub fn replace_input(&mut self, input: I) -> I {
+        mem::replace(&mut self.input, input)
+    }
+
+    pub fn replace_output(&mut self, output: O) -> O {
+        mem::replace(&mut self.output, output)
+    }
+
+    // Accessing any address from `cells` upwards is a fault.
+    pub fn set_memory_limit(&mut self, cells: usize) {
+        self.memory_limit = cells;
+    }
+
+    // Run host functions for opcodes the computer doesn't define itself.
+    pub fn set_extensions(&mut self, extensions: Rc<Extensions>) {
+        self.extensions = extensions;
+    }
+
+    // Call a hook around every instruction from now on.  Hooks aren't
+    // copied into forks.
+    pub fn add_hook(&mut self, hook: impl Hook + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    fn call_hooks(&mut self, address: usize, words: &[i64], call: fn(&mut dyn Hook, &Context)) {
+        let mut hooks = mem::take(&mut self.hooks);
+        let context = Context {
+            address,
+            instruction: words,
+            memory: &self.memory,
+            relative_base: self.relative_base,
+            instructions: self.instructions,
+            extensions: &self.extensions,
+        };
+        for hook in hooks.iter_mut() {
+            call(hook.as_mut(), &context);
+        }
+        self.hooks = hooks;
+    }
+
+    // Keep the last `steps` instructions run, so that they can be stepped back
+    // through to see how the program got where it is.  No history is kept
+    // unless this is called.
+    pub fn record_history(&mut self, steps: usize) {
+        self.history_limit = steps;
+        while self.history.len() > steps {
+            self.history.pop_front();
+        }
+    }
+
+    // The instructions in the history, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &Step> {
+        self.history.iter()
+    }
+
+    // Undo up to `steps` instructions from the history, returning how many
+    // were undone.  Input and output can't be undone, so any input that's
+    // read again after stepping back is the next value rather than the same
+    // one.
+    pub fn step_back(&mut self, steps: usize) -> usize {
+        for undone in 0..steps {
+            let step = match self.history.pop_back() {
+                Some(step) => step,
+                None => return undone,
+            };
+            if let Some((address, value)) = step.overwrote {
+                self.memory[address] = value;
+            }
+            self.instruction_pointer = step.address;
+            self.relative_base = step.relative_base;
+            self.instructions -= 1;
+        }
+        steps
+    }
+
+    // Everything past the end of the memory that's been written to is zero.
+    fn read(&self, address: usize) -> i64 {
+        self.memory.get(address).cloned().unwrap_or(0)
+    }
+
+    // The instruction at an address written out in assembly, or what's wrong
+    // with it if it isn't one.
+    pub fn disassemble(&self, address: usize) -> String {
+        disassemble(&self.words(address), &self.extensions)
+    }
+
+    // The words making up the instruction at an address.
+    fn words(&self, address: usize) -> Vec<i64> {
+        let size = instruction_size(self.read(address), &self.extensions);
+        (address..address + size)
+            .map(|address| self.read(address))
+            .collect()
+    }
+}
+
+// The memory around the instruction pointer is shown this many rows either
+// side of the one it's in, with this many cells to a row.
+const DUMP_ROWS: usize = 2;
+const DUMP_ROW_CELLS: usize = 8;
+
+impl<I, O> fmt::Debug for Process<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Process")
+            .field("name", &self.name)
+            .field("instruction_pointer", &self.instruction_pointer)
+            .field("relative_base", &self.relative_base)
+            .field("instructions", &self.instructions)
+            .field("next", &self.disassemble(self.instruction_pointer))
+            .field("memory_size", &self.memory.len())
+            .field("memory_limit", &self.memory_limit)
+            .field("strict", &self.strict)
+            .finish()
+    }
+}
+
+// Where the process has got to, for working out what went wrong with it: the
+// registers, the next instruction and the memory around it, with the cell the
+// instruction pointer is at marked.
+impl<I, O> fmt::Display for Process<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Process {}: ip {}, relative base {}, {} instructions run",
+            self.name, self.instruction_pointer, self.relative_base, self.instructions
+        )?;
+        write!(
+            f,
+            "{:>8}: {}",
+            self.instruction_pointer,
+            self.disassemble(self.instruction_pointer)
+        )?;
+
+        let row = self.instruction_pointer / DUMP_ROW_CELLS;
+        let first = row.saturating_sub(DUMP_ROWS) * DUMP_ROW_CELLS;
+        let last = (row + DUMP_ROWS + 1) * DUMP_ROW_CELLS;
+        let end = last.min(self.memory.len().max(self.instruction_pointer + 1));
+        for start in (first..end).step_by(DUMP_ROW_CELLS) {
+            write!(f, "\n{:>8}:", start)?;
+            for address in start..(start + DUMP_ROW_CELLS).min(end) {
+                let marker = if address == self.instruction_pointer {
+                    ">"
+                } else {
+                    ""
+                };
+                write!(f, " {:>7}", format!("{}{}", marker, self.read(address)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<I: Input<i64>, O: Output<i64>> Process<I, O> {
+    pub fn new<T: ToString>(name: T, program: &Program, input: I, output: O) -> Self {
+        Process {
+            name: name.to_string(),
+            memory: program.data.to_vec(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            instruction_pointer: 0,
+            instructions: 0,
+            relative_base: 0,
+            strict: false,
+            history: VecDeque::new(),
+            history_limit: 0,
+            overwrote: None,
+            hooks: vec![],
+            extensions: Rc::new(Extensions::new()),
+            input,
+            output,
+        }
+    }
+
+    fn fault(&self, address: usize, kind: FaultKind) -> Fault {
+        Fault {
+            kind,
+            address,
+            instruction: self.words(address),
+        }
+    }
+
+    fn next_instruction(&mut self) -> Result<Instruction, FaultKind> {
+        let address = self.address(self.instruction_pointer as i64)?;
+        let mut data = [0; MAX_INSTRUCTION_SIZE];
+        for (offset, word) in data.iter_mut().enumerate() {
+            *word = self.read(address + offset);
+        }
+        let instruction = Instruction::parse(&data, &self.extensions)?;
+        self.instruction_pointer += instruction.size();
+        Ok(instruction)
+    }
+
+    fn address(&self, address: i64) -> Result<usize, FaultKind> {
+        if address < 0 {
+            Err(FaultKind::AddressOutOfRange(address))
+        } else if address as usize >= self.memory_limit {
+            Err(FaultKind::MemoryLimit {
+                address,
+                limit: self.memory_limit,
+            })
+        } else {
+            Ok(address as usize)
+        }
+    }
+
+    fn resolve(&self, parameter: &Parameter) -> Result<i64, FaultKind> {
+        use Mode::*;
+        match parameter.mode {
+            Position => Ok(self.read(self.address(parameter.value)?)),
+            Immediate => Ok(parameter.value),
+            Relative => Ok(self.read(self.address(self.relative_base + parameter.value)?)),
+        }
+    }
+
+    fn resolve_address(&self, parameter: &Parameter) -> Result<usize, FaultKind> {
+        use Mode::*;
+        match parameter.mode {
+            Relative => self.address(self.relative_base + parameter.value),
+            Immediate if self.strict => Err(FaultKind::ImmediateWrite),
+            Position | Immediate => self.address(parameter.value),
+        }
+    }
+
+    fn write(&mut self, parameter: &Parameter, value: i64) -> Result<(), FaultKind> {
+        let address = self.resolve_address(parameter)?;
+        if self.history_limit > 0 {
+            self.overwrote = Some((address, self.read(address)));
+        }
+        self.set(address, value);
+        Ok(())
+    }
+
+    fn jump(&mut self, address: &Parameter) -> Result<(), FaultKind> {
+        let address = self.resolve(address)?;
+        self.instruction_pointer = self.address(address)?;
+        Ok(())
+    }
+
+    // Run one instruction, returning the state to stop in if it's one which
+    // stops the program.
+    fn step(&mut self, instruction: Instruction) -> Result<Option<State>, FaultKind> {
+        match instruction {
+            Instruction::Add { x, y, output } => {
+                let value = self.resolve(&x)? + self.resolve(&y)?;
+                self.write(&output, value)?;
+            }
+            Instruction::Mul { x, y, output } => {
+                let value = self.resolve(&x)? * self.resolve(&y)?;
+                self.write(&output, value)?;
+            }
+            Instruction::Input { ref output } => {
+                if let Some(input) = self.input.get() {
+                    self.write(output, input)?;
+                } else {
+                    self.instruction_pointer -= instruction.size();
+                    return Ok(Some(State::Blocked));
+                }
+            }
+            Instruction::Output { input } => self.output.put(self.resolve(&input)?),
+            Instruction::JumpIfTrue { input, address } => {
+                if self.resolve(&input)? != 0 {
+                    self.jump(&address)?;
+                }
+            }
+            Instruction::JumpIfFalse { input, address } => {
+                if self.resolve(&input)? == 0 {
+                    self.jump(&address)?;
+                }
+            }
+            Instruction::LessThan { x, y, output } => {
+                let value = if self.resolve(&x)? < self.resolve(&y)? {
+                    1
+                } else {
+                    0
+                };
+                self.write(&output, value)?;
+            }
+            Instruction::Equals { x, y, output } => {
+                let value = if self.resolve(&x)? == self.resolve(&y)? {
+                    1
+                } else {
+                    0
+                };
+                self.write(&output, value)?;
+            }
+            Instruction::RelativeBaseOffset { offset } => {
+                let base = self.relative_base + self.resolve(&offset)?;
+                if base < 0 && self.strict {
+                    return Err(FaultKind::NegativeRelativeBase(base));
+                }
+                self.relative_base = base;
+            }
+            Instruction::Exit => return Ok(Some(State::Complete)),
+            Instruction::Extension {
+                opcode,
+                inputs,
+                output,
+                ..
+            } => {
+                let values = inputs
+                    .iter()
+                    .map(|input| self.resolve(input))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let extensions = self.extensions.clone();
+                let extension = extensions.get(opcode).unwrap();
+                let value = extension.call(&values, &self.memory);
+                if let Some(output) = output {
+                    self.write(&output, value)?;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // Run until the program completes or needs more input, or stop at the
+    // instruction which faults, leaving the process where it was before it.
+    pub fn try_execute(&mut self) -> Result<State, Fault> {
+        self.try_execute_for(usize::MAX)
+    }
+
+    // Like `try_execute`, but yield after `max_instructions` instructions.
+    pub fn try_execute_for(&mut self, mut max_instructions: usize) -> Result<State, Fault> {
+        while max_instructions > 0 {
+            max_instructions -= 1;
+            let address = self.instruction_pointer;
+            let relative_base = self.relative_base;
+            self.overwrote = None;
+            let words = if self.hooks.is_empty() {
+                vec![]
+            } else {
+                let words = self.words(address);
+                self.call_hooks(address, &words, |hook, context| hook.before(context));
+                words
+            };
+            let result = self
+                .next_instruction()
+                .and_then(|instruction| self.step(instruction));
+            match result {
+                Ok(Some(State::Blocked)) => return Ok(State::Blocked),
+                Ok(state) => {
+                    self.instructions += 1;
+                    self.remember(address, relative_base);
+                    if !self.hooks.is_empty() {
+                        self.call_hooks(address, &words, |hook, context| hook.after(context));
+                    }
+                    if let Some(state) = state {
+                        return Ok(state);
+                    }
+                }
+                Err(kind) => {
+                    self.instruction_pointer = address;
+                    return Err(self.fault(address, kind));
+                }
+            }
+        }
+        Ok(State::Yielded)
+    }
+
+    fn remember(&mut self, address: usize, relative_base: i64) {
+        if self.history_limit == 0 {
+            return;
+        }
+        if self.history.len() == self.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(Step {
+            address,
+            relative_base,
+            overwrote: self.overwrote.take(),
+        });
+    }
+
+    // Like `try_execute`, for programs which are trusted not to fault.
+    pub fn execute(&mut self) -> State {
+        self.execute_for(usize::MAX)
+    }
+
+    // The values the process outputs, running it only as far as it takes to
+    // produce each one.  `output` is the channel the process outputs to.
+    pub fn stream<'a>(&'a mut self, output: &'a Channel<i64>) -> Stream<'a, I, O> {
+        Stream {
+            process: self,
+            output,
+            state: None,
+            faulted: false,
+        }
+    }
+
+    pub fn set(&mut self, address: usize, value: i64) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+}
+
+impl<'a> DynProcess<'a> {
+    pub fn boxed<T: ToString>(
+        name: T,
+        program: &Program,
+        input: impl Input<i64> + 'a,
+        output: impl Output<i64> + 'a,
+    ) -> Self {
+        Process::new(name, program, Box::new(input), Box::new(output))
+    }
+}
+
+// Runs a process an instruction at a time, handing over whatever it outputs as
+// soon as it's been output.  It ends when the process completes, blocks or
+// faults, with the fault as its last item.
+pub struct Stream<'a, I, O> {
+    process: &'a mut Process<I, O>,
+    output: &'a Channel<i64>,
+    state: Option<State>,
+    faulted: bool,
+}
+
+impl<'a, I, O> Stream<'a, I, O> {
+    // Whether the process completed or blocked, or None if it's still going
+    // or it faulted.
+    pub fn state(&self) -> Option<State> {
+        self.state
+    }
+}
+
+impl<'a, I: Input<i64>, O: Output<i64>> Iterator for Stream<'a, I, O> {
+    type Item = Result<i64, Fault>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.output.get() {
+                return Some(Ok(value));
+            }
+            if self.state.is_some() || self.faulted {
+                return None;
+            }
+            match self.process.try_execute_for(1) {
+                Ok(State::Yielded) => {}
+                Ok(state) => self.state = Some(state),
+                Err(fault) => {
+                    self.faulted = true;
+                    return Some(Err(fault));
+                }
+            }
+        }
+    }
+}
+
+impl<I: Input<i64>, O: Output<i64>> Execute for Process<I, O> {
+    fn execute_for(&mut self, max_instructions: usize) -> State {
+        match self.try_execute_for(max_instructions) {
+            Ok(state) => state,
+            Err(fault) if self.strict => panic!("{}: {}\n{}", self.name, fault, self),
+            Err(fault) => panic!("{}: {}", self.name, fault),
+        }
+    }
+
+    fn instructions(&self) -> Option<usize> {
+        Some(self.instructions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn process(data: Vec<i64>) -> Process<Channel<i64>, Channel<i64>> {
+        let program = Program {
+            data: data.into_boxed_slice(),
+        };
+        Process::new("TEST", &program, Channel::new(), Channel::new())
+    }
+
+    fn run(data: Vec<i64>, strict: bool) -> Result<State, Fault> {
+        let mut process = process(data);
+        process.set_strict(strict);
+        process.try_execute()
+    }
+
+    #[test]
+    fn fork_copies_state() {
+        let program = Program {
+            data: vec![3, 20, 4, 20, 3, 21, 1, 20, 21, 20, 4, 20, 99].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        input.put(5);
+
+        let mut process = Process::new("TEST".to_string(), &program, &input, &output);
+        assert_eq!(process.execute(), State::Blocked);
+        assert_eq!(output.get(), Some(5));
+
+        let fork_input = Channel::new();
+        let fork_output = Channel::new();
+        let mut fork = process.fork(&fork_input, &fork_output);
+
+        input.put(1);
+        fork_input.put(2);
+
+        assert_eq!(process.execute(), State::Complete);
+        assert_eq!(fork.execute(), State::Complete);
+
+        assert_eq!(output.get(), Some(6));
+        assert_eq!(fork_output.get(), Some(7));
+    }
+
+    #[test]
+    fn jump_position_zero() {
+        let program = Program {
+            data: vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        input.put(0);
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        assert_eq!(output.get(), Some(0));
+    }
+
+    #[test]
+    fn jump_position_nonzero() {
+        let program = Program {
+            data: vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        input.put(1);
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        assert_eq!(output.get(), Some(1));
+    }
+
+    #[test]
+    fn jump_immediate_zero() {
+        let program = Program {
+            data: vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        input.put(0);
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        assert_eq!(output.get(), Some(0));
+    }
+
+    #[test]
+    fn jump_immediate_nonzero() {
+        let program = Program {
+            data: vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        input.put(1);
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        assert_eq!(output.get(), Some(1));
+    }
+
+    #[test]
+    fn test_copy() {
+        let program = Program {
+            data: vec![
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ]
+            .into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        for value in program.data.iter() {
+            assert_eq!(output.get(), Some(*value))
+        }
+    }
+
+    #[test]
+    fn big_number() {
+        let program = Program {
+            data: vec![104, 1125899906842624, 99].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        assert_eq!(output.get(), Some(1125899906842624));
+    }
+
+    #[test]
+    fn big_multiply() {
+        let program = Program {
+            data: vec![1102, 34915192, 34915192, 7, 4, 7, 99, 0].into_boxed_slice(),
+        };
+
+        let input = Channel::new();
+        let output = Channel::new();
+
+        Process::new("TEST".to_string(), &program, &input, &output).execute();
+
+        assert_eq!(output.get(), Some(1219070632396864));
+    }
+
+    #[test]
+    fn faults() {
+        let fault = run(vec![1101, 1, 1, 5, 42, 0], false).unwrap_err();
+        assert_eq!(fault.kind, FaultKind::UnknownOpcode(42));
+        assert_eq!(fault.address, 4);
+        assert_eq!(fault.to_string(), "Unknown opcode 42 at address 4 (42)");
+
+        let fault = run(vec![1, 0, -3, 0, 99], false).unwrap_err();
+        assert_eq!(fault.kind, FaultKind::AddressOutOfRange(-3));
+        assert_eq!(fault.instruction, vec![1, 0, -3, 0]);
+
+        assert_eq!(
+            run(vec![301, 0, 0, 0, 99], false).unwrap_err().kind,
+            FaultKind::UnknownMode(3)
+        );
+
+        // Memory past the end of the program is all zeros.
+        let fault = run(vec![1105, 1, 20000], false).unwrap_err();
+        assert_eq!(fault.to_string(), "Unknown opcode 0 at address 20000 (0)");
+    }
+
+    #[test]
+    fn memory_limit() {
+        // Writes to address 5000, then reads it back.
+        let program = vec![1101, 2, 3, 5000, 4, 5000, 99];
+
+        let mut unlimited = process(program.clone());
+        assert_eq!(unlimited.try_execute(), Ok(State::Complete));
+        assert_eq!(unlimited.output.get(), Some(5));
+        assert_eq!(unlimited.memory.len(), 5001);
+
+        let mut limited = process(program);
+        limited.set_memory_limit(1000);
+        let fault = limited.try_execute().unwrap_err();
+        assert_eq!(
+            fault.kind,
+            FaultKind::MemoryLimit {
+                address: 5000,
+                limit: 1000
+            }
+        );
+        assert_eq!(
+            fault.to_string(),
+            "Address 5000 is past the memory limit of 1000 cells at address 0 (1101,2,3,5000)"
+        );
+        assert_eq!(limited.memory.len(), 7);
+
+        // Running off the end of the limit is a fault too.
+        let mut process = process(vec![1105, 1, 10]);
+        process.set_memory_limit(10);
+        assert_eq!(
+            process.try_execute().unwrap_err().kind,
+            FaultKind::MemoryLimit {
+                address: 10,
+                limit: 10
+            }
+        );
+    }
+
+    #[test]
+    fn strict() {
+        // Adds 1 and 1 and writes it to address 0, or faults in strict mode.
+        let program = vec![11101, 1, 1, 0, 99];
+        assert_eq!(run(program.clone(), false), Ok(State::Complete));
+        let fault = run(program, true).unwrap_err();
+        assert_eq!(fault.kind, FaultKind::ImmediateWrite);
+        assert_eq!(fault.instruction, vec![11101, 1, 1, 0]);
+
+        let program = vec![109, -5, 109, 10, 99];
+        assert_eq!(run(program.clone(), false), Ok(State::Complete));
+        assert_eq!(
+            run(program, true).unwrap_err().kind,
+            FaultKind::NegativeRelativeBase(-5)
+        );
+    }
+
+    #[test]
+    fn time_slices() {
+        // Counts down from 3, outputting each number.
+        let mut process = process(vec![4, 10, 1001, 10, -1, 10, 1005, 10, 0, 99, 3]);
+        assert_eq!(process.try_execute_for(2), Ok(State::Yielded));
+        assert_eq!(process.output.get(), Some(3));
+        assert_eq!(process.output.get(), None);
+        assert_eq!(process.execute_for(3), State::Yielded);
+        assert_eq!(process.output.get(), Some(2));
+        assert_eq!(process.execute(), State::Complete);
+        assert_eq!(process.instructions(), Some(10));
+        assert_eq!(Vec::from(process.output), vec![1]);
+    }
+
+    #[test]
+    fn swap_endpoints() {
+        // Outputs double whatever it reads, forever.
+        let program = Program {
+            data: vec![3, 9, 1002, 9, 2, 9, 4, 9, 1105, 1, 0].into_boxed_slice(),
+        };
+
+        let first_input = Channel::new();
+        let second_input = Rc::new(Channel::new());
+        let first_output = Channel::new();
+        let second_output = Channel::new();
+        first_input.put(1);
+        second_input.put(10);
+
+        let mut process = DynProcess::boxed("TEST", &program, &first_input, &first_output);
+        assert_eq!(process.execute(), State::Blocked);
+        assert_eq!(first_output.get(), Some(2));
+
+        process.replace_input(Box::new(second_input.clone()));
+        process.replace_output(Box::new(&second_output));
+        first_input.put(3);
+        assert_eq!(process.execute(), State::Blocked);
+
+        assert_eq!(first_output.get(), None);
+        assert_eq!(second_output.get(), Some(20));
+        assert_eq!(first_input.get(), Some(3));
+    }
+
+    #[test]
+    fn channel() {
+        let channel = Channel::new();
+        assert!(channel.is_empty());
+        assert_eq!(channel.peek(), None);
+
+        channel.put(1);
+        channel.put(2);
+        channel.put(3);
+        assert_eq!(channel.len(), 3);
+        assert_eq!(channel.peek(), Some(1));
+        assert_eq!(channel.get(), Some(1));
+        assert_eq!(channel.len(), 2);
+
+        assert_eq!(channel.drain(), vec![2, 3]);
+        assert!(channel.is_empty());
+        assert_eq!(channel.get(), None);
+    }
+
+    #[test]
+    fn tee() {
+        let program = Program {
+            data: vec![104, 1, 104, 2, 99].into_boxed_slice(),
+        };
+
+        let first = Channel::new();
+        let second = Channel::new();
+        let third = Channel::new();
+        let output = Tee::new(&first, Tee::new(&second, &third));
+        Process::new("TEST", &program, Channel::new(), output).execute();
+
+        assert_eq!(Vec::from(first), vec![1, 2]);
+        assert_eq!(Vec::from(second), vec![1, 2]);
+        assert_eq!(Vec::from(third), vec![1, 2]);
+    }
+
+    #[test]
+    fn history() {
+        // Counts address 14 up from 0 to 3, moving the relative base along
+        // each time round, then runs off the end of the program.
+        let data = vec![1001, 14, 1, 14, 109, 1, 1007, 14, 3, 15, 1005, 15, 0];
+        let mut recorded = process(data.clone());
+        recorded.record_history(5);
+        let fault = recorded.try_execute().unwrap_err();
+        assert_eq!(fault.address, 13);
+        assert_eq!(recorded.read(14), 3);
+        assert_eq!(recorded.instructions, 12);
+        assert_eq!(recorded.history().count(), 5);
+
+        // Step back to just before the last addition.
+        assert_eq!(recorded.step_back(4), 4);
+        assert_eq!(recorded.instruction_pointer, 0);
+        assert_eq!(recorded.read(14), 2);
+        assert_eq!(recorded.read(15), 1);
+        assert_eq!(recorded.relative_base, 2);
+        assert_eq!(recorded.instructions, 8);
+
+        // Running again gets back to the same place.
+        assert_eq!(recorded.try_execute().unwrap_err(), fault);
+        assert_eq!(recorded.instructions, 12);
+
+        assert_eq!(recorded.step_back(10), 5);
+        assert_eq!(recorded.instruction_pointer, 10);
+        assert_eq!(recorded.step_back(1), 0);
+
+        // Without any history nothing can be undone.
+        let mut unrecorded = process(data);
+        assert!(unrecorded.try_execute().is_err());
+        assert_eq!(unrecorded.step_back(1), 0);
+    }
+
+    #[test]
+    fn stream() {
+        // Outputs 1 and 2, then waits for input.
+        let program = Program {
+            data: vec![104, 1, 104, 2, 3, 0, 99].into_boxed_slice(),
+        };
+        let output = Channel::new();
+        let mut process = Process::new("TEST", &program, Channel::new(), &output);
+        let mut stream = process.stream(&output);
+        assert_eq!(stream.next(), Some(Ok(1)));
+        assert_eq!(stream.state(), None);
+        assert_eq!(stream.next(), Some(Ok(2)));
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.state(), Some(State::Blocked));
+        assert_eq!(process.instructions, 2);
+
+        // A fault ends the stream, after anything output before it.
+        let program = Program {
+            data: vec![104, 3, 42].into_boxed_slice(),
+        };
+        let mut process = Process::new("TEST", &program, Channel::new(), &output);
+        let values: Vec<_> = process.stream(&output).collect();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], Ok(3));
+        assert_eq!(values[1].as_ref().unwrap_err().address, 2);
+    }
+
+    // Counts each opcode run, and notes what's at address 13 after each one.
+    #[derive(Default)]
+    struct Profile {
+        opcodes: std::collections::BTreeMap<i64, usize>,
+        values: Vec<(usize, i64)>,
+    }
+
+    impl Hook for Profile {
+        fn before(&mut self, context: &Context) {
+            *self.opcodes.entry(context.opcode()).or_insert(0) += 1;
+        }
+
+        fn after(&mut self, context: &Context) {
+            self.values.push((context.instructions, context.memory[13]));
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        // Doubles address 13 until it's 8, then waits for input.
+        let mut process = process(vec![1, 13, 13, 13, 1008, 13, 8, 14, 1006, 14, 0, 3, 13, 1]);
+        let profile = Rc::new(RefCell::new(Profile::default()));
+        process.add_hook(profile.clone());
+        assert_eq!(process.execute(), State::Blocked);
+
+        let profile = profile.borrow();
+        let opcodes: Vec<_> = profile.opcodes.iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(opcodes, vec![(1, 3), (3, 1), (6, 3), (8, 3)]);
+        assert_eq!(
+            profile.values,
+            vec![
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (4, 4),
+                (5, 4),
+                (6, 4),
+                (7, 8),
+                (8, 8),
+                (9, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn dump() {
+        let mut process = process(vec![109, -2, 21101, 3, 4, 12, 204, 12, 42]);
+        assert_eq!(process.execute_for(2), State::Yielded);
+        assert_eq!(process.disassemble(0), "arb -2");
+        assert_eq!(process.disassemble(2), "add 3, 4 -> [rb+12]");
+        assert_eq!(process.disassemble(6), "out [rb+12]");
+        assert_eq!(process.disassemble(8), "?? (Unknown opcode 42)");
+
+        let expected = [
+            "Process TEST: ip 6, relative base -2, 2 instructions run",
+            "       6: out [rb+12]",
+            "       0:     109      -2   21101       3       4      12    >204      12",
+            "       8:      42       0       7",
+        ];
+        assert_eq!(process.to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "TEST: Write through an immediate mode parameter at address 4 \
+                    (11101,1,1,0)\nProcess TEST: ip 4"
+    )]
+    fn strict_execute_panics_with_dump() {
+        let mut process = process(vec![1, 0, 0, 0, 11101, 1, 1, 0, 99]);
+        process.set_strict(true);
+        process.execute();
+    }
+
+    #[test]
+    #[should_panic(expected = "TEST: Unknown opcode 42 at address 0 (42)")]
+    fn execute_panics() {
+        let program = Program {
+            data: vec![42].into_boxed_slice(),
+        };
+        Process::new("TEST", &program, Channel::new(), Channel::new()).execute();
+    }
+}
@@ -2,6 +2,7 @@ use std::io::Read;
 use std::str::FromStr;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Error(String);
 
 impl<T: ToString> From<T> for Error {
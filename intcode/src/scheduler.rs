@@ -0,0 +1,263 @@
+// Running several processes together, a slice at a time, for programs which
+// talk to each other.
+
+use super::process::{Execute, State};
+
+struct Task<'a> {
+    id: usize,
+    process: &'a mut dyn Execute,
+    // How many time slices the process gets each round.
+    priority: usize,
+}
+
+// Runs its processes in turn until they've all completed or a callback asks
+// it to stop.  Each round every process which hasn't completed gets its time
+// slice once for each level of its priority.
+pub struct Scheduler<'a> {
+    tasks: Vec<Task<'a>>,
+    next_id: usize,
+    time_slice: usize,
+    on_round: Option<Box<dyn FnMut() -> bool + 'a>>,
+    on_idle: Option<Box<dyn FnMut() -> bool + 'a>>,
+    on_complete: Option<Box<dyn FnMut(usize) + 'a>>,
+}
+
+impl<'a> Scheduler<'a> {
+    // A scheduler which lets each process run until it blocks.
+    pub fn new() -> Self {
+        Scheduler {
+            tasks: vec![],
+            next_id: 0,
+            time_slice: usize::MAX,
+            on_round: None,
+            on_idle: None,
+            on_complete: None,
+        }
+    }
+
+    // Make each process yield after running this many instructions.
+    pub fn time_slice(mut self, max_instructions: usize) -> Self {
+        self.time_slice = max_instructions;
+        self
+    }
+
+    // Called after every round, which can stop the scheduler by returning
+    // true.
+    pub fn on_round(mut self, callback: impl FnMut() -> bool + 'a) -> Self {
+        self.on_round = Some(Box::new(callback));
+        self
+    }
+
+    // Called after a round in which every process was blocked waiting for
+    // input without running anything, which can stop the scheduler by
+    // returning true.  Without this the scheduler stops, as nothing else
+    // would make any progress.
+    pub fn on_idle(mut self, callback: impl FnMut() -> bool + 'a) -> Self {
+        self.on_idle = Some(Box::new(callback));
+        self
+    }
+
+    // Called with the ID of each process as it completes.
+    pub fn on_complete(mut self, callback: impl FnMut(usize) + 'a) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    // Add a process with the normal priority, returning its ID.
+    pub fn add(&mut self, process: &'a mut dyn Execute) -> usize {
+        self.add_with_priority(process, 1)
+    }
+
+    pub fn add_with_priority(&mut self, process: &'a mut dyn Execute, priority: usize) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push(Task {
+            id,
+            process,
+            priority: priority.max(1),
+        });
+        id
+    }
+
+    // Run a round, returning whether every process was blocked without
+    // running anything.
+    fn round(&mut self) -> bool {
+        let mut idle = true;
+        let mut remaining = vec![];
+        for task in self.tasks.drain(..) {
+            let before = task.process.instructions();
+            let mut state = State::Yielded;
+            for _ in 0..task.priority {
+                state = task.process.execute_for(self.time_slice);
+                if state != State::Yielded {
+                    break;
+                }
+            }
+
+            match state {
+                State::Complete => {
+                    if let Some(callback) = self.on_complete.as_mut() {
+                        callback(task.id);
+                    }
+                }
+                State::Blocked => {
+                    if before.is_none() || task.process.instructions() != before {
+                        idle = false;
+                    }
+                    remaining.push(task);
+                }
+                State::Yielded => {
+                    idle = false;
+                    remaining.push(task);
+                }
+            }
+        }
+        self.tasks = remaining;
+        idle
+    }
+
+    pub fn run(mut self) {
+        while !self.tasks.is_empty() {
+            let idle = self.round();
+
+            if let Some(callback) = self.on_round.as_mut() {
+                if callback() {
+                    break;
+                }
+            }
+
+            if idle && !self.tasks.is_empty() {
+                let stop = match self.on_idle.as_mut() {
+                    Some(callback) => callback(),
+                    None => true,
+                };
+                if stop {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Default for Scheduler<'a> {
+    fn default() -> Self {
+        Scheduler::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::{Channel, Input, Output, Process};
+    use crate::program::Program;
+    use std::cell::RefCell;
+
+    // Adds one to each number it reads and outputs it, forever.
+    fn increment() -> Program {
+        Program {
+            data: vec![3, 9, 1001, 9, 1, 9, 4, 9, 1105, 1, 0].into_boxed_slice(),
+        }
+    }
+
+    // Counts how many instructions it's run in a loop, never blocking.
+    struct Counter(usize);
+
+    impl Execute for Counter {
+        fn execute_for(&mut self, max_instructions: usize) -> State {
+            self.0 += max_instructions;
+            State::Yielded
+        }
+    }
+
+    #[test]
+    fn pipeline() {
+        let channels: Vec<Channel<i64>> = (0..4).map(|_| Channel::new()).collect();
+        let program = increment();
+        let mut processes: Vec<_> = channels
+            .windows(2)
+            .enumerate()
+            .map(|(index, channels)| Process::new(index, &program, &channels[0], &channels[1]))
+            .collect();
+
+        channels[0].put(1);
+        channels[0].put(10);
+
+        let mut scheduler = Scheduler::new();
+        for process in processes.iter_mut() {
+            scheduler.add(process);
+        }
+        scheduler.run();
+
+        assert_eq!(channels[3].get(), Some(4));
+        assert_eq!(channels[3].get(), Some(13));
+        assert_eq!(channels[3].get(), None);
+    }
+
+    #[test]
+    fn idle() {
+        let input = Channel::new();
+        let output = Channel::new();
+        let program = increment();
+        let mut process = Process::new("TEST", &program, &input, &output);
+
+        let mut values = vec![3, 2, 1].into_iter();
+        let mut scheduler = Scheduler::new().on_idle(|| match values.next() {
+            Some(value) => {
+                input.put(value);
+                false
+            }
+            None => true,
+        });
+        scheduler.add(&mut process);
+        scheduler.run();
+
+        assert_eq!(Vec::from(output), vec![4, 3, 2]);
+    }
+
+    #[test]
+    fn priorities() {
+        let mut low = Counter(0);
+        let mut high = Counter(0);
+
+        let mut rounds = 0;
+        let mut scheduler = Scheduler::new().time_slice(10).on_round(|| {
+            rounds += 1;
+            rounds == 5
+        });
+        scheduler.add(&mut low);
+        scheduler.add_with_priority(&mut high, 3);
+        scheduler.run();
+
+        assert_eq!(low.0, 50);
+        assert_eq!(high.0, 150);
+    }
+
+    #[test]
+    fn completion() {
+        let programs: Vec<Program> = [vec![99], vec![104, 1, 104, 2, 99], vec![3, 0, 99]]
+            .iter()
+            .map(|data| Program {
+                data: data.clone().into_boxed_slice(),
+            })
+            .collect();
+        let output = Channel::new();
+        let mut processes: Vec<_> = programs
+            .iter()
+            .enumerate()
+            .map(|(index, program)| Process::new(index, program, Channel::new(), &output))
+            .collect();
+
+        let completed = RefCell::new(vec![]);
+        let mut scheduler = Scheduler::new()
+            .time_slice(1)
+            .on_complete(|id| completed.borrow_mut().push(id));
+        for process in processes.iter_mut() {
+            scheduler.add(process);
+        }
+        scheduler.run();
+
+        // The last process never gets any input, so never completes.
+        assert_eq!(completed.into_inner(), vec![0, 1]);
+        assert_eq!(Vec::from(output), vec![1, 2]);
+    }
+}
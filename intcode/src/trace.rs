@@ -0,0 +1,173 @@
+// Writing out every instruction a program runs, one per line, to see exactly
+// what it did.
+//
+// Each line has how many instructions had been run before, the address of the
+// instruction, the relative base and the instruction itself, like
+// "12 @4 rb=0 add 1, [7] -> [7]".
+
+use std::io::Write;
+
+use super::process::{Context, Hook};
+
+pub struct Tracer<W> {
+    writer: W,
+    error: Option<String>,
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn new(writer: W) -> Self {
+        Tracer {
+            writer,
+            error: None,
+        }
+    }
+
+    // Tracing can't stop the program, so the first thing which went wrong
+    // writing the trace is kept until now.
+    pub fn finish(&mut self) -> Result<(), String> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.writer
+            .flush()
+            .map_err(|error| format!("Can't write trace: {}", error))
+    }
+}
+
+impl<W: Write> Hook for Tracer<W> {
+    fn before(&mut self, context: &Context) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = writeln!(
+            self.writer,
+            "{} @{} rb={} {}",
+            context.instructions,
+            context.address,
+            context.relative_base,
+            context.disassemble()
+        );
+        if let Err(error) = result {
+            self.error = Some(format!("Can't write trace: {}", error));
+        }
+    }
+}
+
+// How many instructions had been run before a line of a trace.  This isn't
+// the same as the line number, as an instruction which blocks waiting for
+// input is traced again when it's run.
+fn instructions(line: &str) -> Option<usize> {
+    line.split(' ').next()?.parse().ok()
+}
+
+// Describe where two traces first differ, with the `context` lines before
+// that which they agree on and the `context` lines from each after it, or
+// None if they're the same.  Lines only in the first trace are marked with
+// "-" and lines only in the second with "+", as in a diff.
+pub fn diff(first: &str, second: &str, context: usize) -> Option<String> {
+    let first: Vec<_> = first.lines().collect();
+    let second: Vec<_> = second.lines().collect();
+    let same = first
+        .iter()
+        .zip(second.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if same == first.len() && same == second.len() {
+        return None;
+    }
+
+    let diverging = first.get(same).or_else(|| second.get(same)).unwrap();
+    let after = match instructions(diverging) {
+        Some(instructions) => format!("{} instructions", instructions),
+        None => format!("{} lines", same),
+    };
+    let mut report = match (first.get(same), second.get(same)) {
+        (Some(_), Some(_)) => format!("The traces differ after {}\n", after),
+        (Some(_), None) => format!("The second trace ends after {}\n", after),
+        _ => format!("The first trace ends after {}\n", after),
+    };
+    for line in &first[same.saturating_sub(context)..same] {
+        report += &format!("  {}\n", line);
+    }
+    for (marker, trace) in [("-", &first), ("+", &second)].iter() {
+        for line in trace.iter().skip(same).take(context.max(1)) {
+            report += &format!("{} {}\n", marker, line);
+        }
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::{Channel, Output, Process, State};
+    use crate::program::Program;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn trace() {
+        let program = Program::parse("109,5,203,2,204,2,99".as_bytes()).unwrap();
+        let input = Channel::new();
+        let tracer = Rc::new(RefCell::new(Tracer::new(vec![])));
+        let mut process = Process::new("TEST", &program, &input, Channel::new());
+        process.add_hook(tracer.clone());
+
+        assert_eq!(process.execute(), State::Blocked);
+        input.put(7);
+        assert_eq!(process.execute(), State::Complete);
+        drop(process);
+
+        let mut tracer = Rc::try_unwrap(tracer).ok().unwrap().into_inner();
+        tracer.finish().unwrap();
+        let expected = [
+            "0 @0 rb=0 arb 5",
+            "1 @2 rb=5 in -> [rb+2]",
+            "1 @2 rb=5 in -> [rb+2]",
+            "2 @4 rb=5 out [rb+2]",
+            "3 @6 rb=5 hlt",
+            "",
+        ];
+        assert_eq!(
+            String::from_utf8(tracer.writer).unwrap(),
+            expected.join("\n")
+        );
+    }
+
+    #[test]
+    fn differences() {
+        let trace =
+            "0 @0 rb=0 arb 5\n1 @2 rb=5 in -> [rb+2]\n2 @4 rb=5 out [rb+2]\n3 @6 rb=5 hlt\n";
+        assert_eq!(diff(trace, trace, 2), None);
+
+        let changed = trace.replace("out", "mul");
+        assert_eq!(
+            diff(trace, &changed, 1).unwrap(),
+            "The traces differ after 2 instructions\n  1 @2 rb=5 in -> [rb+2]\n\
+             - 2 @4 rb=5 out [rb+2]\n+ 2 @4 rb=5 mul [rb+2]\n"
+        );
+        assert_eq!(diff(trace, &changed, 5).unwrap().lines().count(), 7);
+
+        let shorter = &trace[..38];
+        assert_eq!(
+            diff(trace, shorter, 1).unwrap(),
+            "The second trace ends after 2 instructions\n  1 @2 rb=5 in -> [rb+2]\n\
+             - 2 @4 rb=5 out [rb+2]\n"
+        );
+        assert!(diff(shorter, trace, 0)
+            .unwrap()
+            .starts_with("The first trace ends after 2 instructions\n+ 2 @4"));
+
+        // The input instruction was traced twice as the program waited for
+        // input the first time it ran it.
+        let input = "1 @2 rb=5 in -> [rb+2]\n";
+        let blocked = trace.replace(input, &input.repeat(2));
+        assert!(diff(&blocked, &blocked.replace("out", "mul"), 0)
+            .unwrap()
+            .starts_with("The traces differ after 2 instructions\n"));
+        assert!(diff("a\nb\n", "a\nc\n", 0)
+            .unwrap()
+            .starts_with("The traces differ after 1 lines\n"));
+    }
+}
@@ -0,0 +1,458 @@
+// Record every value passed in and out of an Intcode program, so that a run
+// can be checked against the program again later without whatever was
+// driving it.
+//
+// A transcript has a line for each value, "in" for one the program read and
+// "out" for one it wrote, after "set" lines for any memory changed before it
+// started, and ends with "halt" if the program finished.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use super::process::{Input, Output, Process, State};
+use super::program::Program;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    In(i64),
+    Out(i64),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    pub patches: Vec<(usize, i64)>,
+    pub events: Vec<Event>,
+    pub halted: bool,
+}
+
+impl Transcript {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read_to_string(path)
+            .map_err(|error| format!("Can't read transcript: {}", error))?
+            .parse()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_string())
+            .map_err(|error| format!("Can't write transcript: {}", error))
+    }
+
+    // Everything the program wrote.
+    pub fn outputs(&self) -> impl Iterator<Item = i64> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            Event::Out(value) => Some(*value),
+            Event::In(_) => None,
+        })
+    }
+
+    // Everything the program read.
+    pub fn inputs(&self) -> impl Iterator<Item = i64> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            Event::In(value) => Some(*value),
+            Event::Out(_) => None,
+        })
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (address, value) in self.patches.iter() {
+            writeln!(f, "set {} {}", address, value)?;
+        }
+        for event in self.events.iter() {
+            match event {
+                Event::In(value) => writeln!(f, "in {}", value)?,
+                Event::Out(value) => writeln!(f, "out {}", value)?,
+            }
+        }
+        if self.halted {
+            writeln!(f, "halt")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut transcript = Transcript::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let invalid = || format!("Invalid transcript line {}: {:?}", number + 1, line);
+            let words: Vec<_> = line.split_whitespace().collect();
+            match words[..] {
+                [] => {}
+                ["halt"] => transcript.halted = true,
+                ["set", address, value] => transcript.patches.push((
+                    address.parse().map_err(|_| invalid())?,
+                    value.parse().map_err(|_| invalid())?,
+                )),
+                ["in", value] => transcript
+                    .events
+                    .push(Event::In(value.parse().map_err(|_| invalid())?)),
+                ["out", value] => transcript
+                    .events
+                    .push(Event::Out(value.parse().map_err(|_| invalid())?)),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(transcript)
+    }
+}
+
+#[derive(Default)]
+pub struct Recorder {
+    transcript: RefCell<Transcript>,
+}
+
+impl Recorder {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Recorder::default())
+    }
+
+    // Note a change made to the program's memory before it runs.
+    pub fn set(&self, address: usize, value: i64) {
+        self.transcript.borrow_mut().patches.push((address, value));
+    }
+
+    // Note that the program finished.
+    pub fn halt(&self) {
+        self.transcript.borrow_mut().halted = true;
+    }
+
+    fn record(&self, event: Event) {
+        self.transcript.borrow_mut().events.push(event);
+    }
+
+    pub fn transcript(&self) -> Transcript {
+        self.transcript.borrow().clone()
+    }
+}
+
+pub struct RecordingInput<I> {
+    input: I,
+    recorder: Rc<Recorder>,
+}
+
+impl<I> RecordingInput<I> {
+    pub fn new(input: I, recorder: &Rc<Recorder>) -> Self {
+        RecordingInput {
+            input,
+            recorder: recorder.clone(),
+        }
+    }
+}
+
+impl<I: Input<i64>> Input<i64> for RecordingInput<I> {
+    fn get(&self) -> Option<i64> {
+        let value = self.input.get();
+        if let Some(value) = value {
+            self.recorder.record(Event::In(value));
+        }
+        value
+    }
+}
+
+pub struct RecordingOutput<O> {
+    output: O,
+    recorder: Rc<Recorder>,
+}
+
+impl<O> RecordingOutput<O> {
+    pub fn new(output: O, recorder: &Rc<Recorder>) -> Self {
+        RecordingOutput {
+            output,
+            recorder: recorder.clone(),
+        }
+    }
+}
+
+impl<O: Output<i64>> Output<i64> for RecordingOutput<O> {
+    fn put(&self, value: i64) {
+        self.recorder.record(Event::Out(value));
+        self.output.put(value)
+    }
+}
+
+// Feeds the program the inputs from a transcript, and checks it writes the
+// same outputs in the same order.
+pub struct Playback {
+    patches: Vec<(usize, i64)>,
+    halted: bool,
+    events: RefCell<VecDeque<Event>>,
+    replayed: Cell<usize>,
+    mismatch: RefCell<Option<String>>,
+}
+
+impl Playback {
+    pub fn new(transcript: Transcript) -> Self {
+        Playback {
+            patches: transcript.patches,
+            halted: transcript.halted,
+            events: RefCell::new(transcript.events.into()),
+            replayed: Cell::new(0),
+            mismatch: RefCell::new(None),
+        }
+    }
+
+    fn next(&self, actual: Event) -> Option<Event> {
+        if self.mismatch.borrow().is_some() {
+            return None;
+        }
+
+        let expected = self.events.borrow_mut().pop_front();
+        let matches = match (expected, actual) {
+            (Some(Event::In(_)), Event::In(_)) => true,
+            (Some(expected), actual) => expected == actual,
+            // The recording stopped while the program was waiting for input.
+            (None, Event::In(_)) => return None,
+            (None, Event::Out(_)) => false,
+        };
+
+        if matches {
+            self.replayed.set(self.replayed.get() + 1);
+            expected
+        } else {
+            let expected = expected.map_or("the end".to_string(), |event| format!("{:?}", event));
+            let actual = match actual {
+                Event::In(_) => "a read".to_string(),
+                event => format!("{:?}", event),
+            };
+            *self.mismatch.borrow_mut() = Some(format!(
+                "Expected {} after {} values, but got {}",
+                expected,
+                self.replayed.get(),
+                actual
+            ));
+            None
+        }
+    }
+
+    // Run the program against the transcript, returning how many values were
+    // checked.
+    pub fn replay(&self, program: &Program) -> Result<usize, String> {
+        let mut process = Process::new("Replay".to_string(), program, self, self);
+        for &(address, value) in self.patches.iter() {
+            process.set(address, value);
+        }
+        let state = process.execute();
+
+        if let Some(mismatch) = self.mismatch.borrow_mut().take() {
+            return Err(mismatch);
+        }
+        let remaining = self.events.borrow().len();
+        match state {
+            State::Complete if remaining > 0 => Err(format!(
+                "The program finished with {} values of the transcript left",
+                remaining
+            )),
+            State::Blocked if self.halted => Err(format!(
+                "The program wanted more input after {} values instead of finishing",
+                self.replayed.get()
+            )),
+            State::Complete | State::Blocked | State::Yielded => Ok(self.replayed.get()),
+        }
+    }
+}
+
+impl Input<i64> for Playback {
+    fn get(&self) -> Option<i64> {
+        match self.next(Event::In(0)) {
+            Some(Event::In(value)) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Output<i64> for Playback {
+    fn put(&self, value: i64) {
+        self.next(Event::Out(value));
+    }
+}
+
+// Stands in for a program, writing what it wrote in a transcript and checking
+// it's given the same inputs, so that whatever drives the program can be tried
+// out without running it.
+pub struct Stub<I, O> {
+    events: VecDeque<Event>,
+    halted: bool,
+    input: I,
+    output: O,
+    replayed: usize,
+    divergence: Option<String>,
+}
+
+impl<I: Input<i64>, O: Output<i64>> Stub<I, O> {
+    pub fn new(transcript: Transcript, input: I, output: O) -> Self {
+        Stub {
+            events: transcript.events.into(),
+            halted: transcript.halted,
+            input,
+            output,
+            replayed: 0,
+            divergence: None,
+        }
+    }
+
+    // Like running the program: write everything up to the next input it
+    // needs.  Once the stub's been given an input the program wasn't, it
+    // stops for good.
+    pub fn execute(&mut self) -> State {
+        if self.divergence.is_some() {
+            return State::Blocked;
+        }
+
+        while let Some(event) = self.events.pop_front() {
+            match event {
+                Event::Out(value) => self.output.put(value),
+                Event::In(expected) => match self.input.get() {
+                    None => {
+                        self.events.push_front(event);
+                        return State::Blocked;
+                    }
+                    Some(value) if value != expected => {
+                        self.divergence = Some(format!(
+                            "Given {} where the transcript has {} after {} values",
+                            value, expected, self.replayed
+                        ));
+                        return State::Blocked;
+                    }
+                    Some(_) => {}
+                },
+            }
+            self.replayed += 1;
+        }
+
+        if self.halted {
+            State::Complete
+        } else {
+            State::Blocked
+        }
+    }
+
+    // Where the inputs stopped matching the transcript, if they have.
+    pub fn divergence(&self) -> Option<&str> {
+        self.divergence.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::Channel;
+
+    // Adds up the numbers it reads until it reads a zero, writing the total
+    // so far after each one.
+    const TOTAL: &str = "3,100,1006,100,14,1,100,101,101,4,101,1105,1,0,99";
+
+    fn record(inputs: &[i64]) -> Transcript {
+        let program = Program::parse(TOTAL.as_bytes()).unwrap();
+        let input = Channel::new();
+        for value in inputs {
+            input.put(*value);
+        }
+        let output = Channel::new();
+
+        let recorder = Recorder::new();
+        let mut process = Process::new(
+            "TEST".to_string(),
+            &program,
+            RecordingInput::new(&input, &recorder),
+            RecordingOutput::new(&output, &recorder),
+        );
+        if process.execute() == State::Complete {
+            recorder.halt();
+        }
+        drop(process);
+
+        recorder.transcript()
+    }
+
+    #[test]
+    fn recording() {
+        let transcript = record(&[3, 4, 0]);
+        use Event::*;
+        assert_eq!(transcript.events, [In(3), Out(3), In(4), Out(7), In(0)]);
+        assert_eq!(transcript.outputs().collect::<Vec<_>>(), [3, 7]);
+        assert_eq!(transcript.inputs().collect::<Vec<_>>(), [3, 4, 0]);
+
+        let text = transcript.to_string();
+        assert_eq!(text, "in 3\nout 3\nin 4\nout 7\nin 0\nhalt\n");
+        assert_eq!(text.parse::<Transcript>(), Ok(transcript));
+        assert_eq!(
+            "set 0 2\nout -1\n".parse::<Transcript>().unwrap().patches,
+            [(0, 2)]
+        );
+        assert!("in".parse::<Transcript>().is_err());
+        assert!("out x".parse::<Transcript>().is_err());
+    }
+
+    #[test]
+    fn playback() {
+        let program = Program::parse(TOTAL.as_bytes()).unwrap();
+        let transcript = record(&[3, 4, 0]);
+        assert_eq!(Playback::new(transcript.clone()).replay(&program), Ok(5));
+
+        let mut wrong = transcript.clone();
+        wrong.events[3] = Event::Out(8);
+        assert_eq!(
+            Playback::new(wrong).replay(&program),
+            Err("Expected Out(8) after 3 values, but got Out(7)".to_string())
+        );
+
+        let mut short = transcript.clone();
+        short.events.pop();
+        short.halted = false;
+        assert_eq!(Playback::new(short.clone()).replay(&program), Ok(4));
+        short.halted = true;
+        assert!(Playback::new(short).replay(&program).is_err());
+
+        let mut shorter = transcript.clone();
+        shorter.events.truncate(3);
+        assert_eq!(
+            Playback::new(shorter).replay(&program),
+            Err("Expected the end after 3 values, but got Out(7)".to_string())
+        );
+
+        let mut long = transcript;
+        long.events.push(Event::Out(7));
+        assert!(Playback::new(long).replay(&program).is_err());
+    }
+
+    #[test]
+    fn stub() {
+        let transcript = record(&[3, 4, 0]);
+
+        let input = Channel::new();
+        let output = Channel::new();
+        let mut stub = Stub::new(transcript.clone(), &input, &output);
+        assert_eq!(stub.execute(), State::Blocked);
+        assert_eq!(output.get(), None);
+
+        input.put(3);
+        assert_eq!(stub.execute(), State::Blocked);
+        assert_eq!(output.get(), Some(3));
+        input.put(4);
+        input.put(0);
+        assert_eq!(stub.execute(), State::Complete);
+        assert_eq!(output.get(), Some(7));
+        assert_eq!(stub.divergence(), None);
+
+        let mut stub = Stub::new(transcript, &input, &output);
+        input.put(3);
+        input.put(5);
+        assert_eq!(stub.execute(), State::Blocked);
+        assert_eq!(
+            stub.divergence(),
+            Some("Given 5 where the transcript has 4 after 2 values")
+        );
+        assert_eq!(stub.execute(), State::Blocked);
+    }
+}